@@ -0,0 +1,193 @@
+//! CPI interface for programs that deposit into the psol privacy pool on behalf of users,
+//! such as DEX routers and payroll programs.
+//!
+//! Build [`DepositFor`] from the calling instruction's accounts, compute the deposit's
+//! commitment with [`next_commitment`], and call [`deposit_for`]. The deposit waits in the
+//! beneficiary's `PendingDeposits` record until they claim it, which the first deposit
+//! creates at the depositor's expense. The depositor must
+//! sign: pass the user's signature through, or sign for a system-owned PDA of the calling
+//! program with `CpiContext::new_with_signer`. While a pool enforces
+//! `CallPolicy::ApprovedCallersOnly`, the calling program must be on its caller allowlist.
+//!
+//! The `deposit_for` accounts and arguments are part of psol's stable interface: later
+//! versions only append accounts and keep existing ones in place. `pending_deposits` was
+//! appended this way.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use psol::state::{PendingDeposits, PrivacyPool, StorageBackend};
+
+pub use psol;
+/// Accounts of a `deposit_for` CPI. Optional accounts left `None` are passed as psol's
+/// program ID.
+pub use psol::cpi::accounts::DepositFor;
+pub use psol::ID;
+
+pub const POOL_SEED: &[u8] = b"privacy_pool";
+pub const VAULT_SEED: &[u8] = b"vault";
+pub const COMMITMENT_SEED: &[u8] = b"commitment";
+pub const PRIVACY_ACCOUNT_SEED: &[u8] = b"privacy";
+pub const CALLER_ALLOWLIST_SEED: &[u8] = b"caller_allowlist";
+pub const PENDING_DEPOSITS_SEED: &[u8] = b"pending_deposits";
+
+/// psol PDAs used by `deposit_for`.
+pub mod pda {
+    use super::*;
+
+    pub fn pool() -> Pubkey {
+        Pubkey::find_program_address(&[POOL_SEED], &ID).0
+    }
+
+    pub fn vault(pool: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[VAULT_SEED, pool.as_ref()], &ID).0
+    }
+
+    pub fn commitment_registry(pool: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[COMMITMENT_SEED, pool.as_ref()], &ID).0
+    }
+
+    pub fn privacy_account(owner: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[PRIVACY_ACCOUNT_SEED, owner.as_ref()], &ID).0
+    }
+
+    pub fn caller_allowlist(pool: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[CALLER_ALLOWLIST_SEED, pool.as_ref()], &ID).0
+    }
+
+    pub fn pending_deposits(privacy_account: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[PENDING_DEPOSITS_SEED, privacy_account.as_ref()], &ID).0
+    }
+}
+
+/// Arguments of psol's `deposit_for`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DepositForArgs {
+    pub amount: u64,
+    /// The deposit's commitment, from [`next_commitment`].
+    pub commitment: [u8; 32],
+    /// `amount` encrypted to the beneficiary's `view_key`.
+    pub encrypted_amount: [u8; 64],
+    pub proof: Vec<u8>,
+    /// Optional memo sealed to the beneficiary's `view_key`.
+    pub memo: Option<Vec<u8>>,
+}
+
+/// The commitment `deposit_for` expects for `amount` into `privacy_account`, given its
+/// `PendingDeposits` record if one exists. Computed from public state, so callers need
+/// none of the owner's secrets.
+pub fn next_commitment(
+    privacy_account: &Pubkey,
+    pending: Option<&PendingDeposits>,
+    amount: u64,
+) -> [u8; 32] {
+    psol::crypto::pedersen_commit(amount, &PendingDeposits::head(privacy_account, pending))
+}
+
+/// Calls psol's `deposit_for`. Use `CpiContext::new_with_signer` when the depositor is a
+/// PDA of the calling program.
+pub fn deposit_for<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, DepositFor<'info>>,
+    args: DepositForArgs,
+) -> Result<()> {
+    psol::cpi::deposit_for(
+        ctx,
+        args.amount,
+        args.commitment,
+        args.encrypted_amount,
+        args.proof,
+        args.memo,
+    )
+}
+
+/// Addresses of a `deposit_for` for `beneficiary`'s privacy account, with the commitment
+/// store matching the pool's backend. Off-chain clients extend these with their own
+/// program's accounts.
+pub fn deposit_for_accounts(
+    pool: &PrivacyPool,
+    beneficiary: &Pubkey,
+    depositor: &Pubkey,
+    with_caller_allowlist: bool,
+) -> psol::accounts::DepositFor {
+    let pool_key = pda::pool();
+    let privacy_account = pda::privacy_account(beneficiary);
+    let (commitment_registry, commitment_tree) = match pool.storage_backend {
+        StorageBackend::Accounts => (Some(pda::commitment_registry(&pool_key)), None),
+        StorageBackend::Compressed => (None, Some(pool.commitment_tree)),
+    };
+    psol::accounts::DepositFor {
+        pool: pool_key,
+        vault: pda::vault(&pool_key),
+        commitment_registry,
+        commitment_tree,
+        privacy_account,
+        depositor: *depositor,
+        instructions_sysvar: sysvar::instructions::ID,
+        caller_allowlist: with_caller_allowlist.then(|| pda::caller_allowlist(&pool_key)),
+        system_program: anchor_lang::system_program::ID,
+        pending_deposits: pda::pending_deposits(&privacy_account),
+    }
+}
+
+/// A top-level `deposit_for` instruction, for depositors calling psol directly.
+pub fn deposit_for_instruction(
+    accounts: psol::accounts::DepositFor,
+    args: DepositForArgs,
+) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: psol::instruction::DepositFor {
+            amount: args.amount,
+            commitment: args.commitment,
+            encrypted_amount: args.encrypted_amount,
+            proof: args.proof,
+            memo: args.memo,
+        }
+        .data(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Discriminator;
+
+    #[test]
+    fn deposit_for_instruction_targets_the_beneficiary() {
+        let mut pool = PrivacyPool::try_from_slice(&[0u8; PrivacyPool::SIZE - 8]).unwrap();
+        pool.storage_backend = StorageBackend::Accounts;
+        let (owner, depositor) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let account = pda::privacy_account(&owner);
+
+        // The caller derives the commitment from public state alone; the first deposit
+        // extends the account's address.
+        let commitment = next_commitment(&account, None, 40);
+        assert_eq!(commitment, psol::crypto::pedersen_commit(40, &account.to_bytes()));
+        // A commitment computed before another deposit landed no longer matches.
+        let mut pending =
+            PendingDeposits::try_from_slice(&[0u8; PendingDeposits::SIZE - 8]).unwrap();
+        pending.commitment = commitment;
+        assert_ne!(next_commitment(&account, Some(&pending), 40), commitment);
+
+        let args = DepositForArgs {
+            amount: 40,
+            commitment,
+            encrypted_amount: [0u8; 64],
+            proof: vec![1u8; 64],
+            memo: None,
+        };
+        let accounts = deposit_for_accounts(&pool, &owner, &depositor, false);
+        let ix = deposit_for_instruction(accounts, args);
+        assert_eq!(ix.program_id, ID);
+        assert_eq!(ix.data[..8], psol::instruction::DepositFor::DISCRIMINATOR);
+        let pool_key = pda::pool();
+        assert_eq!(ix.accounts[2].pubkey, pda::commitment_registry(&pool_key));
+        assert_eq!(ix.accounts[3].pubkey, ID); // no commitment tree
+        assert_eq!(ix.accounts[4].pubkey, pda::privacy_account(&owner));
+        assert!(ix.accounts[5].pubkey == depositor && ix.accounts[5].is_signer);
+        assert_eq!(ix.accounts[7].pubkey, ID); // no caller allowlist
+        assert_eq!(ix.accounts[9].pubkey, pda::pending_deposits(&account));
+    }
+}
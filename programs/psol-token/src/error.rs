@@ -43,4 +43,61 @@ pub enum ErrorCode {
 
     #[msg("Invalid nullifier.")]
     InvalidNullifier,
+
+    #[msg("Pool does not match the token config.")]
+    InvalidPool,
+
+    #[msg("psol program does not match the token config.")]
+    InvalidProgram,
+
+    #[msg("Withdrawal limit of the pool exceeded.")]
+    WithdrawalLimitExceeded,
+
+    #[msg("Account layout version is newer than this program understands.")]
+    UnsupportedAccountVersion,
+
+    #[msg("Account is already on the current layout.")]
+    AccountAlreadyCurrent,
+
+    #[msg("pSOL mint does not match the token config.")]
+    InvalidMint,
+
+    #[msg("pSOL supply and pool total_locked are out of step.")]
+    BackingInvariantViolated,
+
+    #[msg("Pool has an auditor; the transfer must carry the auditor ciphertext.")]
+    AuditorCiphertextRequired,
+
+    #[msg("Address is on the pSOL denylist.")]
+    AddressDenied,
+
+    #[msg("The config's denylist account must be supplied.")]
+    DenylistRequired,
+
+    #[msg("Denylist is full.")]
+    DenylistFull,
+
+    #[msg("Swaps are paused until the pSOL supply is backed again.")]
+    SwapsPaused,
+
+    #[msg("Rewards need outstanding pSOL to accrue to.")]
+    NoOutstandingSupply,
+
+    #[msg("Token metadata name, symbol or URI is too long.")]
+    MetadataTooLong,
+
+    #[msg("Legacy config still has shielded pSOL; unshield it before migrating.")]
+    ShieldedSupplyOutstanding,
+
+    #[msg("Swap output after fees is below the caller's minimum.")]
+    SlippageExceeded,
+
+    #[msg("Swap deadline slot has passed.")]
+    SwapDeadlineExceeded,
+
+    #[msg("swap_to_sol cannot follow swap_to_psol in the same transaction.")]
+    DepositThenWithdraw,
+
+    #[msg("The mint has token metadata; the token metadata program must be supplied.")]
+    MetadataProgramRequired,
 }
@@ -1,22 +1,35 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::program_option::COption;
-use anchor_spl::token::{self, Burn, Mint, MintTo, SetAuthority, Token, TokenAccount};
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::system_program::{self, Transfer};
+use anchor_lang::Discriminator;
+use anchor_spl::metadata::mpl_token_metadata::types::DataV2;
+use anchor_spl::metadata::{self, CreateMetadataAccountsV3, Metadata, UpdateMetadataAccountsV2};
+use anchor_spl::token::{
+    self, Burn, FreezeAccount, Mint, MintTo, SetAuthority, ThawAccount, Token, TokenAccount,
+};
 use psol::program::Psol;
-use psol::{
-    self, state::CommitmentRegistry, state::NullifierRegistry, state::PrivacyAccount,
-    state::PrivacyPool,
+use psol::merkle::NonMembershipProof;
+use psol::crypto;
+use psol::guard;
+use psol::state::{
+    layout_version, rewrite_account, CallPolicy, Operation, PrivacyAccount, PrivacyPool,
+    StakeStrategy,
 };
 
 pub mod error;
 pub mod state;
 
 pub use error::ErrorCode;
-use state::Config;
+use state::{Config, ConfigV0, Denylist, ExchangeRate, TokenPrivacyLink};
 
 declare_id!("CgERkyXGARsLFqgBHyTk4Njyt7nyq9foJfmV7ptadYLy");
 
 const MINT_AUTH_SEED: &[u8] = b"psol_mint_auth";
 const CONFIG_SEED: &[u8] = b"psol_config";
+const PRIVACY_LINK_SEED: &[u8] = b"privacy_link";
+const DENYLIST_SEED: &[u8] = b"denylist";
+const WRAPPER_SEED: &[u8] = b"wrapper";
 
 #[program]
 pub mod psol_token {
@@ -24,12 +37,13 @@ pub mod psol_token {
 
     pub fn initialize_token(ctx: Context<InitializeToken>) -> Result<()> {
         let config = &mut ctx.accounts.config;
+        config.version = Config::VERSION;
         config.admin = ctx.accounts.admin.key();
         config.pool = ctx.accounts.pool.key();
         config.psol_mint = ctx.accounts.psol_mint.key();
         config.psol_program = ctx.accounts.psol_program.key();
-        config.mint_authority_bump = *ctx.bumps.get("mint_authority").unwrap();
-        config.bump = *ctx.bumps.get("config").unwrap();
+        config.mint_authority_bump = ctx.bumps.mint_authority;
+        config.bump = ctx.bumps.config;
 
         if ctx.accounts.psol_mint.mint_authority != COption::Some(ctx.accounts.mint_authority.key())
         {
@@ -47,10 +61,239 @@ pub mod psol_token {
             )?;
         }
 
+        // Mints created without a freeze authority cannot gain one; the freeze
+        // instructions then fail in the token program.
+        let freeze_authority = ctx.accounts.psol_mint.freeze_authority;
+        if freeze_authority.is_some()
+            && freeze_authority != COption::Some(ctx.accounts.mint_authority.key())
+        {
+            let cpi_ctx = CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                SetAuthority {
+                    current_authority: ctx.accounts.admin.to_account_info(),
+                    account_or_mint: ctx.accounts.psol_mint.to_account_info(),
+                },
+            );
+            token::set_authority(
+                cpi_ctx,
+                anchor_spl::token::spl_token::instruction::AuthorityType::FreezeAccount,
+                Some(ctx.accounts.mint_authority.key()),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Admin-only; freezes a pSOL token account so it can neither send nor receive.
+    pub fn freeze_psol_account(ctx: Context<FreezePsolAccount>) -> Result<()> {
+        let pool_key = ctx.accounts.config.pool;
+        let bump = [ctx.accounts.config.mint_authority_bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[MINT_AUTH_SEED, pool_key.as_ref(), &bump]];
+        token::freeze_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            FreezeAccount {
+                account: ctx.accounts.psol_account.to_account_info(),
+                mint: ctx.accounts.psol_mint.to_account_info(),
+                authority: ctx.accounts.mint_authority.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+        emit!(PsolAccountFreezeChanged {
+            account: ctx.accounts.psol_account.key(),
+            frozen: true,
+        });
+        Ok(())
+    }
+
+    /// Admin-only; thaws a pSOL token account frozen by `freeze_psol_account`.
+    pub fn thaw_psol_account(ctx: Context<FreezePsolAccount>) -> Result<()> {
+        let pool_key = ctx.accounts.config.pool;
+        let bump = [ctx.accounts.config.mint_authority_bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[MINT_AUTH_SEED, pool_key.as_ref(), &bump]];
+        token::thaw_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            ThawAccount {
+                account: ctx.accounts.psol_account.to_account_info(),
+                mint: ctx.accounts.psol_mint.to_account_info(),
+                authority: ctx.accounts.mint_authority.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+        emit!(PsolAccountFreezeChanged {
+            account: ctx.accounts.psol_account.key(),
+            frozen: false,
+        });
+        Ok(())
+    }
+
+    /// Permissionless; runs the backing invariant hook against the current pool and mint
+    /// and reports the figures in `SupplyReconciled`.
+    pub fn reconcile(ctx: Context<Reconcile>) -> Result<()> {
+        let backed = run_backing_hook(
+            &mut ctx.accounts.config,
+            &ctx.accounts.pool,
+            &ctx.accounts.psol_mint,
+        )?;
+        let config = &ctx.accounts.config;
+        emit!(SupplyReconciled {
+            outstanding_supply: config.outstanding_supply(ctx.accounts.psol_mint.supply)?,
+            wrapped_locked: config.wrapped_locked,
+            total_locked: ctx.accounts.pool.total_locked,
+            backed,
+        });
+        Ok(())
+    }
+
+    /// Admin-only; lifts a pause set by the invariant hook once the supply is backed again.
+    pub fn resume_swaps(ctx: Context<ResumeSwaps>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(
+            config.is_backed(ctx.accounts.psol_mint.supply, ctx.accounts.pool.total_locked)?,
+            ErrorCode::BackingInvariantViolated
+        );
+        config.swaps_paused = false;
+        Ok(())
+    }
+
+    /// Admin-only; creates the denylist that swaps and confidential transfers are screened
+    /// against from now on.
+    pub fn init_denylist(ctx: Context<InitDenylist>) -> Result<()> {
+        let denylist = &mut ctx.accounts.denylist;
+        denylist.version = Denylist::VERSION;
+        denylist.bump = ctx.bumps.denylist;
+        denylist.entries = Vec::new();
+        ctx.accounts.config.denylist = denylist.key();
+        Ok(())
+    }
+
+    /// Admin-only; adds `address` to the denylist, or removes it when `denied` is false.
+    pub fn update_denylist(
+        ctx: Context<UpdateDenylist>,
+        address: Pubkey,
+        denied: bool,
+    ) -> Result<()> {
+        let denylist = &mut ctx.accounts.denylist;
+        if denied {
+            denylist.insert(address)?;
+        } else {
+            denylist.remove(&address);
+        }
+        emit!(DenylistUpdated { address, denied });
+        Ok(())
+    }
+
+    /// Admin-only; stores `name`, `symbol` and `uri` in the config and publishes them as
+    /// the pSOL mint's Token Metadata, creating the metadata account on first use. The mint
+    /// authority PDA signs as mint and update authority.
+    pub fn set_mint_metadata(
+        ctx: Context<SetMintMetadata>,
+        name: String,
+        symbol: String,
+        uri: String,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.set_metadata(&name, &symbol, &uri)?;
+        let data = DataV2 {
+            name,
+            symbol,
+            uri,
+            seller_fee_basis_points: 0,
+            creators: None,
+            collection: None,
+            uses: None,
+        };
+
+        let pool_key = config.pool;
+        let bump = [config.mint_authority_bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[MINT_AUTH_SEED, pool_key.as_ref(), &bump]];
+        let mint_authority = ctx.accounts.mint_authority.to_account_info();
+        let program = ctx.accounts.token_metadata_program.to_account_info();
+        if ctx.accounts.metadata.data_is_empty() {
+            let cpi_ctx = CpiContext::new_with_signer(
+                program,
+                CreateMetadataAccountsV3 {
+                    metadata: ctx.accounts.metadata.to_account_info(),
+                    mint: ctx.accounts.psol_mint.to_account_info(),
+                    mint_authority: mint_authority.clone(),
+                    payer: ctx.accounts.admin.to_account_info(),
+                    update_authority: mint_authority,
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    rent: ctx.accounts.rent.to_account_info(),
+                },
+                signer_seeds,
+            );
+            metadata::create_metadata_accounts_v3(cpi_ctx, data, true, true, None)?;
+        } else {
+            let cpi_ctx = CpiContext::new_with_signer(
+                program,
+                UpdateMetadataAccountsV2 {
+                    metadata: ctx.accounts.metadata.to_account_info(),
+                    update_authority: mint_authority,
+                },
+                signer_seeds,
+            );
+            metadata::update_metadata_accounts_v2(cpi_ctx, None, Some(data), None, None)?;
+        }
+
+        let (name, symbol, uri) = ctx.accounts.config.metadata();
+        emit!(MintMetadataUpdated { name, symbol, uri });
         Ok(())
     }
 
-    pub fn swap_to_psol(ctx: Context<SwapToPsol>, amount: u64, nonce: u64) -> Result<()> {
+    /// Permissionless; creates the psol privacy account owned by the wrapper PDA, which
+    /// holds every wrapped lamport so that only this program can withdraw it. `payer`
+    /// funds its rent.
+    pub fn init_wrapper_account(ctx: Context<InitWrapperAccount>) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.config.psol_program,
+            ctx.accounts.psol_program.key(),
+            ErrorCode::InvalidProgram
+        );
+        let rent = Rent::get()?.minimum_balance(PrivacyAccount::SIZE);
+        fund_wrapper(
+            &ctx.accounts.payer,
+            &ctx.accounts.wrapper,
+            &ctx.accounts.system_program,
+            rent,
+        )?;
+        let config_key = ctx.accounts.config.key();
+        let wrapper_bump = [ctx.bumps.wrapper];
+        let wrapper_seeds: &[&[&[u8]]] = &[&[WRAPPER_SEED, config_key.as_ref(), &wrapper_bump]];
+        let cpi_accounts = psol::cpi::accounts::InitPrivacyAccount {
+            privacy_account: ctx.accounts.wrapper_account.to_account_info(),
+            owner: ctx.accounts.wrapper.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.psol_program.to_account_info(),
+            cpi_accounts,
+            wrapper_seeds,
+        );
+        let wrapper = ctx.accounts.wrapper.key().to_bytes();
+        psol::cpi::init_privacy_account(cpi_ctx, wrapper, wrapper)
+    }
+
+    /// Wraps `amount` lamports: deposits them into the wrapper's privacy account, locking
+    /// them in the pool, and mints pSOL for the amount net of the pool fee at the current
+    /// exchange rate. `encrypted_amount` and `proof` are those `deposit_private` expects for
+    /// the wrapper's account. Fails unless at least `min_out` pSOL is minted by
+    /// `deadline_slot`.
+    pub fn swap_to_psol(
+        ctx: Context<SwapToPsol>,
+        amount: u64,
+        encrypted_amount: [u8; 64],
+        proof: Vec<u8>,
+        memo: Option<Vec<u8>>,
+        min_out: u64,
+        deadline_slot: u64,
+    ) -> Result<()> {
+        ctx.accounts.pool.check_not_paused(Operation::SwapToPsol)?;
+        require!(!ctx.accounts.config.swaps_paused, ErrorCode::SwapsPaused);
+        check_not_denied(
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &[ctx.accounts.user.key()],
+        )?;
         require_keys_eq!(
             ctx.accounts.config.pool,
             ctx.accounts.pool.key(),
@@ -62,20 +305,52 @@ pub mod psol_token {
             ErrorCode::InvalidProgram
         );
 
-        let seeds: &[&[u8]] = &[MINT_AUTH_SEED, &[ctx.accounts.config.mint_authority_bump]];
+        enter(&mut ctx.accounts.config)?;
+        let locked_before = ctx.accounts.pool.total_locked;
+        let supply_before = ctx.accounts.psol_mint.supply;
+        let (net_amount, _) = ctx.accounts.pool.apply_fee(amount)?;
+        let shares = ctx
+            .accounts
+            .config
+            .shares_for_lamports(net_amount, supply_before)?;
+        require!(shares > 0, ErrorCode::InvalidAmount);
+        check_swap_terms(shares, min_out, deadline_slot, Clock::get()?.slot)?;
 
+        // The lamports pass through the wrapper so that it, not the user, owns the deposit.
+        fund_wrapper(
+            &ctx.accounts.user,
+            &ctx.accounts.wrapper,
+            &ctx.accounts.system_program,
+            amount,
+        )?;
+        let config_key = ctx.accounts.config.key();
+        let wrapper_bump = [ctx.bumps.wrapper];
+        let wrapper_seeds: &[&[&[u8]]] = &[&[WRAPPER_SEED, config_key.as_ref(), &wrapper_bump]];
         let cpi_accounts = psol::cpi::accounts::DepositPrivate {
             pool: ctx.accounts.pool.to_account_info(),
             vault: ctx.accounts.vault.to_account_info(),
-            commitment_registry: ctx.accounts.commitment_registry.to_account_info(),
-            privacy_account: ctx.accounts.privacy_account.to_account_info(),
-            user: ctx.accounts.user.to_account_info(),
+            commitment_registry: ctx
+                .accounts
+                .commitment_registry
+                .as_ref()
+                .map(|a| a.to_account_info()),
+            commitment_tree: ctx.accounts.commitment_tree.as_ref().map(|a| a.to_account_info()),
+            privacy_account: ctx.accounts.wrapper_account.to_account_info(),
+            user: ctx.accounts.wrapper.to_account_info(),
+            instructions_sysvar: ctx.accounts.instructions_sysvar.to_account_info(),
+            caller_allowlist: ctx.accounts.caller_allowlist.as_ref().map(|a| a.to_account_info()),
             system_program: ctx.accounts.system_program.to_account_info(),
         };
-        let cpi_ctx = CpiContext::new(ctx.accounts.psol_program.to_account_info(), cpi_accounts);
-        psol::cpi::deposit_private(cpi_ctx, amount, nonce)?;
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.psol_program.to_account_info(),
+            cpi_accounts,
+            wrapper_seeds,
+        );
+        psol::cpi::deposit_private(cpi_ctx, amount, encrypted_amount, proof, memo)?;
 
-        let (net_amount, _) = ctx.accounts.pool.apply_fee(amount)?;
+        let pool_key = ctx.accounts.config.pool;
+        let bump = [ctx.accounts.config.mint_authority_bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[MINT_AUTH_SEED, pool_key.as_ref(), &bump]];
         let mint_ctx = CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
             MintTo {
@@ -83,13 +358,56 @@ pub mod psol_token {
                 to: ctx.accounts.user_psol_account.to_account_info(),
                 authority: ctx.accounts.mint_authority.to_account_info(),
             },
-            &[seeds],
+            signer_seeds,
         );
-        token::mint_to(mint_ctx, net_amount)?;
+        token::mint_to(mint_ctx, shares)?;
+
+        ctx.accounts.pool.reload()?;
+        ctx.accounts.psol_mint.reload()?;
+        check_swap_deltas(
+            &ctx.accounts.pool,
+            &ctx.accounts.psol_mint,
+            locked_before.checked_add(amount),
+            supply_before.checked_add(shares),
+        )?;
+        let config = &mut ctx.accounts.config;
+        config.wrapped_locked = config
+            .wrapped_locked
+            .checked_add(net_amount)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        run_backing_hook(config, &ctx.accounts.pool, &ctx.accounts.psol_mint)?;
+
+        emit!(Wrapped {
+            pool: ctx.accounts.pool.key(),
+            user: ctx.accounts.user.key(),
+            amount,
+            minted: shares,
+        });
+        ctx.accounts.config.leave();
         Ok(())
     }
 
-    pub fn swap_to_sol(ctx: Context<SwapToSol>, amount: u64, nullifier: [u8; 32]) -> Result<()> {
+    /// Unwraps `amount` pSOL: burns it and withdraws its value at the current exchange rate
+    /// from the wrapper's privacy account, releasing the SOL net of the pool fee to
+    /// `recipient`. `proof` and `nullifier_proof` are those `withdraw_private` expects for
+    /// the wrapper's account; on `Accounts` pools the user pays the `NullifierSet` rent.
+    /// Fails unless at least `min_out` lamports are released by `deadline_slot`.
+    pub fn swap_to_sol(
+        ctx: Context<SwapToSol>,
+        amount: u64,
+        nullifier: [u8; 32],
+        proof: Vec<u8>,
+        nullifier_proof: Option<NonMembershipProof>,
+        min_out: u64,
+        deadline_slot: u64,
+    ) -> Result<()> {
+        ctx.accounts.pool.check_not_paused(Operation::SwapToSol)?;
+        require!(!ctx.accounts.config.swaps_paused, ErrorCode::SwapsPaused);
+        check_not_denied(
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &[ctx.accounts.user.key(), ctx.accounts.recipient.key()],
+        )?;
         require_keys_eq!(
             ctx.accounts.config.pool,
             ctx.accounts.pool.key(),
@@ -101,6 +419,42 @@ pub mod psol_token {
             ErrorCode::InvalidProgram
         );
 
+        enter(&mut ctx.accounts.config)?;
+        let locked_before = ctx.accounts.pool.total_locked;
+        let supply_before = ctx.accounts.psol_mint.supply;
+        let lamports = ctx
+            .accounts
+            .config
+            .lamports_for_shares(amount, supply_before)?;
+        require!(lamports > 0, ErrorCode::InvalidAmount);
+        let (net_amount, _) = ctx.accounts.pool.apply_fee(lamports)?;
+        let slot = Clock::get()?.slot;
+        check_swap_terms(net_amount, min_out, deadline_slot, slot)?;
+        // psol also rejects the withdrawal once a wrap has credited the wrapper's account
+        // in this slot; checking here fails before any CPI.
+        if ctx.accounts.pool.enforces(CallPolicy::NoDepositThenWithdraw) {
+            require!(
+                !guard::preceded_by(
+                    &ctx.accounts.instructions_sysvar,
+                    &crate::ID,
+                    &[instruction::SwapToPsol::DISCRIMINATOR],
+                )?,
+                ErrorCode::DepositThenWithdraw
+            );
+        }
+
+        // Fail before burning; the window counter itself advances inside the
+        // withdraw_private CPI below.
+        if ctx.accounts.pool.check_withdrawal_limits(lamports, slot).is_err() {
+            emit!(SwapLimitHit {
+                pool: ctx.accounts.pool.key(),
+                user: ctx.accounts.user.key(),
+                amount: lamports,
+                slot,
+            });
+            return err!(ErrorCode::WithdrawalLimitExceeded);
+        }
+
         let burn_ctx = CpiContext::new(
             ctx.accounts.token_program.to_account_info(),
             Burn {
@@ -111,100 +465,1244 @@ pub mod psol_token {
         );
         token::burn(burn_ctx, amount)?;
 
+        if ctx.accounts.nullifier_set.is_some() {
+            let rent = Rent::get()?.minimum_balance(psol::state::NullifierSet::SIZE);
+            fund_wrapper(
+                &ctx.accounts.user,
+                &ctx.accounts.wrapper,
+                &ctx.accounts.system_program,
+                rent,
+            )?;
+        }
+        let config_key = ctx.accounts.config.key();
+        let wrapper_bump = [ctx.bumps.wrapper];
+        let wrapper_seeds: &[&[&[u8]]] = &[&[WRAPPER_SEED, config_key.as_ref(), &wrapper_bump]];
         let cpi_accounts = psol::cpi::accounts::WithdrawPrivate {
             pool: ctx.accounts.pool.to_account_info(),
             vault: ctx.accounts.vault.to_account_info(),
-            nullifier_registry: ctx.accounts.nullifier_registry.to_account_info(),
-            privacy_account: ctx.accounts.privacy_account.to_account_info(),
-            owner: ctx.accounts.user.to_account_info(),
+            nullifier_set: ctx.accounts.nullifier_set.as_ref().map(|a| a.to_account_info()),
+            nullifier_archive: ctx
+                .accounts
+                .nullifier_archive
+                .as_ref()
+                .map(|a| a.to_account_info()),
+            nullifier_tree: ctx.accounts.nullifier_tree.as_ref().map(|a| a.to_account_info()),
+            proof_buffer: None,
+            privacy_account: ctx.accounts.wrapper_account.to_account_info(),
+            owner: ctx.accounts.wrapper.to_account_info(),
             recipient: ctx.accounts.recipient.to_account_info(),
+            rent_refund: Some(ctx.accounts.user.to_account_info()),
+            instructions_sysvar: ctx.accounts.instructions_sysvar.to_account_info(),
+            caller_allowlist: ctx.accounts.caller_allowlist.as_ref().map(|a| a.to_account_info()),
             system_program: ctx.accounts.system_program.to_account_info(),
         };
-        let cpi_ctx = CpiContext::new(ctx.accounts.psol_program.to_account_info(), cpi_accounts);
-        psol::cpi::withdraw_private(cpi_ctx, amount, nullifier)?;
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.psol_program.to_account_info(),
+            cpi_accounts,
+            wrapper_seeds,
+        );
+        psol::cpi::withdraw_private(cpi_ctx, lamports, nullifier, proof, nullifier_proof)?;
+
+        ctx.accounts.pool.reload()?;
+        ctx.accounts.psol_mint.reload()?;
+        check_swap_deltas(
+            &ctx.accounts.pool,
+            &ctx.accounts.psol_mint,
+            locked_before.checked_sub(net_amount),
+            supply_before.checked_sub(amount),
+        )?;
+        let config = &mut ctx.accounts.config;
+        config.wrapped_locked = config
+            .wrapped_locked
+            .checked_sub(lamports)
+            .ok_or(ErrorCode::BackingInvariantViolated)?;
+        run_backing_hook(config, &ctx.accounts.pool, &ctx.accounts.psol_mint)?;
+
+        emit!(Unwrapped {
+            pool: ctx.accounts.pool.key(),
+            user: ctx.accounts.user.key(),
+            burned: amount,
+            released: net_amount,
+        });
+        ctx.accounts.config.leave();
         Ok(())
     }
 
-    pub fn transfer_psol(_ctx: Context<TransferPsol>, _amount: u64) -> Result<()> {
-        err!(ErrorCode::TransfersDisabled)
+    /// Adds `amount` lamports of yield to the pool on behalf of pSOL holders, raising the
+    /// exchange rate. Permissionless, so the admin or a crank can fund it. The yield is
+    /// credited to the wrapper's privacy account so that `swap_to_sol` can release it.
+    pub fn deposit_rewards(ctx: Context<DepositRewards>, amount: u64) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.config.psol_program,
+            ctx.accounts.psol_program.key(),
+            ErrorCode::InvalidProgram
+        );
+        require!(
+            ctx.accounts.config.outstanding_supply(ctx.accounts.psol_mint.supply)? > 0,
+            ErrorCode::NoOutstandingSupply
+        );
+
+        enter(&mut ctx.accounts.config)?;
+        let locked_before = ctx.accounts.pool.total_locked;
+        fund_wrapper(
+            &ctx.accounts.payer,
+            &ctx.accounts.wrapper,
+            &ctx.accounts.system_program,
+            amount,
+        )?;
+        let config_key = ctx.accounts.config.key();
+        let wrapper_bump = [ctx.bumps.wrapper];
+        let wrapper_seeds: &[&[&[u8]]] = &[&[WRAPPER_SEED, config_key.as_ref(), &wrapper_bump]];
+        let cpi_accounts = psol::cpi::accounts::DepositRewards {
+            pool: ctx.accounts.pool.to_account_info(),
+            vault: ctx.accounts.vault.to_account_info(),
+            payer: ctx.accounts.wrapper.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            beneficiary: Some(ctx.accounts.wrapper_account.to_account_info()),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.psol_program.to_account_info(),
+            cpi_accounts,
+            wrapper_seeds,
+        );
+        psol::cpi::deposit_rewards(cpi_ctx, amount)?;
+
+        ctx.accounts.pool.reload()?;
+        check_swap_deltas(
+            &ctx.accounts.pool,
+            &ctx.accounts.psol_mint,
+            locked_before.checked_add(amount),
+            Some(ctx.accounts.psol_mint.supply),
+        )?;
+        let config = &mut ctx.accounts.config;
+        config.wrapped_locked = config
+            .wrapped_locked
+            .checked_add(amount)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        run_backing_hook(config, &ctx.accounts.pool, &ctx.accounts.psol_mint)?;
+
+        let rate = config.exchange_rate(ctx.accounts.psol_mint.supply)?;
+        emit!(RewardsAdded {
+            payer: ctx.accounts.payer.key(),
+            amount,
+            backing: rate.backing,
+            lamports_per_psol: rate.lamports_per_psol,
+        });
+        ctx.accounts.config.leave();
+        Ok(())
+    }
+
+    /// Permissionless crank claiming the pool's harvested stake rewards, for which the
+    /// wrapper PDA must be the strategy's rewards authority, into the wrapper's privacy
+    /// account and adding them to the backing. Waits for outstanding pSOL so the rewards
+    /// accrue to existing holders.
+    pub fn harvest_rewards(ctx: Context<HarvestRewards>) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.config.psol_program,
+            ctx.accounts.psol_program.key(),
+            ErrorCode::InvalidProgram
+        );
+        require!(
+            ctx.accounts.config.outstanding_supply(ctx.accounts.psol_mint.supply)? > 0,
+            ErrorCode::NoOutstandingSupply
+        );
+
+        enter(&mut ctx.accounts.config)?;
+        let config_key = ctx.accounts.config.key();
+        let bump = [ctx.bumps.wrapper];
+        let signer_seeds: &[&[&[u8]]] = &[&[WRAPPER_SEED, config_key.as_ref(), &bump]];
+        let cpi_accounts = psol::cpi::accounts::ClaimStakeRewards {
+            stake_strategy: ctx.accounts.stake_strategy.to_account_info(),
+            rewards_authority: ctx.accounts.wrapper.to_account_info(),
+            beneficiary: Some(ctx.accounts.wrapper_account.to_account_info()),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.psol_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
+        );
+        let amount = psol::cpi::claim_stake_rewards(cpi_ctx)?.get();
+
+        let config = &mut ctx.accounts.config;
+        config.wrapped_locked = config
+            .wrapped_locked
+            .checked_add(amount)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        run_backing_hook(config, &ctx.accounts.pool, &ctx.accounts.psol_mint)?;
+
+        let rate = config.exchange_rate(ctx.accounts.psol_mint.supply)?;
+        emit!(StakeRewardsAdded {
+            amount,
+            backing: rate.backing,
+            lamports_per_psol: rate.lamports_per_psol,
+        });
+        ctx.accounts.config.leave();
+        Ok(())
+    }
+
+    /// View of the current exchange rate; simulate and read the return data.
+    pub fn exchange_rate(ctx: Context<ViewExchangeRate>) -> Result<ExchangeRate> {
+        ctx.accounts.config.exchange_rate(ctx.accounts.psol_mint.supply)
+    }
+
+    /// Rewrites a `Config` stored under an older layout into the current one.
+    pub fn upgrade_config(ctx: Context<UpgradeConfig>) -> Result<()> {
+        let config = ctx.accounts.config.to_account_info();
+        let mut upgraded = Vec::new();
+        let from_version = {
+            let data = config.try_borrow_data()?;
+            let from_version = layout_version::<Config>(&data, 8 + ConfigV0::SPACE)?;
+            let mut current = Config::decode_any_version(&data)?;
+            if from_version == 0 {
+                // v0 did not track backing; all pSOL outstanding then came from swaps.
+                require_keys_eq!(
+                    current.psol_mint,
+                    ctx.accounts.psol_mint.key(),
+                    ErrorCode::InvalidMint
+                );
+                current.wrapped_locked = ctx.accounts.psol_mint.supply;
+            }
+            current.try_serialize(&mut upgraded)?;
+            from_version
+        };
+        require!(from_version != Config::VERSION, ErrorCode::AccountAlreadyCurrent);
+
+        rewrite_account(
+            &config,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &upgraded,
+        )
+    }
+
+    /// Moves a config from the unseeded `["psol_config"]` PDA of earlier deployments to
+    /// `["psol_config", pool]`, in the current layout, and hands the mint, freeze and
+    /// metadata update authorities from `["psol_mint_auth"]` to `["psol_mint_auth", pool]`.
+    /// Privacy links and the denylist are seeded by config, so the legacy config must have
+    /// nothing shielded, and the admin recreates the denylist afterwards.
+    pub fn migrate_legacy_config(ctx: Context<MigrateLegacyConfig>) -> Result<()> {
+        let legacy = ctx.accounts.legacy_config.to_account_info();
+        let (from_version, mut migrated) = Config::migrate_legacy(
+            &legacy.try_borrow_data()?,
+            &ctx.accounts.admin.key(),
+            &ctx.accounts.pool.key(),
+            &ctx.accounts.psol_mint.key(),
+            ctx.accounts.psol_mint.supply,
+        )?;
+        migrated.mint_authority_bump = ctx.bumps.mint_authority;
+        migrated.bump = ctx.bumps.config;
+        ctx.accounts.config.set_inner(migrated);
+
+        let legacy_bump = [ctx.bumps.legacy_mint_authority];
+        let signer_seeds: &[&[&[u8]]] = &[&[MINT_AUTH_SEED, &legacy_bump]];
+        let new_authority = ctx.accounts.mint_authority.key();
+        let mut authority_types =
+            vec![anchor_spl::token::spl_token::instruction::AuthorityType::MintTokens];
+        let legacy_authority = ctx.accounts.legacy_mint_authority.key();
+        if ctx.accounts.psol_mint.freeze_authority == COption::Some(legacy_authority) {
+            authority_types
+                .push(anchor_spl::token::spl_token::instruction::AuthorityType::FreezeAccount);
+        }
+        for authority_type in authority_types {
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                SetAuthority {
+                    current_authority: ctx.accounts.legacy_mint_authority.to_account_info(),
+                    account_or_mint: ctx.accounts.psol_mint.to_account_info(),
+                },
+                signer_seeds,
+            );
+            token::set_authority(cpi_ctx, authority_type, Some(new_authority))?;
+        }
+
+        // Left behind, the update authority would stay with the legacy PDA, which nothing
+        // signs for once the legacy config is closed.
+        let metadata = &ctx.accounts.metadata;
+        if !metadata.data_is_empty() {
+            let program = ctx
+                .accounts
+                .token_metadata_program
+                .as_ref()
+                .ok_or(ErrorCode::MetadataProgramRequired)?;
+            let cpi_ctx = CpiContext::new_with_signer(
+                program.to_account_info(),
+                UpdateMetadataAccountsV2 {
+                    metadata: metadata.to_account_info(),
+                    update_authority: ctx.accounts.legacy_mint_authority.to_account_info(),
+                },
+                signer_seeds,
+            );
+            metadata::update_metadata_accounts_v2(
+                cpi_ctx,
+                Some(new_authority),
+                None,
+                None,
+                None,
+            )?;
+        }
+
+        let admin = ctx.accounts.admin.to_account_info();
+        **admin.try_borrow_mut_lamports()? += legacy.lamports();
+        **legacy.try_borrow_mut_lamports()? = 0;
+        legacy.assign(&System::id());
+        legacy.realloc(0, false)?;
+        emit!(ConfigMigrated {
+            legacy_config: ctx.accounts.legacy_config.key(),
+            config: ctx.accounts.config.key(),
+            pool: ctx.accounts.pool.key(),
+            from_version,
+        });
+        Ok(())
+    }
+
+    pub fn init_privacy_link(ctx: Context<InitPrivacyLink>, view_key: [u8; 32]) -> Result<()> {
+        let link = &mut ctx.accounts.privacy_link;
+        link.version = TokenPrivacyLink::VERSION;
+        link.owner = ctx.accounts.owner.key();
+        link.encrypted_balance = [0u8; 64];
+        link.commitment = [0u8; 32];
+        link.view_key = view_key;
+        link.nonce = 0;
+        link.last_sync = Clock::get()?.slot;
+        link.bump = ctx.bumps.privacy_link;
+        Ok(())
+    }
+
+    /// Moves `amount` pSOL from the caller's SPL balance into their confidential balance.
+    /// The SPL tokens are burned and counted in `Config::shielded_supply` instead.
+    /// `proof` shows `encrypted_amount` encrypts `amount` to the link's view key.
+    pub fn shield_psol(
+        ctx: Context<ShieldPsol>,
+        amount: u64,
+        encrypted_amount: [u8; 64],
+        proof: Vec<u8>,
+    ) -> Result<()> {
+        ctx.accounts.pool.check_not_paused(Operation::Transfer)?;
+        check_not_denied(
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &[ctx.accounts.owner.key()],
+        )?;
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        let link = &mut ctx.accounts.privacy_link;
+        require!(
+            crypto::verify_proof(
+                &proof,
+                &amount_statement(&encrypted_amount, &link.view_key, amount)
+            )?,
+            ErrorCode::InvalidProof
+        );
+
+        enter(&mut ctx.accounts.config)?;
+        let supply_before = ctx.accounts.psol_mint.supply;
+        let burn_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.psol_mint.to_account_info(),
+                from: ctx.accounts.user_psol_account.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        );
+        token::burn(burn_ctx, amount)?;
+
+        link.encrypted_balance = crypto::add_encrypted(&link.encrypted_balance, &encrypted_amount)?;
+        link.commitment = crypto::pedersen_commit(amount, &link.commitment);
+        link.nonce = link.nonce.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        link.last_sync = Clock::get()?.slot;
+
+        let config = &mut ctx.accounts.config;
+        config.shielded_supply = config
+            .shielded_supply
+            .checked_add(amount)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        ctx.accounts.psol_mint.reload()?;
+        require!(
+            supply_before.checked_sub(amount) == Some(ctx.accounts.psol_mint.supply),
+            ErrorCode::BackingInvariantViolated
+        );
+        require!(
+            config.is_backed(ctx.accounts.psol_mint.supply, ctx.accounts.pool.total_locked)?,
+            ErrorCode::BackingInvariantViolated
+        );
+        ctx.accounts.config.leave();
+        Ok(())
+    }
+
+    /// Moves an encrypted pSOL amount between confidential balances. Checked like
+    /// `psol::transfer_private`: the sender's proof must cover `encrypted_amount`, and
+    /// pools with an auditor require `auditor_amount`.
+    pub fn transfer_psol(
+        ctx: Context<TransferPsol>,
+        encrypted_amount: [u8; 64],
+        proof: Vec<u8>,
+        auditor_amount: Option<[u8; 64]>,
+    ) -> Result<()> {
+        ctx.accounts.pool.check_not_paused(Operation::Transfer)?;
+        check_not_denied(
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &[ctx.accounts.sender.key(), ctx.accounts.recipient_link.owner],
+        )?;
+        if ctx.accounts.pool.has_auditor() {
+            require!(
+                auditor_amount.is_some(),
+                ErrorCode::AuditorCiphertextRequired
+            );
+        }
+
+        let sender_link = &mut ctx.accounts.sender_link;
+        let recipient_link = &mut ctx.accounts.recipient_link;
+        require!(
+            crypto::verify_transfer_proof(
+                &sender_link.encrypted_balance,
+                &encrypted_amount,
+                &sender_link.commitment,
+                // Links have no separate spend key; the owner signs for the balance.
+                &sender_link.owner.to_bytes(),
+                &proof,
+            ),
+            ErrorCode::InvalidProof
+        );
+
+        sender_link.encrypted_balance =
+            crypto::subtract_encrypted(&sender_link.encrypted_balance, &encrypted_amount)?;
+        recipient_link.encrypted_balance =
+            crypto::add_encrypted(&recipient_link.encrypted_balance, &encrypted_amount)?;
+        sender_link.nonce = sender_link
+            .nonce
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        recipient_link.nonce = recipient_link
+            .nonce
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        let slot = Clock::get()?.slot;
+        sender_link.last_sync = slot;
+        recipient_link.last_sync = slot;
+
+        emit!(ConfidentialTransfer {
+            sender_link: sender_link.key(),
+            recipient_link: recipient_link.key(),
+            encrypted_amount,
+            auditor_amount,
+            slot,
+        });
+        Ok(())
+    }
+
+    /// Moves `amount` pSOL from the caller's confidential balance back to SPL tokens.
+    /// `proof` shows the encrypted balance covers `amount`.
+    pub fn unshield_psol(ctx: Context<UnshieldPsol>, amount: u64, proof: Vec<u8>) -> Result<()> {
+        ctx.accounts.pool.check_not_paused(Operation::Transfer)?;
+        check_not_denied(
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &[ctx.accounts.owner.key(), ctx.accounts.user_psol_account.owner],
+        )?;
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        let link = &mut ctx.accounts.privacy_link;
+        require!(
+            crypto::verify_proof(
+                &proof,
+                &amount_statement(&link.encrypted_balance, &link.view_key, amount)
+            )?,
+            ErrorCode::InvalidProof
+        );
+
+        enter(&mut ctx.accounts.config)?;
+        let encrypted_amount = crypto::encrypt_amount(amount, &link.view_key);
+        link.encrypted_balance =
+            crypto::subtract_encrypted(&link.encrypted_balance, &encrypted_amount)?;
+        link.commitment = crypto::pedersen_commit(amount, &link.commitment);
+        link.nonce = link.nonce.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        link.last_sync = Clock::get()?.slot;
+
+        let config = &mut ctx.accounts.config;
+        config.shielded_supply = config
+            .shielded_supply
+            .checked_sub(amount)
+            .ok_or(ErrorCode::BackingInvariantViolated)?;
+
+        let supply_before = ctx.accounts.psol_mint.supply;
+        let pool_key = config.pool;
+        let bump = [config.mint_authority_bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[MINT_AUTH_SEED, pool_key.as_ref(), &bump]];
+        let mint_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.psol_mint.to_account_info(),
+                to: ctx.accounts.user_psol_account.to_account_info(),
+                authority: ctx.accounts.mint_authority.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::mint_to(mint_ctx, amount)?;
+
+        ctx.accounts.psol_mint.reload()?;
+        require!(
+            supply_before.checked_add(amount) == Some(ctx.accounts.psol_mint.supply),
+            ErrorCode::BackingInvariantViolated
+        );
+        require!(
+            ctx.accounts
+                .config
+                .is_backed(ctx.accounts.psol_mint.supply, ctx.accounts.pool.total_locked)?,
+            ErrorCode::BackingInvariantViolated
+        );
+        ctx.accounts.config.leave();
+        Ok(())
     }
 }
 
+/// Public inputs binding `ciphertext` under `view_key` to a cleartext `amount`: for
+/// `shield_psol` the ciphertext encrypts `amount`, for `unshield_psol` it covers it.
+pub fn amount_statement(ciphertext: &[u8; 64], view_key: &[u8; 32], amount: u64) -> [[u8; 32]; 3] {
+    let mut amount_word = [0u8; 32];
+    amount_word[..8].copy_from_slice(&amount.to_le_bytes());
+    [
+        anchor_lang::solana_program::hash::hash(ciphertext).to_bytes(),
+        *view_key,
+        amount_word,
+    ]
+}
+
+/// Checks the caller's terms for a swap: `output`, net of fees at the current exchange rate,
+/// is at least `min_out`, and `slot` is no later than `deadline_slot`. Fees and the rate
+/// can change between signing and execution, so clients sign for the worst they accept.
+pub fn check_swap_terms(output: u64, min_out: u64, deadline_slot: u64, slot: u64) -> Result<()> {
+    require!(slot <= deadline_slot, ErrorCode::SwapDeadlineExceeded);
+    require!(output >= min_out, ErrorCode::SlippageExceeded);
+    Ok(())
+}
+
+/// Takes the config's reentrancy lock and writes it through to the account before any
+/// CPI, so a nested call into this program sees it. Anchor writes the released lock back on
+/// success.
+fn enter(config: &mut Account<Config>) -> Result<()> {
+    config.enter()?;
+    config.exit(&crate::ID)
+}
+
+/// Moves `lamports` from `from` to the wrapper PDA, which forwards them to psol under its
+/// own signature.
+fn fund_wrapper<'info>(
+    from: &Signer<'info>,
+    wrapper: &UncheckedAccount<'info>,
+    system_program: &Program<'info, System>,
+    lamports: u64,
+) -> Result<()> {
+    system_program::transfer(
+        CpiContext::new(
+            system_program.to_account_info(),
+            Transfer {
+                from: from.to_account_info(),
+                to: wrapper.to_account_info(),
+            },
+        ),
+        lamports,
+    )
+}
+
+/// Checks a swap moved `total_locked` and the pSOL supply by exactly the expected amounts.
+fn check_swap_deltas(
+    pool: &PrivacyPool,
+    psol_mint: &Mint,
+    expected_locked: Option<u64>,
+    expected_supply: Option<u64>,
+) -> Result<()> {
+    require!(
+        expected_locked == Some(pool.total_locked) && expected_supply == Some(psol_mint.supply),
+        ErrorCode::BackingInvariantViolated
+    );
+    Ok(())
+}
+
+/// Invariant hook run after every swap and by `reconcile`. When the outstanding supply is
+/// not backed (see `Config::is_backed`) it pauses swaps and emits `SwapsPausedForBacking`
+/// rather than failing, so the pause sticks. Returns whether the invariant holds.
+fn run_backing_hook(config: &mut Config, pool: &PrivacyPool, psol_mint: &Mint) -> Result<bool> {
+    let backed = config.is_backed(psol_mint.supply, pool.total_locked)?;
+    if !backed && !config.swaps_paused {
+        config.swaps_paused = true;
+        emit!(SwapsPausedForBacking {
+            outstanding_supply: config.outstanding_supply(psol_mint.supply)?,
+            wrapped_locked: config.wrapped_locked,
+            total_locked: pool.total_locked,
+            slot: Clock::get()?.slot,
+        });
+    }
+    Ok(backed)
+}
+
+/// Screens `addresses` against the config's denylist, which must be supplied once one
+/// has been created.
+fn check_not_denied(
+    config: &Config,
+    denylist: &Option<Account<Denylist>>,
+    addresses: &[Pubkey],
+) -> Result<()> {
+    if config.denylist == Pubkey::default() {
+        return Ok(());
+    }
+    let denylist = denylist
+        .as_ref()
+        .filter(|denylist| denylist.key() == config.denylist)
+        .ok_or(ErrorCode::DenylistRequired)?;
+    for address in addresses {
+        require!(!denylist.contains(address), ErrorCode::AddressDenied);
+    }
+    Ok(())
+}
+
 #[derive(Accounts)]
 pub struct InitializeToken<'info> {
+    /// Only the pool's admin may wrap it, so nobody else can front-run the one config PDA.
+    #[account(constraint = pool.admin == admin.key() @ ErrorCode::Unauthorized)]
+    pub pool: Account<'info, PrivacyPool>,
     #[account(
         init,
         payer = admin,
         space = 8 + Config::SPACE,
-        seeds = [CONFIG_SEED],
+        seeds = [CONFIG_SEED, pool.key().as_ref()],
         bump
     )]
     pub config: Account<'info, Config>,
     pub psol_program: Program<'info, Psol>,
-    pub pool: Account<'info, PrivacyPool>,
     #[account(mut)]
     pub psol_mint: Account<'info, Mint>,
     /// CHECK: PDA mint authority
-    #[account(seeds = [MINT_AUTH_SEED], bump)]
+    #[account(seeds = [MINT_AUTH_SEED, pool.key().as_ref()], bump)]
     pub mint_authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
     pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
 pub struct SwapToPsol<'info> {
-    #[account(mut, seeds = [CONFIG_SEED], bump = config.bump)]
+    #[account(mut, seeds = [CONFIG_SEED, pool.key().as_ref()], bump = config.bump)]
     pub config: Account<'info, Config>,
+    /// Required once the admin has created a denylist.
+    #[account(seeds = [DENYLIST_SEED, config.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
     pub psol_program: Program<'info, Psol>,
     #[account(mut)]
     pub pool: Account<'info, PrivacyPool>,
     /// CHECK: vault managed by psol
-    #[account(mut, seeds = [b"vault", pool.key().as_ref()], bump = pool.vault_bump)]
+    #[account(
+        mut,
+        seeds = [b"vault", pool.key().as_ref()],
+        bump = pool.vault_bump,
+        seeds::program = psol::ID
+    )]
     pub vault: UncheckedAccount<'info>,
-    /// CHECK: registry PDAs validated in CPI
-    #[account(mut, seeds = [b"commitment", pool.key().as_ref()], bump = pool.commitment_bump)]
-    pub commitment_registry: Account<'info, CommitmentRegistry>,
-    #[account(mut, seeds = [b"privacy", user.key().as_ref()], bump)]
-    pub privacy_account: Account<'info, PrivacyAccount>,
+    /// CHECK: registry PDA validated in CPI; set for `Accounts` pools
+    #[account(mut)]
+    pub commitment_registry: Option<UncheckedAccount<'info>>,
+    /// CHECK: commitment tree validated in CPI; set for `Compressed` pools
+    #[account(mut)]
+    pub commitment_tree: Option<UncheckedAccount<'info>>,
+    /// CHECK: system-owned PDA that owns `wrapper_account` and signs for it
+    #[account(mut, seeds = [WRAPPER_SEED, config.key().as_ref()], bump)]
+    pub wrapper: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"privacy", wrapper.key().as_ref()],
+        bump = wrapper_account.bump,
+        seeds::program = psol::ID
+    )]
+    pub wrapper_account: Account<'info, PrivacyAccount>,
     #[account(mut)]
     pub user: Signer<'info>,
     #[account(mut)]
     pub user_psol_account: Account<'info, TokenAccount>,
-    #[account(mut)]
+    #[account(mut, address = config.psol_mint @ ErrorCode::InvalidMint)]
     pub psol_mint: Account<'info, Mint>,
     /// CHECK: PDA mint authority
-    #[account(seeds = [MINT_AUTH_SEED], bump = config.mint_authority_bump)]
+    #[account(seeds = [MINT_AUTH_SEED, config.pool.as_ref()], bump = config.mint_authority_bump)]
     pub mint_authority: UncheckedAccount<'info>,
     pub token_program: Program<'info, Token>,
+    /// CHECK: instructions sysvar, read by psol's call policy checks
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    /// CHECK: psol's caller allowlist, validated in CPI; required while the pool only
+    /// admits approved callers
+    pub caller_allowlist: Option<UncheckedAccount<'info>>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
 pub struct SwapToSol<'info> {
-    #[account(mut, seeds = [CONFIG_SEED], bump = config.bump)]
+    #[account(mut, seeds = [CONFIG_SEED, pool.key().as_ref()], bump = config.bump)]
     pub config: Account<'info, Config>,
+    /// Required once the admin has created a denylist.
+    #[account(seeds = [DENYLIST_SEED, config.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
     pub psol_program: Program<'info, Psol>,
     #[account(mut)]
     pub pool: Account<'info, PrivacyPool>,
     /// CHECK: vault managed by psol
-    #[account(mut, seeds = [b"vault", pool.key().as_ref()], bump = pool.vault_bump)]
+    #[account(
+        mut,
+        seeds = [b"vault", pool.key().as_ref()],
+        bump = pool.vault_bump,
+        seeds::program = psol::ID
+    )]
     pub vault: UncheckedAccount<'info>,
-    /// CHECK: registry PDAs validated in CPI
-    #[account(mut, seeds = [b"nullifier", pool.key().as_ref()], bump = pool.nullifier_bump)]
-    pub nullifier_registry: Account<'info, NullifierRegistry>,
-    #[account(mut, seeds = [b"privacy", user.key().as_ref()], bump)]
-    pub privacy_account: Account<'info, PrivacyAccount>,
+    /// CHECK: per-nullifier record created by the withdraw_private CPI; `Accounts` pools
+    #[account(mut)]
+    pub nullifier_set: Option<UncheckedAccount<'info>>,
+    /// CHECK: archive PDA validated in CPI; `Accounts` pools
+    pub nullifier_archive: Option<UncheckedAccount<'info>>,
+    /// CHECK: nullifier tree validated in CPI; `Compressed` pools
+    #[account(mut)]
+    pub nullifier_tree: Option<UncheckedAccount<'info>>,
+    /// CHECK: system-owned PDA that owns `wrapper_account` and signs for it
+    #[account(mut, seeds = [WRAPPER_SEED, config.key().as_ref()], bump)]
+    pub wrapper: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"privacy", wrapper.key().as_ref()],
+        bump = wrapper_account.bump,
+        seeds::program = psol::ID
+    )]
+    pub wrapper_account: Account<'info, PrivacyAccount>,
     #[account(mut)]
     pub user: Signer<'info>,
     #[account(mut)]
     pub user_psol_account: Account<'info, TokenAccount>,
-    #[account(mut)]
+    #[account(mut, address = config.psol_mint @ ErrorCode::InvalidMint)]
     pub psol_mint: Account<'info, Mint>,
     /// CHECK: recipient of SOL
     #[account(mut)]
     pub recipient: UncheckedAccount<'info>,
     pub token_program: Program<'info, Token>,
+    /// CHECK: instructions sysvar, read by psol's call policy checks
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    /// CHECK: psol's caller allowlist, validated in CPI; required while the pool only
+    /// admits approved callers
+    pub caller_allowlist: Option<UncheckedAccount<'info>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DepositRewards<'info> {
+    #[account(mut, seeds = [CONFIG_SEED, config.pool.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    pub psol_program: Program<'info, Psol>,
+    #[account(mut, address = config.pool @ ErrorCode::InvalidPool)]
+    pub pool: Account<'info, PrivacyPool>,
+    /// CHECK: vault managed by psol
+    #[account(
+        mut,
+        seeds = [b"vault", pool.key().as_ref()],
+        bump = pool.vault_bump,
+        seeds::program = psol::ID
+    )]
+    pub vault: UncheckedAccount<'info>,
+    #[account(address = config.psol_mint @ ErrorCode::InvalidMint)]
+    pub psol_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: system-owned PDA that owns `wrapper_account` and signs for it
+    #[account(mut, seeds = [WRAPPER_SEED, config.key().as_ref()], bump)]
+    pub wrapper: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"privacy", wrapper.key().as_ref()],
+        bump = wrapper_account.bump,
+        seeds::program = psol::ID
+    )]
+    pub wrapper_account: Account<'info, PrivacyAccount>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct HarvestRewards<'info> {
+    #[account(mut, seeds = [CONFIG_SEED, config.pool.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    pub psol_program: Program<'info, Psol>,
+    #[account(address = config.pool @ ErrorCode::InvalidPool)]
+    pub pool: Account<'info, PrivacyPool>,
+    #[account(
+        mut,
+        seeds = [b"stake_strategy", pool.key().as_ref()],
+        bump = stake_strategy.bump,
+        seeds::program = psol::ID
+    )]
+    pub stake_strategy: Account<'info, StakeStrategy>,
+    #[account(address = config.psol_mint @ ErrorCode::InvalidMint)]
+    pub psol_mint: Account<'info, Mint>,
+    /// CHECK: system-owned PDA, the strategy's rewards authority
+    #[account(seeds = [WRAPPER_SEED, config.key().as_ref()], bump)]
+    pub wrapper: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"privacy", wrapper.key().as_ref()],
+        bump = wrapper_account.bump,
+        seeds::program = psol::ID
+    )]
+    pub wrapper_account: Account<'info, PrivacyAccount>,
+}
+
+#[derive(Accounts)]
+pub struct InitWrapperAccount<'info> {
+    #[account(seeds = [CONFIG_SEED, config.pool.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    pub psol_program: Program<'info, Psol>,
+    /// CHECK: system-owned PDA that will own `wrapper_account`
+    #[account(mut, seeds = [WRAPPER_SEED, config.key().as_ref()], bump)]
+    pub wrapper: UncheckedAccount<'info>,
+    /// CHECK: created and validated by the init_privacy_account CPI
+    #[account(mut)]
+    pub wrapper_account: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ViewExchangeRate<'info> {
+    #[account(seeds = [CONFIG_SEED, config.pool.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(address = config.psol_mint @ ErrorCode::InvalidMint)]
+    pub psol_mint: Account<'info, Mint>,
+}
+
+#[derive(Accounts)]
+pub struct UpgradeConfig<'info> {
+    pub pool: Account<'info, PrivacyPool>,
+    /// CHECK: layout version is read from the data by the handler
+    #[account(mut, owner = crate::ID, seeds = [CONFIG_SEED, pool.key().as_ref()], bump)]
+    pub config: UncheckedAccount<'info>,
+    pub psol_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateLegacyConfig<'info> {
+    /// CHECK: layout version is read from the data by the handler, which closes it
+    #[account(mut, owner = crate::ID, seeds = [CONFIG_SEED], bump)]
+    pub legacy_config: UncheckedAccount<'info>,
+    pub pool: Account<'info, PrivacyPool>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Config::SPACE,
+        seeds = [CONFIG_SEED, pool.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub psol_mint: Account<'info, Mint>,
+    /// CHECK: legacy PDA mint authority, signs to hand its authorities over
+    #[account(seeds = [MINT_AUTH_SEED], bump)]
+    pub legacy_mint_authority: UncheckedAccount<'info>,
+    /// CHECK: PDA mint authority
+    #[account(seeds = [MINT_AUTH_SEED, pool.key().as_ref()], bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+    /// CHECK: metadata PDA of `psol_mint`, empty unless `set_mint_metadata` has run
+    #[account(
+        mut,
+        seeds = [b"metadata", metadata::ID.as_ref(), psol_mint.key().as_ref()],
+        bump,
+        seeds::program = metadata::ID
+    )]
+    pub metadata: UncheckedAccount<'info>,
+    /// Required when `metadata` exists.
+    pub token_metadata_program: Option<Program<'info, Metadata>>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct TransferPsol {}
\ No newline at end of file
+pub struct Reconcile<'info> {
+    #[account(mut, seeds = [CONFIG_SEED, config.pool.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(address = config.pool @ ErrorCode::InvalidPool)]
+    pub pool: Account<'info, PrivacyPool>,
+    #[account(address = config.psol_mint @ ErrorCode::InvalidMint)]
+    pub psol_mint: Account<'info, Mint>,
+}
+
+#[derive(Accounts)]
+pub struct ResumeSwaps<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, config.pool.as_ref()],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+    #[account(address = config.pool @ ErrorCode::InvalidPool)]
+    pub pool: Account<'info, PrivacyPool>,
+    #[account(address = config.psol_mint @ ErrorCode::InvalidMint)]
+    pub psol_mint: Account<'info, Mint>,
+}
+
+#[derive(Accounts)]
+pub struct FreezePsolAccount<'info> {
+    #[account(
+        seeds = [CONFIG_SEED, config.pool.as_ref()],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+    #[account(mut, token::mint = psol_mint)]
+    pub psol_account: Account<'info, TokenAccount>,
+    #[account(address = config.psol_mint @ ErrorCode::InvalidMint)]
+    pub psol_mint: Account<'info, Mint>,
+    /// CHECK: PDA mint and freeze authority
+    #[account(seeds = [MINT_AUTH_SEED, config.pool.as_ref()], bump = config.mint_authority_bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct InitDenylist<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, config.pool.as_ref()],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Denylist::SPACE,
+        seeds = [DENYLIST_SEED, config.key().as_ref()],
+        bump
+    )]
+    pub denylist: Account<'info, Denylist>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateDenylist<'info> {
+    #[account(
+        seeds = [CONFIG_SEED, config.pool.as_ref()],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut, address = config.denylist @ ErrorCode::DenylistRequired)]
+    pub denylist: Account<'info, Denylist>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetMintMetadata<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, config.pool.as_ref()],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(address = config.psol_mint @ ErrorCode::InvalidMint)]
+    pub psol_mint: Account<'info, Mint>,
+    /// CHECK: PDA mint authority; also the metadata update authority
+    #[account(seeds = [MINT_AUTH_SEED, config.pool.as_ref()], bump = config.mint_authority_bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+    /// CHECK: metadata PDA of `psol_mint`, created or updated by the CPI
+    #[account(
+        mut,
+        seeds = [b"metadata", metadata::ID.as_ref(), psol_mint.key().as_ref()],
+        bump,
+        seeds::program = metadata::ID
+    )]
+    pub metadata: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct InitPrivacyLink<'info> {
+    #[account(seeds = [CONFIG_SEED, config.pool.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + TokenPrivacyLink::SPACE,
+        seeds = [PRIVACY_LINK_SEED, config.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub privacy_link: Account<'info, TokenPrivacyLink>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ShieldPsol<'info> {
+    #[account(mut, seeds = [CONFIG_SEED, config.pool.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    /// Required once the admin has created a denylist.
+    #[account(seeds = [DENYLIST_SEED, config.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    #[account(address = config.pool @ ErrorCode::InvalidPool)]
+    pub pool: Account<'info, PrivacyPool>,
+    #[account(
+        mut,
+        seeds = [PRIVACY_LINK_SEED, config.key().as_ref(), owner.key().as_ref()],
+        bump = privacy_link.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub privacy_link: Account<'info, TokenPrivacyLink>,
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub user_psol_account: Account<'info, TokenAccount>,
+    #[account(mut, address = config.psol_mint @ ErrorCode::InvalidMint)]
+    pub psol_mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct TransferPsol<'info> {
+    #[account(seeds = [CONFIG_SEED, config.pool.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    /// Required once the admin has created a denylist.
+    #[account(seeds = [DENYLIST_SEED, config.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    #[account(address = config.pool @ ErrorCode::InvalidPool)]
+    pub pool: Account<'info, PrivacyPool>,
+    #[account(
+        mut,
+        seeds = [PRIVACY_LINK_SEED, config.key().as_ref(), sender.key().as_ref()],
+        bump = sender_link.bump,
+        constraint = sender_link.owner == sender.key() @ ErrorCode::Unauthorized
+    )]
+    pub sender_link: Account<'info, TokenPrivacyLink>,
+    #[account(
+        mut,
+        seeds = [PRIVACY_LINK_SEED, config.key().as_ref(), recipient_link.owner.as_ref()],
+        bump = recipient_link.bump,
+        constraint = recipient_link.key() != sender_link.key() @ ErrorCode::InvalidRecipient
+    )]
+    pub recipient_link: Account<'info, TokenPrivacyLink>,
+    pub sender: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UnshieldPsol<'info> {
+    #[account(mut, seeds = [CONFIG_SEED, config.pool.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    /// Required once the admin has created a denylist.
+    #[account(seeds = [DENYLIST_SEED, config.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    #[account(address = config.pool @ ErrorCode::InvalidPool)]
+    pub pool: Account<'info, PrivacyPool>,
+    #[account(
+        mut,
+        seeds = [PRIVACY_LINK_SEED, config.key().as_ref(), owner.key().as_ref()],
+        bump = privacy_link.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub privacy_link: Account<'info, TokenPrivacyLink>,
+    pub owner: Signer<'info>,
+    #[account(mut, token::mint = psol_mint)]
+    pub user_psol_account: Account<'info, TokenAccount>,
+    #[account(mut, address = config.psol_mint @ ErrorCode::InvalidMint)]
+    pub psol_mint: Account<'info, Mint>,
+    /// CHECK: PDA mint authority
+    #[account(seeds = [MINT_AUTH_SEED, config.pool.as_ref()], bump = config.mint_authority_bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[event]
+pub struct Wrapped {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub minted: u64,
+}
+
+#[event]
+pub struct Unwrapped {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub burned: u64,
+    pub released: u64,
+}
+
+#[event]
+pub struct RewardsAdded {
+    pub payer: Pubkey,
+    pub amount: u64,
+    pub backing: u64,
+    pub lamports_per_psol: u64,
+}
+
+#[event]
+pub struct StakeRewardsAdded {
+    pub amount: u64,
+    pub backing: u64,
+    pub lamports_per_psol: u64,
+}
+
+#[event]
+pub struct SwapsPausedForBacking {
+    pub outstanding_supply: u64,
+    pub wrapped_locked: u64,
+    pub total_locked: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct SupplyReconciled {
+    pub outstanding_supply: u64,
+    pub wrapped_locked: u64,
+    pub total_locked: u64,
+    pub backed: bool,
+}
+
+#[event]
+pub struct PsolAccountFreezeChanged {
+    pub account: Pubkey,
+    pub frozen: bool,
+}
+
+#[event]
+pub struct MintMetadataUpdated {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+}
+
+#[event]
+pub struct ConfigMigrated {
+    pub legacy_config: Pubkey,
+    pub config: Pubkey,
+    pub pool: Pubkey,
+    pub from_version: u8,
+}
+
+#[event]
+pub struct DenylistUpdated {
+    pub address: Pubkey,
+    pub denied: bool,
+}
+
+/// `encrypted_amount` is encrypted to the recipient link's `view_key`.
+#[event]
+pub struct ConfidentialTransfer {
+    pub sender_link: Pubkey,
+    pub recipient_link: Pubkey,
+    pub encrypted_amount: [u8; 64],
+    pub auditor_amount: Option<[u8; 64]>,
+    pub slot: u64,
+}
+
+#[event]
+pub struct SwapLimitHit {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub slot: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::tests::test_config;
+    use anchor_lang::AnchorDeserialize;
+
+    /// A psol pool charging `fee_bps`, every other field zeroed.
+    fn pool_with_fee(fee_bps: u16) -> PrivacyPool {
+        let mut pool = PrivacyPool::try_from_slice(&[0u8; PrivacyPool::SIZE - 8]).unwrap();
+        pool.version = PrivacyPool::VERSION;
+        pool.storage_version = PrivacyPool::STORAGE_VERSION;
+        pool.fee_bps = fee_bps;
+        pool
+    }
+
+    #[test]
+    fn wrapped_supply_stays_backed_through_fees() {
+        let pool = pool_with_fee(100);
+        let mut config = test_config();
+        let (mut locked, mut supply) = (0u64, 0u64);
+
+        // swap_to_psol locks the full amount and mints it net of the fee.
+        let (net, _) = pool.apply_fee(1_000_000).unwrap();
+        let minted = config.shares_for_lamports(net, supply).unwrap();
+        locked += 1_000_000;
+        supply += minted;
+        config.wrapped_locked += net;
+        assert_eq!(minted, 990_000);
+        assert!(config.is_backed(supply, locked).unwrap());
+
+        // swap_to_sol burns the full amount and unlocks its value net of the fee.
+        let redeemed = config.lamports_for_shares(supply, supply).unwrap();
+        let (released, _) = pool.apply_fee(redeemed).unwrap();
+        locked -= released;
+        config.wrapped_locked -= redeemed;
+        supply = 0;
+        assert_eq!(released, 980_100);
+        assert_eq!((locked, config.wrapped_locked), (19_900, 0));
+        assert!(config.is_backed(supply, locked).unwrap());
+        assert!(!config.is_backed(1, locked).unwrap());
+    }
+
+    #[test]
+    fn swaps_honour_min_out_and_deadline() {
+        // Signed against a 1% fee; the admin raises it to 2% before the swap lands.
+        let (min_out, _) = pool_with_fee(100).apply_fee(1_000_000).unwrap();
+        let (net, _) = pool_with_fee(200).apply_fee(1_000_000).unwrap();
+        assert_eq!(
+            check_swap_terms(net, min_out, 50, 50),
+            Err(ErrorCode::SlippageExceeded.into())
+        );
+        assert!(check_swap_terms(net, net, 50, 50).is_ok());
+        assert_eq!(
+            check_swap_terms(net, net, 50, 51),
+            Err(ErrorCode::SwapDeadlineExceeded.into())
+        );
+        assert!(check_swap_terms(net, 0, u64::MAX, u64::MAX).is_ok());
+    }
+
+    #[test]
+    fn confidential_amount_statements_bind_every_input() {
+        let view_key = [7u8; 32];
+        let ciphertext = crypto::encrypt_amount(50, &view_key);
+        let statement = amount_statement(&ciphertext, &view_key, 50);
+        assert_ne!(statement, amount_statement(&ciphertext, &view_key, 51));
+        assert_ne!(statement, amount_statement(&ciphertext, &[8u8; 32], 50));
+        let other = crypto::encrypt_amount(51, &view_key);
+        assert_ne!(statement, amount_statement(&other, &view_key, 50));
+        assert!(crypto::verify_proof(&[1u8; 64], &statement).unwrap());
+    }
+
+    #[test]
+    fn configs_and_mint_authorities_are_seeded_by_pool() {
+        let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &crate::ID).0;
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        for seed in [CONFIG_SEED, MINT_AUTH_SEED] {
+            let for_a = pda(&[seed, a.as_ref()]);
+            assert_ne!(for_a, pda(&[seed, b.as_ref()]));
+            // Nor may a pool's PDA collide with the unseeded one of earlier deployments.
+            assert_ne!(for_a, pda(&[seed]));
+        }
+        let wrapper = |pool: &Pubkey| {
+            let config = pda(&[CONFIG_SEED, pool.as_ref()]);
+            pda(&[WRAPPER_SEED, config.as_ref()])
+        };
+        assert_ne!(wrapper(&a), wrapper(&b));
+    }
+}
@@ -1,14 +1,605 @@
 use anchor_lang::prelude::*;
+use psol::state::layout_version;
 
+use crate::error::ErrorCode;
+
+/// Wrapper for one pool, at `["psol_config", pool]`; the pool's pSOL mint is controlled by
+/// the PDA at `["psol_mint_auth", pool]`.
 #[account]
 pub struct Config {
+    /// Layout version, `Config::VERSION` for configs written by this program.
+    pub version: u8,
     pub admin: Pubkey,
     pub psol_program: Pubkey,
     pub pool: Pubkey,
     pub psol_mint: Pubkey,
     pub mint_authority_bump: u8,
     pub bump: u8,
+    /// pSOL burned from the SPL supply into `TokenPrivacyLink` balances by `shield_psol`
+    /// and not yet unshielded.
+    pub shielded_supply: u64,
+    /// `Denylist` swaps and confidential transfers are screened against; default until
+    /// `init_denylist`.
+    pub denylist: Pubkey,
+    /// SOL backing the outstanding pSOL: locked through `swap_to_psol` net of fees or added
+    /// by `deposit_rewards`, less what `swap_to_sol` has redeemed. The exchange rate is
+    /// `wrapped_locked / outstanding_supply`.
+    pub wrapped_locked: u64,
+    /// Set by the backing invariant hook; cleared by `resume_swaps`.
+    pub swaps_paused: bool,
+    /// Token metadata published for `psol_mint` by `set_mint_metadata`, zero-padded.
+    pub name: [u8; Config::MAX_NAME_LEN],
+    pub symbol: [u8; Config::MAX_SYMBOL_LEN],
+    pub uri: [u8; Config::MAX_URI_LEN],
+    /// Held for the duration of instructions that move the supply or the backing, see
+    /// `enter`.
+    pub reentrancy_lock: bool,
+    /// Zeroed space for future fields, so they can be added without a realloc.
+    pub reserved: [u8; 14],
 }
 
 impl Config {
+    pub const SPACE: usize = ConfigV1::SPACE
+        + Self::MAX_NAME_LEN
+        + Self::MAX_SYMBOL_LEN
+        + Self::MAX_URI_LEN;
+
+    pub const VERSION: u8 = 2;
+
+    /// Token Metadata program limits.
+    pub const MAX_NAME_LEN: usize = 32;
+    pub const MAX_SYMBOL_LEN: usize = 10;
+    pub const MAX_URI_LEN: usize = 200;
+
+    /// Virtual pSOL and lamports added to both sides of the exchange rate. A first wrapper
+    /// who inflates the rate with rewards donates all but a millionth of them to the
+    /// virtual shares, so rounding cannot take a meaningful cut of later wraps.
+    pub const VIRTUAL_OFFSET: u64 = 1_000_000;
+
+    /// Decodes a config stored under any layout version (discriminator included) into the
+    /// current layout.
+    pub fn decode_any_version(data: &[u8]) -> Result<Self> {
+        match layout_version::<Self>(data, 8 + ConfigV0::SPACE)? {
+            0 => Ok(ConfigV0::deserialize(&mut &data[8..])?.upgrade().upgrade()),
+            1 => Ok(ConfigV1::deserialize(&mut &data[8..])?.upgrade()),
+            Self::VERSION => Self::try_deserialize(&mut &data[..]),
+            _ => err!(ErrorCode::UnsupportedAccountVersion),
+        }
+    }
+
+    /// Decodes a config from the unseeded `["psol_config"]` PDA for `migrate_legacy_config`,
+    /// checking it belongs to `admin`, `pool` and `psol_mint` and has nothing shielded.
+    /// Returns the layout version it was stored under and the config in the current layout
+    /// with its denylist cleared; the caller sets the new bumps.
+    pub fn migrate_legacy(
+        data: &[u8],
+        admin: &Pubkey,
+        pool: &Pubkey,
+        psol_mint: &Pubkey,
+        mint_supply: u64,
+    ) -> Result<(u8, Self)> {
+        let from_version = layout_version::<Self>(data, 8 + ConfigV0::SPACE)?;
+        let mut migrated = Self::decode_any_version(data)?;
+        require_keys_eq!(migrated.admin, *admin, ErrorCode::Unauthorized);
+        require_keys_eq!(migrated.pool, *pool, ErrorCode::InvalidPool);
+        require_keys_eq!(migrated.psol_mint, *psol_mint, ErrorCode::InvalidMint);
+        require!(
+            migrated.shielded_supply == 0,
+            ErrorCode::ShieldedSupplyOutstanding
+        );
+        if from_version == 0 {
+            // v0 did not track backing; all pSOL outstanding then came from swaps.
+            migrated.wrapped_locked = mint_supply;
+        }
+        migrated.denylist = Pubkey::default();
+        Ok((from_version, migrated))
+    }
+
+    /// Takes the reentrancy lock, failing with `ReentrancyDetected` if it is already held.
+    /// A failed instruction rolls back its writes, so the lock is never left held.
+    pub fn enter(&mut self) -> Result<()> {
+        require!(!self.reentrancy_lock, ErrorCode::ReentrancyDetected);
+        self.reentrancy_lock = true;
+        Ok(())
+    }
+
+    pub fn leave(&mut self) {
+        self.reentrancy_lock = false;
+    }
+
+    /// pSOL outstanding: the SPL supply plus the shielded balances.
+    pub fn outstanding_supply(&self, spl_supply: u64) -> Result<u64> {
+        spl_supply
+            .checked_add(self.shielded_supply)
+            .ok_or_else(|| error!(ErrorCode::ArithmeticOverflow))
+    }
+
+    /// Stores the metadata `set_mint_metadata` publishes, rejecting values the Token
+    /// Metadata program would.
+    pub fn set_metadata(&mut self, name: &str, symbol: &str, uri: &str) -> Result<()> {
+        (self.name, self.symbol, self.uri) = (pad(name)?, pad(symbol)?, pad(uri)?);
+        Ok(())
+    }
+
+    /// Stored `(name, symbol, uri)`.
+    pub fn metadata(&self) -> (String, String, String) {
+        (unpad(&self.name), unpad(&self.symbol), unpad(&self.uri))
+    }
+
+    /// pSOL minted against `lamports` of new backing at the current exchange rate, 1:1
+    /// while the backing equals the supply. Rounds down, so minting never lowers the rate.
+    pub fn shares_for_lamports(&self, lamports: u64, spl_supply: u64) -> Result<u64> {
+        let outstanding = self.outstanding_supply(spl_supply)?;
+        require!(
+            outstanding == 0 || self.wrapped_locked > 0,
+            ErrorCode::BackingInvariantViolated
+        );
+        let (shares, backing) = self.virtual_totals(outstanding)?;
+        mul_div(lamports, shares, backing)
+    }
+
+    /// Lamports of backing redeemed by burning `shares` pSOL at the current exchange rate.
+    /// Rounds down, so redeeming never lowers the rate.
+    pub fn lamports_for_shares(&self, shares: u64, spl_supply: u64) -> Result<u64> {
+        let outstanding = self.outstanding_supply(spl_supply)?;
+        let (total_shares, backing) = self.virtual_totals(outstanding)?;
+        mul_div(shares, backing, total_shares)
+    }
+
+    /// `(outstanding, wrapped_locked)`, each plus `VIRTUAL_OFFSET`.
+    fn virtual_totals(&self, outstanding: u64) -> Result<(u64, u64)> {
+        let offset = |value: u64| {
+            value
+                .checked_add(Self::VIRTUAL_OFFSET)
+                .ok_or_else(|| error!(ErrorCode::ArithmeticOverflow))
+        };
+        Ok((offset(outstanding)?, offset(self.wrapped_locked)?))
+    }
+
+    /// Current exchange rate, as returned by the `exchange_rate` view.
+    pub fn exchange_rate(&self, spl_supply: u64) -> Result<ExchangeRate> {
+        Ok(ExchangeRate {
+            backing: self.wrapped_locked,
+            outstanding_supply: self.outstanding_supply(spl_supply)?,
+            lamports_per_psol: self.lamports_for_shares(ExchangeRate::PSOL, spl_supply)?,
+        })
+    }
+
+    /// Backing invariant: the outstanding supply is no greater than the SOL backing it, and
+    /// that SOL is still locked in the pool. The exchange rate starts at 1 and only rises,
+    /// so the first half holds whatever rewards have accrued.
+    pub fn is_backed(&self, spl_supply: u64, pool_total_locked: u64) -> Result<bool> {
+        Ok(self.outstanding_supply(spl_supply)? <= self.wrapped_locked
+            && self.wrapped_locked <= pool_total_locked)
+    }
+}
+
+/// Lamports per pSOL, returned by the `exchange_rate` view.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExchangeRate {
+    pub backing: u64,
+    pub outstanding_supply: u64,
+    /// Lamports redeemed by one whole pSOL (`ExchangeRate::PSOL` base units).
+    pub lamports_per_psol: u64,
+}
+
+impl ExchangeRate {
+    /// Base units in one pSOL; the mint uses SOL's 9 decimals.
+    pub const PSOL: u64 = 1_000_000_000;
+}
+
+fn pad<const N: usize>(value: &str) -> Result<[u8; N]> {
+    require!(value.len() <= N, ErrorCode::MetadataTooLong);
+    let mut padded = [0u8; N];
+    padded[..value.len()].copy_from_slice(value.as_bytes());
+    Ok(padded)
+}
+
+fn unpad(value: &[u8]) -> String {
+    let len = value.iter().rposition(|b| *b != 0).map_or(0, |i| i + 1);
+    String::from_utf8_lossy(&value[..len]).into_owned()
+}
+
+fn mul_div(value: u64, numerator: u64, denominator: u64) -> Result<u64> {
+    u64::try_from(value as u128 * numerator as u128 / denominator as u128)
+        .map_err(|_| error!(ErrorCode::ArithmeticOverflow))
+}
+
+/// `Config` layout before versioning.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ConfigV0 {
+    pub admin: Pubkey,
+    pub psol_program: Pubkey,
+    pub pool: Pubkey,
+    pub psol_mint: Pubkey,
+    pub mint_authority_bump: u8,
+    pub bump: u8,
+}
+
+impl ConfigV0 {
     pub const SPACE: usize = 32 + 32 + 32 + 32 + 1 + 1;
+
+    pub fn upgrade(self) -> ConfigV1 {
+        ConfigV1 {
+            version: 1,
+            admin: self.admin,
+            psol_program: self.psol_program,
+            pool: self.pool,
+            psol_mint: self.psol_mint,
+            mint_authority_bump: self.mint_authority_bump,
+            bump: self.bump,
+            shielded_supply: 0,
+            denylist: Pubkey::default(),
+            wrapped_locked: 0,
+            swaps_paused: false,
+            reserved: [0u8; 15],
+        }
+    }
+}
+
+/// `Config` layout version 1, before token metadata.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ConfigV1 {
+    pub version: u8,
+    pub admin: Pubkey,
+    pub psol_program: Pubkey,
+    pub pool: Pubkey,
+    pub psol_mint: Pubkey,
+    pub mint_authority_bump: u8,
+    pub bump: u8,
+    pub shielded_supply: u64,
+    pub denylist: Pubkey,
+    pub wrapped_locked: u64,
+    pub swaps_paused: bool,
+    pub reserved: [u8; 15],
+}
+
+impl ConfigV1 {
+    pub const SPACE: usize = 1 + 32 + 32 + 32 + 32 + 1 + 1 + 8 + 32 + 8 + 1 + 15;
+
+    pub fn upgrade(self) -> Config {
+        Config {
+            version: Config::VERSION,
+            admin: self.admin,
+            psol_program: self.psol_program,
+            pool: self.pool,
+            psol_mint: self.psol_mint,
+            mint_authority_bump: self.mint_authority_bump,
+            bump: self.bump,
+            shielded_supply: self.shielded_supply,
+            denylist: self.denylist,
+            wrapped_locked: self.wrapped_locked,
+            swaps_paused: self.swaps_paused,
+            name: [0u8; Config::MAX_NAME_LEN],
+            symbol: [0u8; Config::MAX_SYMBOL_LEN],
+            uri: [0u8; Config::MAX_URI_LEN],
+            reentrancy_lock: false,
+            reserved: [0u8; 14],
+        }
+    }
+}
+
+/// Confidential pSOL balance of `owner`, at `["privacy_link", config, owner]`. Balances are
+/// encrypted to `view_key` and move with `transfer_psol`; the matching SPL tokens are
+/// burned while shielded.
+#[account]
+pub struct TokenPrivacyLink {
+    pub version: u8,
+    pub owner: Pubkey,
+    pub encrypted_balance: [u8; 64],
+    pub commitment: [u8; 32],
+    pub view_key: [u8; 32],
+    pub nonce: u64,
+    pub last_sync: u64,
+    pub bump: u8,
+    /// Zeroed space for future fields, so they can be added without a realloc.
+    pub reserved: [u8; 32],
+}
+
+impl TokenPrivacyLink {
+    pub const SPACE: usize = 1 + 32 + 64 + 32 + 32 + 8 + 8 + 1 + 32;
+
+    pub const VERSION: u8 = 1;
+}
+
+/// Addresses barred from `swap_to_psol` and `swap_to_sol`, at `["denylist", config]`. Kept
+/// sorted.
+#[account]
+pub struct Denylist {
+    pub version: u8,
+    pub bump: u8,
+    pub entries: Vec<Pubkey>,
+}
+
+impl Denylist {
+    pub const MAX_ENTRIES: usize = 256;
+
+    pub const SPACE: usize = 1 + 1 + 4 + 32 * Self::MAX_ENTRIES;
+
+    pub const VERSION: u8 = 1;
+
+    pub fn contains(&self, address: &Pubkey) -> bool {
+        self.entries.binary_search(address).is_ok()
+    }
+
+    /// Adds `address`; a no-op if it is already listed.
+    pub fn insert(&mut self, address: Pubkey) -> Result<()> {
+        if let Err(index) = self.entries.binary_search(&address) {
+            require!(
+                self.entries.len() < Self::MAX_ENTRIES,
+                ErrorCode::DenylistFull
+            );
+            self.entries.insert(index, address);
+        }
+        Ok(())
+    }
+
+    /// Removes `address`; a no-op if it is not listed.
+    pub fn remove(&mut self, address: &Pubkey) {
+        if let Ok(index) = self.entries.binary_search(address) {
+            self.entries.remove(index);
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use anchor_lang::{AccountSerialize, Discriminator};
+
+    /// A current-layout config upgraded from a fresh v0 config.
+    pub(crate) fn test_config() -> Config {
+        ConfigV0 {
+            admin: Pubkey::default(),
+            psol_program: psol::ID,
+            pool: Pubkey::default(),
+            psol_mint: Pubkey::default(),
+            mint_authority_bump: 255,
+            bump: 255,
+        }
+        .upgrade()
+        .upgrade()
+    }
+
+    #[test]
+    fn token_config_layouts_decode() {
+        let v0 = ConfigV0 {
+            admin: Pubkey::new_unique(),
+            psol_program: psol::ID,
+            pool: Pubkey::new_unique(),
+            psol_mint: Pubkey::new_unique(),
+            mint_authority_bump: 255,
+            bump: 254,
+        };
+        let mut data = Config::DISCRIMINATOR.to_vec();
+        v0.serialize(&mut data).unwrap();
+        assert_eq!(data.len(), 8 + ConfigV0::SPACE);
+        let config = Config::decode_any_version(&data).unwrap();
+        assert_eq!(config.version, Config::VERSION);
+        assert_eq!((config.pool, config.psol_mint), (v0.pool, v0.psol_mint));
+        assert_eq!((config.mint_authority_bump, config.bump), (255, 254));
+
+        let mut v1 = v0.clone().upgrade();
+        v1.wrapped_locked = 7;
+        let mut data = Config::DISCRIMINATOR.to_vec();
+        v1.serialize(&mut data).unwrap();
+        assert_eq!(data.len(), 8 + ConfigV1::SPACE);
+        let config = Config::decode_any_version(&data).unwrap();
+        assert_eq!((config.version, config.wrapped_locked), (Config::VERSION, 7));
+        assert_eq!(config.metadata(), (String::new(), String::new(), String::new()));
+
+        let mut current = Vec::new();
+        config.try_serialize(&mut current).unwrap();
+        assert_eq!(current.len(), 8 + Config::SPACE);
+        assert_eq!(Config::decode_any_version(&current).unwrap().admin, v0.admin);
+        assert!(Config::decode_any_version(&[]).is_err());
+    }
+
+    #[test]
+    fn legacy_configs_migrate_to_the_current_layout() {
+        let v0 = ConfigV0 {
+            admin: Pubkey::new_unique(),
+            psol_program: psol::ID,
+            pool: Pubkey::new_unique(),
+            psol_mint: Pubkey::new_unique(),
+            mint_authority_bump: 255,
+            bump: 254,
+        };
+        let (admin, pool, mint) = (v0.admin, v0.pool, v0.psol_mint);
+        let mut data = Config::DISCRIMINATOR.to_vec();
+        v0.clone().serialize(&mut data).unwrap();
+        let (from_version, config) =
+            Config::migrate_legacy(&data, &admin, &pool, &mint, 500).unwrap();
+        assert_eq!((from_version, config.version), (0, Config::VERSION));
+        // v0 predates backing; every outstanding pSOL was wrapped.
+        assert_eq!(config.wrapped_locked, 500);
+
+        let other = Pubkey::new_unique();
+        assert_eq!(
+            Config::migrate_legacy(&data, &other, &pool, &mint, 500).map(|_| ()),
+            Err(ErrorCode::Unauthorized.into())
+        );
+        assert_eq!(
+            Config::migrate_legacy(&data, &admin, &other, &mint, 500).map(|_| ()),
+            Err(ErrorCode::InvalidPool.into())
+        );
+        assert_eq!(
+            Config::migrate_legacy(&data, &admin, &pool, &other, 500).map(|_| ()),
+            Err(ErrorCode::InvalidMint.into())
+        );
+
+        let mut v1 = v0.upgrade();
+        v1.wrapped_locked = 7;
+        v1.denylist = Pubkey::new_unique();
+        let mut data = Config::DISCRIMINATOR.to_vec();
+        v1.serialize(&mut data).unwrap();
+        let (from_version, config) =
+            Config::migrate_legacy(&data, &admin, &pool, &mint, 500).unwrap();
+        assert_eq!((from_version, config.wrapped_locked), (1, 7));
+        // The denylist is seeded by the old config and has to be recreated.
+        assert_eq!(config.denylist, Pubkey::default());
+
+        v1.shielded_supply = 1;
+        let mut data = Config::DISCRIMINATOR.to_vec();
+        v1.serialize(&mut data).unwrap();
+        assert_eq!(
+            Config::migrate_legacy(&data, &admin, &pool, &mint, 500).map(|_| ()),
+            Err(ErrorCode::ShieldedSupplyOutstanding.into())
+        );
+    }
+
+    #[test]
+    fn mint_metadata_fits_token_metadata_limits() {
+        let mut config = test_config();
+        config
+            .set_metadata("Private SOL", "pSOL", "https://psol.example/meta.json")
+            .unwrap();
+        assert_eq!(
+            config.metadata(),
+            (
+                "Private SOL".to_string(),
+                "pSOL".to_string(),
+                "https://psol.example/meta.json".to_string()
+            )
+        );
+
+        let long_symbol = "S".repeat(Config::MAX_SYMBOL_LEN + 1);
+        assert_eq!(
+            config.set_metadata("Private SOL", &long_symbol, ""),
+            Err(ErrorCode::MetadataTooLong.into())
+        );
+        let name = "N".repeat(Config::MAX_NAME_LEN);
+        config.set_metadata(&name, "", "").unwrap();
+        assert_eq!(config.metadata().0, name);
+    }
+
+    #[test]
+    fn rewards_raise_the_exchange_rate() {
+        let mut config = test_config();
+        assert_eq!(config.exchange_rate(0).unwrap().lamports_per_psol, ExchangeRate::PSOL);
+
+        // The first wrap mints 1:1; rewards then accrue to the holder.
+        let mut supply = config.shares_for_lamports(2_000_000_000, 0).unwrap();
+        config.wrapped_locked = 2_000_000_000;
+        assert_eq!(supply, 2_000_000_000);
+        config.wrapped_locked += 500_000_000;
+        assert_eq!(
+            config.exchange_rate(supply).unwrap(),
+            ExchangeRate {
+                backing: 2_500_000_000,
+                outstanding_supply: 2_000_000_000,
+                // The virtual shares hold a sliver of the rewards.
+                lamports_per_psol: 1_249_875_062,
+            }
+        );
+
+        // Later wraps buy in at the higher rate and do not dilute earlier holders.
+        let held = config.lamports_for_shares(2_000_000_000, supply).unwrap();
+        let minted = config.shares_for_lamports(1_000_000_000, supply).unwrap();
+        assert_eq!(minted, 800_079_968);
+        supply += minted;
+        config.wrapped_locked += 1_000_000_000;
+        assert_eq!(config.lamports_for_shares(2_000_000_000, supply).unwrap(), held);
+        assert!(config.is_backed(supply, config.wrapped_locked).unwrap());
+
+        // Rounding favours the backing: one base unit cannot mint or redeem more than it is
+        // worth.
+        assert_eq!(config.shares_for_lamports(1, supply).unwrap(), 0);
+        assert_eq!(config.lamports_for_shares(1, supply).unwrap(), 1);
+        config.shielded_supply = 1;
+        assert!(config.lamports_for_shares(supply + 1, supply).unwrap() <= config.wrapped_locked);
+
+        // Outstanding pSOL with nothing behind it has no rate to mint at.
+        config.wrapped_locked = 0;
+        assert!(config.shares_for_lamports(1_000, supply).is_err());
+    }
+
+    #[test]
+    fn first_wrapper_cannot_inflate_the_rate_against_later_wraps() {
+        let mut config = test_config();
+
+        // The attacker wraps one lamport, then donates 10 SOL of rewards to the backing.
+        let mut supply = config.shares_for_lamports(1, 0).unwrap();
+        assert_eq!(supply, 1);
+        config.wrapped_locked = 1 + 10_000_000_000;
+
+        // A 0.01 SOL wrap still mints, and redeems for all but a few hundred lamports.
+        let deposit = 10_000_000;
+        let minted = config.shares_for_lamports(deposit, supply).unwrap();
+        assert!(minted > 0);
+        supply += minted;
+        config.wrapped_locked += deposit;
+        let redeemed = config.lamports_for_shares(minted, supply).unwrap();
+        assert!(deposit - redeemed < 20_000, "victim lost {}", deposit - redeemed);
+
+        // The attacker's share of their own donation is a millionth of it.
+        let attacker = config.lamports_for_shares(1, supply).unwrap();
+        assert!(attacker < 20_000, "attacker redeems {attacker}");
+        assert!(config.is_backed(supply, config.wrapped_locked).unwrap());
+    }
+
+    #[test]
+    fn shielded_psol_counts_toward_backing() {
+        let mut config = test_config();
+        assert_eq!(config.shielded_supply, 0);
+        assert!(!config.swaps_paused);
+        config.wrapped_locked = 1_000;
+        let locked = 1_000;
+
+        // Shielding burns SPL pSOL but the shielded amount stays outstanding.
+        let spl_supply = 1_000 - 400;
+        config.shielded_supply = 400;
+        assert_eq!(config.outstanding_supply(spl_supply).unwrap(), 1_000);
+        assert!(config.is_backed(spl_supply, locked).unwrap());
+
+        // Minting SPL pSOL without unshielding would leave it unbacked.
+        assert!(!config.is_backed(spl_supply + 1, locked).unwrap());
+    }
+
+    #[test]
+    fn backing_breaks_when_pool_loses_wrapped_sol() {
+        let mut config = test_config();
+        config.wrapped_locked = 500;
+
+        // SOL that left the pool outside the swaps (a private withdrawal by a wrapper, say)
+        // leaves pSOL unbacked even though the supply matches wrapped_locked.
+        assert!(config.is_backed(500, 800).unwrap());
+        assert!(!config.is_backed(500, 499).unwrap());
+        assert!(!config.is_backed(501, 800).unwrap());
+    }
+
+    #[test]
+    fn reentrancy_lock_rejects_nested_entry() {
+        let mut config = test_config();
+        config.enter().unwrap();
+        assert_eq!(config.enter(), Err(ErrorCode::ReentrancyDetected.into()));
+        config.leave();
+        config.enter().unwrap();
+    }
+
+    #[test]
+    fn denylist_stays_sorted_and_bounded() {
+        let mut denylist = Denylist {
+            version: Denylist::VERSION,
+            bump: 255,
+            entries: Vec::new(),
+        };
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        denylist.insert(b).unwrap();
+        denylist.insert(a).unwrap();
+        denylist.insert(a).unwrap();
+        assert_eq!(denylist.entries.len(), 2);
+        assert!(denylist.entries.windows(2).all(|w| w[0] < w[1]));
+        assert!(denylist.contains(&a) && denylist.contains(&b));
+
+        denylist.remove(&a);
+        assert!(!denylist.contains(&a));
+        denylist.remove(&a);
+
+        while denylist.entries.len() < Denylist::MAX_ENTRIES {
+            denylist.insert(Pubkey::new_unique()).unwrap();
+        }
+        assert!(denylist.insert(a).is_err());
+        denylist.insert(b).unwrap();
+    }
+}
@@ -9,12 +9,59 @@ pub fn verify_proof(proof: &[u8], public_inputs: &[[u8; 32]]) -> Result<bool> {
     Ok(proof.len() >= 32 && !public_inputs.is_empty())
 }
 
+/// Verify several proofs at once (placeholder).
+///
+/// A Groth16 backend folds the batch into one pairing check over a random linear
+/// combination of the proofs; the placeholder checks each proof in turn. Fails if any
+/// proof fails.
+pub fn verify_proof_batch(proofs: &[&[u8]], public_inputs: &[Vec<[u8; 32]>]) -> Result<bool> {
+    require!(
+        proofs.len() == public_inputs.len(),
+        ErrorCode::InvalidBatchSize
+    );
+    for (proof, inputs) in proofs.iter().zip(public_inputs) {
+        if !verify_proof(proof, inputs)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Public inputs folded into the verifier state per `verify_proof_step` call.
+pub const INPUTS_PER_STEP: usize = 4;
+
+/// Advance a verification that is split across transactions (placeholder).
+///
+/// The first steps fold `INPUTS_PER_STEP` public inputs each into `accumulator` (the
+/// `vk_x` multi-scalar multiplication of a Groth16 verifier); the final step runs the
+/// pairing check. Returns `true` once the proof is accepted, and fails if it is rejected.
+pub fn verify_proof_step(
+    proof: &[u8],
+    public_inputs: &[[u8; 32]],
+    step: &mut u32,
+    accumulator: &mut [u8; 32],
+) -> Result<bool> {
+    let folded = *step as usize * INPUTS_PER_STEP;
+    if folded < public_inputs.len() {
+        let end = (folded + INPUTS_PER_STEP).min(public_inputs.len());
+        for input in &public_inputs[folded..end] {
+            *accumulator =
+                anchor_lang::solana_program::hash::hashv(&[accumulator, input]).to_bytes();
+        }
+        *step += 1;
+        return Ok(false);
+    }
+    require!(verify_proof(proof, public_inputs)?, ErrorCode::InvalidProof);
+    *step += 1;
+    Ok(true)
+}
+
 /// Generate Pedersen commitment (placeholder)
 pub fn pedersen_commit(value: u64, blinding: &[u8; 32]) -> [u8; 32] {
     let mut data = Vec::new();
     data.extend_from_slice(&value.to_le_bytes());
     data.extend_from_slice(blinding);
-    solana_program::hash::hash(&data).to_bytes()
+    anchor_lang::solana_program::hash::hash(&data).to_bytes()
 }
 
 /// Encrypt amount (placeholder XOR, NOT production secure)
@@ -29,11 +76,11 @@ pub fn encrypt_amount(amount: u64, public_key: &[u8; 32]) -> [u8; 64] {
     encrypted
 }
 
-/// Decrypt amount (placeholder)
-pub fn decrypt_amount(ciphertext: &[u8; 64], secret_key: &[u8; 32]) -> Result<u64> {
+/// Decrypt amount (placeholder, keyed by the same public key as `encrypt_amount`)
+pub fn decrypt_amount(ciphertext: &[u8; 64], key: &[u8; 32]) -> Result<u64> {
     let mut amount_bytes = [0u8; 8];
     for i in 0..8 {
-        amount_bytes[i] = ciphertext[i] ^ secret_key[i % 32];
+        amount_bytes[i] = ciphertext[i] ^ key[i % 32];
     }
     Ok(u64::from_le_bytes(amount_bytes))
 }
@@ -57,7 +104,7 @@ pub fn generate_nullifier(commitment: &[u8; 32], secret: &[u8; 32]) -> [u8; 32]
     let mut data = Vec::new();
     data.extend_from_slice(commitment);
     data.extend_from_slice(secret);
-    solana_program::hash::hash(&data).to_bytes()
+    anchor_lang::solana_program::hash::hash(&data).to_bytes()
 }
 
 /// Verify nullifier hasn't been used (checked by NullifierSet PDA)
@@ -65,20 +112,198 @@ pub fn verify_nullifier_unused(_nullifier: &[u8; 32]) -> Result<bool> {
     Ok(true)
 }
 
+/* ============================================================
+   Encrypted memos
+   ============================================================ */
+
+/// Largest encrypted memo envelope accepted on transfers and deposits.
+pub const MAX_MEMO_LEN: usize = 256;
+
+/// Envelope header: X25519 ephemeral public key (32) followed by an XChaCha20 nonce (24).
+pub const MEMO_HEADER_LEN: usize = 32 + 24;
+
+/// Poly1305 tag appended to the memo ciphertext.
+pub const MEMO_TAG_LEN: usize = 16;
+
+/// Structural check of an encrypted memo envelope.
+///
+/// Memos are sealed off-chain to the recipient's `view_key` with
+/// X25519 + XChaCha20-Poly1305 as `ephemeral_pubkey || nonce || ciphertext || tag`.
+/// The program cannot decrypt them; it only bounds their size and forwards them in events.
+pub fn validate_memo(memo: &[u8]) -> Result<()> {
+    require!(memo.len() <= MAX_MEMO_LEN, ErrorCode::MemoTooLong);
+    require!(
+        memo.len() > MEMO_HEADER_LEN + MEMO_TAG_LEN,
+        ErrorCode::InvalidMemo
+    );
+    Ok(())
+}
+
+/* ============================================================
+   SnarkPack proof aggregation
+   ============================================================ */
+
+/// Most Groth16 proofs a single aggregate may cover.
+pub const MAX_AGGREGATED_PROOFS: usize = 8;
+
+const AGGREGATION_DOMAIN: &[u8] = b"psol-snarkpack-v1";
+
+/// `amount` as a little-endian public input.
+fn amount_word(amount: u64) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[..8].copy_from_slice(&amount.to_le_bytes());
+    word
+}
+
+/// Public inputs of `withdraw_private` and `batch_withdraw`. The proof shows the holder of
+/// `spend_key` can spend the balance under `commitment` and that it covers `amount`.
+pub fn withdrawal_inputs(
+    commitment: &[u8; 32],
+    spend_key: &[u8; 32],
+    nullifier: &[u8; 32],
+    amount: u64,
+) -> Vec<[u8; 32]> {
+    vec![*commitment, *spend_key, *nullifier, amount_word(amount)]
+}
+
+/// Public inputs of a withdrawal proved inside an aggregate. The recipient and amount
+/// are bound so a relayer submitting the aggregate cannot redirect or resize a payout.
+pub fn aggregated_withdrawal_inputs(
+    commitment: &[u8; 32],
+    spend_key: &[u8; 32],
+    nullifier: &[u8; 32],
+    recipient: &Pubkey,
+    amount: u64,
+) -> Vec<[u8; 32]> {
+    vec![
+        *commitment,
+        *spend_key,
+        *nullifier,
+        recipient.to_bytes(),
+        amount_word(amount),
+    ]
+}
+
+/// Fiat-Shamir challenge binding an aggregate proof to every statement it covers, in order.
+pub fn aggregation_transcript(public_inputs: &[Vec<[u8; 32]>]) -> [u8; 32] {
+    let mut data = Vec::with_capacity(
+        AGGREGATION_DOMAIN.len() + 8 + public_inputs.iter().map(|i| 8 + 32 * i.len()).sum::<usize>(),
+    );
+    data.extend_from_slice(AGGREGATION_DOMAIN);
+    data.extend_from_slice(&(public_inputs.len() as u64).to_le_bytes());
+    for inputs in public_inputs {
+        data.extend_from_slice(&(inputs.len() as u64).to_le_bytes());
+        for input in inputs {
+            data.extend_from_slice(input);
+        }
+    }
+    anchor_lang::solana_program::hash::hash(&data).to_bytes()
+}
+
+/// Verify a SnarkPack aggregate of Groth16 proofs, one per entry of `public_inputs`
+/// (placeholder).
+///
+/// The aggregate opens with the transcript challenge `r`. The full verifier checks the
+/// TIPP/MIPP arguments for `Σ rⁱ·e(Aᵢ, Bᵢ)` and `Σ rⁱ·Cᵢ` and a single combined pairing
+/// equation with the alt_bn128 syscalls, so cost grows logarithmically in the number of
+/// proofs; the placeholder checks the transcript binding and the proof length.
+///
+/// The transcript is a hash of public data, so anyone can build an aggregate that passes
+/// for any statements: like `verify_proof`, this proves nothing until the pairing checks
+/// land. It only ties an aggregate to the exact statements it was built for.
+pub fn verify_aggregate_proof(aggregate: &[u8], public_inputs: &[Vec<[u8; 32]>]) -> Result<bool> {
+    require!(
+        !public_inputs.is_empty() && public_inputs.len() <= MAX_AGGREGATED_PROOFS,
+        ErrorCode::InvalidBatchSize
+    );
+    if aggregate.len() < 64 || aggregate[..32] != aggregation_transcript(public_inputs) {
+        return Ok(false);
+    }
+    Ok(public_inputs.iter().all(|inputs| !inputs.is_empty()))
+}
+
 /* ============================================================
    Aliases required by instructions (so transfer.rs compiles)
    ============================================================ */
 
+/// The proof is bound to the sender's `spend_key`, so only its holder can move the balance.
 pub fn verify_transfer_proof(
     _sender_balance: &[u8; 64],
     _encrypted_amount: &[u8; 64],
     sender_commitment: &[u8; 32],
+    sender_spend_key: &[u8; 32],
     proof: &[u8],
 ) -> bool {
-    match verify_proof(proof, &[*sender_commitment]) {
-        Ok(v) => v,
-        Err(_) => false,
-    }
+    verify_proof(proof, &[*sender_commitment, *sender_spend_key]).unwrap_or_default()
+}
+
+/// Public inputs of a re-encryption proof: both ciphertexts, hashed to one word each, and
+/// both keys.
+pub fn reencryption_inputs(
+    old_ciphertext: &[u8; 64],
+    new_ciphertext: &[u8; 64],
+    old_key: &[u8; 32],
+    new_key: &[u8; 32],
+) -> [[u8; 32]; 4] {
+    [
+        anchor_lang::solana_program::hash::hash(old_ciphertext).to_bytes(),
+        anchor_lang::solana_program::hash::hash(new_ciphertext).to_bytes(),
+        *old_key,
+        *new_key,
+    ]
+}
+
+/// Verify that `old_ciphertext` under `old_key` and `new_ciphertext` under `new_key`
+/// encrypt the same value (placeholder until the equality proof circuit lands).
+pub fn verify_reencryption_proof(
+    old_ciphertext: &[u8; 64],
+    new_ciphertext: &[u8; 64],
+    old_key: &[u8; 32],
+    new_key: &[u8; 32],
+    proof: &[u8],
+) -> bool {
+    let public_inputs = reencryption_inputs(old_ciphertext, new_ciphertext, old_key, new_key);
+    verify_proof(proof, &public_inputs).unwrap_or(false)
+}
+
+/// Verify that `encrypted_amount` under `view_key` encrypts the `amount` that `commitment`
+/// adds to a privacy account (placeholder).
+pub fn verify_deposit_proof(
+    commitment: &[u8; 32],
+    encrypted_amount: &[u8; 64],
+    view_key: &[u8; 32],
+    amount: u64,
+    proof: &[u8],
+) -> bool {
+    let mut amount_input = [0u8; 32];
+    amount_input[..8].copy_from_slice(&amount.to_le_bytes());
+    let public_inputs = [
+        *commitment,
+        anchor_lang::solana_program::hash::hash(encrypted_amount).to_bytes(),
+        *view_key,
+        amount_input,
+    ];
+    verify_proof(proof, &public_inputs).unwrap_or(false)
+}
+
+/// Verify that the holder of `old_spend_key` hands `commitment` over to `new_spend_key`,
+/// whose holder can open it (placeholder).
+pub fn verify_spend_key_rotation_proof(
+    commitment: &[u8; 32],
+    old_spend_key: &[u8; 32],
+    new_spend_key: &[u8; 32],
+    proof: &[u8],
+) -> bool {
+    verify_proof(proof, &[*commitment, *old_spend_key, *new_spend_key]).unwrap_or(false)
+}
+
+/// Verify that `ciphertext` under `view_key` encrypts zero (placeholder).
+pub fn verify_zero_balance_proof(ciphertext: &[u8; 64], view_key: &[u8; 32], proof: &[u8]) -> bool {
+    let public_inputs = [
+        anchor_lang::solana_program::hash::hash(ciphertext).to_bytes(),
+        *view_key,
+    ];
+    verify_proof(proof, &public_inputs).unwrap_or(false)
 }
 
 pub fn add_encrypted(a: &[u8; 64], b: &[u8; 64]) -> Result<[u8; 64]> {
@@ -88,3 +313,119 @@ pub fn add_encrypted(a: &[u8; 64], b: &[u8; 64]) -> Result<[u8; 64]> {
 pub fn subtract_encrypted(a: &[u8; 64], b: &[u8; 64]) -> Result<[u8; 64]> {
     Ok(homomorphic_sub(a, b))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crypto_helpers_are_stable() {
+        let blinding = [1u8; 32];
+        let c1 = pedersen_commit(10, &blinding);
+        assert_eq!(c1, pedersen_commit(10, &blinding));
+        assert_ne!(c1, pedersen_commit(11, &blinding));
+        let n = generate_nullifier(&c1, &[2u8; 32]);
+        assert_ne!(n, [0u8; 32]);
+        assert_ne!(n, generate_nullifier(&c1, &[3u8; 32]));
+    }
+
+    #[test]
+    fn memo_envelope_bounds() {
+        let shortest = MEMO_HEADER_LEN + MEMO_TAG_LEN + 1;
+        assert!(validate_memo(&vec![0u8; shortest]).is_ok());
+        assert!(validate_memo(&vec![0u8; MAX_MEMO_LEN]).is_ok());
+        // Header and tag with no ciphertext between them.
+        assert_eq!(
+            validate_memo(&vec![0u8; shortest - 1]),
+            Err(ErrorCode::InvalidMemo.into())
+        );
+        assert_eq!(validate_memo(&[]), Err(ErrorCode::InvalidMemo.into()));
+        assert_eq!(
+            validate_memo(&vec![0u8; MAX_MEMO_LEN + 1]),
+            Err(ErrorCode::MemoTooLong.into())
+        );
+    }
+
+    #[test]
+    fn reencryption_statement_binds_both_ciphertexts() {
+        let (old_key, new_key) = ([1u8; 32], [2u8; 32]);
+        let old = encrypt_amount(50, &old_key);
+        let new = encrypt_amount(50, &new_key);
+        let inputs = reencryption_inputs(&old, &new, &old_key, &new_key);
+        let other_old = encrypt_amount(51, &old_key);
+        let other_new = encrypt_amount(51, &new_key);
+        assert_ne!(inputs, reencryption_inputs(&other_old, &new, &old_key, &new_key));
+        assert_ne!(inputs, reencryption_inputs(&old, &other_new, &old_key, &new_key));
+        assert_ne!(inputs, reencryption_inputs(&old, &new, &[3u8; 32], &new_key));
+        assert_ne!(inputs, reencryption_inputs(&old, &new, &old_key, &[3u8; 32]));
+        // Swapping the ciphertexts is a different statement too.
+        assert_ne!(inputs, reencryption_inputs(&new, &old, &old_key, &new_key));
+        assert!(verify_reencryption_proof(&old, &new, &old_key, &new_key, &[7u8; 64]));
+        assert!(!verify_reencryption_proof(&old, &new, &old_key, &new_key, &[]));
+    }
+
+    #[test]
+    fn batch_proof_verification_rejects_any_bad_item() {
+        let good = [7u8; 64];
+        let inputs = vec![vec![[1u8; 32]], vec![[2u8; 32]]];
+        assert!(verify_proof_batch(&[&good[..], &good[..]], &inputs).unwrap());
+        assert!(!verify_proof_batch(&[&good[..], &[]], &inputs).unwrap());
+        assert!(verify_proof_batch(&[&good[..]], &inputs).is_err());
+    }
+
+    #[test]
+    fn aggregate_proof_is_bound_to_every_statement() {
+        let recipient = Pubkey::new_unique();
+        let statements: Vec<_> = (1..=3u8)
+            .map(|i| {
+                aggregated_withdrawal_inputs(&[i; 32], &[9u8; 32], &[i + 10; 32], &recipient, 100)
+            })
+            .collect();
+        let mut aggregate = aggregation_transcript(&statements).to_vec();
+        aggregate.extend_from_slice(&[0u8; 32]);
+        assert!(verify_aggregate_proof(&aggregate, &statements).unwrap());
+
+        // Redirecting one payout, resizing it, or dropping a statement breaks the binding.
+        let mut redirected = statements.clone();
+        redirected[1] = aggregated_withdrawal_inputs(
+            &[2u8; 32],
+            &[9u8; 32],
+            &[12u8; 32],
+            &Pubkey::new_unique(),
+            100,
+        );
+        assert!(!verify_aggregate_proof(&aggregate, &redirected).unwrap());
+        let mut resized = statements.clone();
+        resized[2] =
+            aggregated_withdrawal_inputs(&[3u8; 32], &[9u8; 32], &[13u8; 32], &recipient, 101);
+        assert!(!verify_aggregate_proof(&aggregate, &resized).unwrap());
+        assert!(!verify_aggregate_proof(&aggregate, &statements[..2]).unwrap());
+
+        let too_many = vec![statements[0].clone(); MAX_AGGREGATED_PROOFS + 1];
+        assert!(verify_aggregate_proof(&aggregate, &too_many).is_err());
+    }
+
+    #[test]
+    fn aggregate_proof_rejects_mismatched_public_inputs() {
+        let recipient = Pubkey::new_unique();
+        let statements = vec![
+            aggregated_withdrawal_inputs(&[1u8; 32], &[9u8; 32], &[11u8; 32], &recipient, 100),
+            aggregated_withdrawal_inputs(&[2u8; 32], &[9u8; 32], &[12u8; 32], &recipient, 200),
+        ];
+        let mut aggregate = aggregation_transcript(&statements).to_vec();
+        aggregate.extend_from_slice(&[0u8; 32]);
+
+        // Same statements in another order.
+        let reordered = vec![statements[1].clone(), statements[0].clone()];
+        assert!(!verify_aggregate_proof(&aggregate, &reordered).unwrap());
+        // A statement with the single-withdrawal inputs, which leave out the recipient.
+        let mut unbound = statements.clone();
+        unbound[0] = withdrawal_inputs(&[1u8; 32], &[9u8; 32], &[11u8; 32], 100);
+        assert!(!verify_aggregate_proof(&aggregate, &unbound).unwrap());
+        // Inputs moved from one statement to the next keep the same flattened words.
+        let mut shifted = statements.clone();
+        let moved = shifted[0].pop().unwrap();
+        shifted[1].insert(0, moved);
+        assert!(!verify_aggregate_proof(&aggregate, &shifted).unwrap());
+    }
+}
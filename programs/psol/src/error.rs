@@ -48,4 +48,124 @@ pub enum ErrorCode {
 
     #[msg("Reentrancy detected.")]
     ReentrancyDetected,
+
+    #[msg("Withdrawal exceeds the per-withdrawal limit.")]
+    WithdrawalTooLarge,
+
+    #[msg("Withdrawal exceeds the limit for the current window.")]
+    WithdrawalRateLimited,
+
+    #[msg("Pause mask contains unknown flags.")]
+    InvalidPauseMask,
+
+    #[msg("Emergency exit is not available.")]
+    EmergencyExitUnavailable,
+
+    #[msg("Account has sent private transfers and cannot use the emergency exit.")]
+    EmergencyExitIneligible,
+
+    #[msg("Encrypted memo too long.")]
+    MemoTooLong,
+
+    #[msg("Encrypted memo is malformed.")]
+    InvalidMemo,
+
+    #[msg("Pool requires an auditor ciphertext.")]
+    AuditorCiphertextRequired,
+
+    #[msg("Invalid encryption key.")]
+    InvalidEncryptionKey,
+
+    #[msg("Invalid nullifier.")]
+    InvalidNullifier,
+
+    #[msg("Invalid Merkle proof.")]
+    InvalidMerkleProof,
+
+    #[msg("Nullifier archive is full.")]
+    NullifierArchiveFull,
+
+    #[msg("Nullifier record is too recent to archive.")]
+    NullifierTooRecent,
+
+    #[msg("Merkle root is not in the tree's recent root history.")]
+    StaleMerkleRoot,
+
+    #[msg("Leaf was modified after the proof's root; rebuild the proof.")]
+    MerkleLeafChanged,
+
+    #[msg("Storage backend accounts do not match the pool's backend.")]
+    StorageBackendMismatch,
+
+    #[msg("Batch must hold between 1 and MAX_BATCH_SIZE items.")]
+    InvalidBatchSize,
+
+    #[msg("Remaining accounts do not match the batch's nullifier records.")]
+    InvalidBatchAccounts,
+
+    #[msg("Proof buffer is not in the stage this instruction requires.")]
+    ProofBufferNotReady,
+
+    #[msg("Proof buffer was prepared for a different withdrawal.")]
+    ProofBufferMismatch,
+
+    #[msg("Proof chunk is out of order or overruns the declared length.")]
+    InvalidProofChunk,
+
+    #[msg("Proof or public inputs exceed the proof buffer limits.")]
+    ProofTooLarge,
+
+    #[msg("Pool storage is being migrated; operations resume once it completes.")]
+    MigrationInProgress,
+
+    #[msg("Pool storage is already on the current layout.")]
+    PoolAlreadyMigrated,
+
+    #[msg("Account layout version is newer than this program understands.")]
+    UnsupportedAccountVersion,
+
+    #[msg("Account is already on the current layout.")]
+    AccountAlreadyCurrent,
+
+    #[msg("Account type cannot be upgraded.")]
+    UnsupportedAccountType,
+
+    #[msg("Stake target must be at most 10000 basis points.")]
+    InvalidStakeTarget,
+
+    #[msg("Stake is already at its target or a transient account awaits settle_stake.")]
+    NothingToRebalance,
+
+    #[msg("No transient stake account to settle.")]
+    NoTransientStake,
+
+    #[msg("Call policy mask has undefined bits.")]
+    InvalidCallPolicy,
+
+    #[msg("Caller allowlist is full.")]
+    CallerAllowlistFull,
+
+    #[msg("The pool's caller allowlist must be supplied.")]
+    CallerAllowlistRequired,
+
+    #[msg("Calling program is not on the pool's caller allowlist.")]
+    UnapprovedCaller,
+
+    #[msg("Withdrawals cannot follow a deposit in the same transaction.")]
+    DepositThenWithdraw,
+
+    #[msg("Commitment does not extend the latest pending deposit's commitment.")]
+    CommitmentMismatch,
+
+    #[msg("No pending deposits to claim.")]
+    NoPendingDeposits,
+
+    #[msg("Emergency delay is below the minimum.")]
+    EmergencyDelayTooShort,
+
+    #[msg("Invalid spend key.")]
+    InvalidSpendKey,
+
+    #[msg("Sender and recipient accounts must differ.")]
+    SelfTransfer,
 }
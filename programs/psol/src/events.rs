@@ -0,0 +1,11 @@
+use anchor_lang::prelude::*;
+
+/// Encrypted memo sealed to the `view_key` of `recipient_account`. Emitted by every
+/// instruction that accepts a memo: `deposit_private`, `deposit_for`, `transfer_private`
+/// and `transfer_stealth`.
+#[event]
+pub struct MemoAttached {
+    pub pool: Pubkey,
+    pub recipient_account: Pubkey,
+    pub memo: Vec<u8>,
+}
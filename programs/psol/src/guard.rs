@@ -0,0 +1,187 @@
+//! Reentrancy lock and call policy checks for the instructions that move `total_locked`.
+//!
+//! The call policy reads the instructions sysvar, which lists only the transaction's
+//! top-level instructions. A CPI into psol is attributed to the top-level program that led
+//! to it, and a deposit made inside another program's instruction is visible only to that
+//! program; psol_token screens its own swaps the same way.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
+use anchor_lang::Discriminator;
+
+use crate::error::ErrorCode;
+use crate::state::{CallPolicy, CallerAllowlist, Operation, PrivacyAccount, PrivacyPool};
+
+/// psol instructions that deposit into the pool, for `CallPolicy::NoDepositThenWithdraw`.
+pub const DEPOSIT_INSTRUCTIONS: [[u8; 8]; 3] = [
+    crate::instruction::DepositPrivate::DISCRIMINATOR,
+    crate::instruction::DepositFor::DISCRIMINATOR,
+    crate::instruction::BatchDeposit::DISCRIMINATOR,
+];
+
+/// Takes the pool's reentrancy lock and writes it through to the account before any CPI,
+/// so a nested call into psol sees it. Anchor writes the released lock back on success.
+pub fn enter(pool: &mut Account<PrivacyPool>) -> Result<()> {
+    pool.enter()?;
+    pool.exit(&crate::ID)
+}
+
+/// Enforces the pool's `CallPolicy` flags for `op`, a deposit or a withdrawal.
+pub fn check_call_policy(
+    pool: &PrivacyPool,
+    allowlist: Option<&CallerAllowlist>,
+    instructions_sysvar: &AccountInfo,
+    op: Operation,
+) -> Result<()> {
+    if pool.enforces(CallPolicy::ApprovedCallersOnly) {
+        let caller = top_level_program(instructions_sysvar)?;
+        if caller != crate::ID {
+            let allowlist = allowlist.ok_or(ErrorCode::CallerAllowlistRequired)?;
+            require!(allowlist.contains(&caller), ErrorCode::UnapprovedCaller);
+        }
+    }
+    if op == Operation::Withdraw && pool.enforces(CallPolicy::NoDepositThenWithdraw) {
+        require!(
+            !preceded_by(instructions_sysvar, &crate::ID, &DEPOSIT_INSTRUCTIONS)?,
+            ErrorCode::DepositThenWithdraw
+        );
+    }
+    Ok(())
+}
+
+/// `CallPolicy::NoDepositThenWithdraw` for deposits the instructions sysvar cannot show:
+/// a withdrawal fails when its account was deposited into or credited in the same slot,
+/// whether directly, through CPI or in an earlier transaction.
+pub fn check_not_deposited_in_slot(
+    pool: &PrivacyPool,
+    privacy_account: &PrivacyAccount,
+    slot: u64,
+) -> Result<()> {
+    if pool.enforces(CallPolicy::NoDepositThenWithdraw) {
+        require!(
+            privacy_account.last_deposit_slot != slot,
+            ErrorCode::DepositThenWithdraw
+        );
+    }
+    Ok(())
+}
+
+/// Program of the top-level instruction currently executing.
+pub fn top_level_program(instructions_sysvar: &AccountInfo) -> Result<Pubkey> {
+    let index = load_current_index_checked(instructions_sysvar)?;
+    Ok(load_instruction_at_checked(index as usize, instructions_sysvar)?.program_id)
+}
+
+/// Whether a top-level instruction before the current one calls `program_id` with one of
+/// `discriminators`.
+pub fn preceded_by(
+    instructions_sysvar: &AccountInfo,
+    program_id: &Pubkey,
+    discriminators: &[[u8; 8]],
+) -> Result<bool> {
+    let current = load_current_index_checked(instructions_sysvar)? as usize;
+    for index in 0..current {
+        let instruction = load_instruction_at_checked(index, instructions_sysvar)?;
+        if instruction.program_id == *program_id
+            && instruction.data.len() >= 8
+            && discriminators.iter().any(|d| instruction.data[..8] == d[..])
+        {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::pool::tests::test_pool;
+    use crate::state::privacy_accounts::tests::test_account;
+    use anchor_lang::solana_program::sysvar::{self, instructions};
+
+    /// Instructions sysvar data for a transaction of `(program_id, data)` instructions.
+    fn instructions_sysvar_data(transaction: &[(Pubkey, [u8; 8])]) -> Vec<u8> {
+        let borrowed: Vec<_> = transaction
+            .iter()
+            .map(|(program_id, data)| instructions::BorrowedInstruction {
+                program_id,
+                accounts: Vec::new(),
+                data,
+            })
+            .collect();
+        instructions::construct_instructions_data(&borrowed)
+    }
+
+    #[test]
+    fn call_policy_screens_callers_and_same_transaction_deposits() {
+        // A router instruction, then a direct deposit_for and a direct withdrawal.
+        let router = Pubkey::new_unique();
+        let mut data = instructions_sysvar_data(&[
+            (router, [0u8; 8]),
+            (crate::ID, crate::instruction::DepositFor::DISCRIMINATOR),
+            (crate::ID, crate::instruction::WithdrawPrivate::DISCRIMINATOR),
+        ]);
+        let (key, owner, mut lamports) = (sysvar::instructions::ID, sysvar::ID, 0);
+        let mut check = |current, pool: &PrivacyPool, allowlist: Option<&CallerAllowlist>, op| {
+            instructions::store_current_index(&mut data, current);
+            let info =
+                AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+            check_call_policy(pool, allowlist, &info, op)
+        };
+
+        let mut pool = test_pool();
+        check(2, &pool, None, Operation::Withdraw).unwrap();
+
+        pool.call_policy = CallPolicy::NoDepositThenWithdraw.flag();
+        assert_eq!(
+            check(2, &pool, None, Operation::Withdraw),
+            Err(ErrorCode::DepositThenWithdraw.into())
+        );
+        check(1, &pool, None, Operation::Deposit).unwrap();
+
+        // Only the router's instruction reaches psol through CPI.
+        pool.call_policy = CallPolicy::ApprovedCallersOnly.flag();
+        let mut allowlist = CallerAllowlist {
+            version: CallerAllowlist::VERSION,
+            pool: Pubkey::default(),
+            bump: 255,
+            programs: Vec::new(),
+        };
+        assert_eq!(
+            check(0, &pool, None, Operation::Deposit),
+            Err(ErrorCode::CallerAllowlistRequired.into())
+        );
+        assert_eq!(
+            check(0, &pool, Some(&allowlist), Operation::Deposit),
+            Err(ErrorCode::UnapprovedCaller.into())
+        );
+        allowlist.insert(router).unwrap();
+        check(0, &pool, Some(&allowlist), Operation::Deposit).unwrap();
+        check(1, &pool, None, Operation::Deposit).unwrap();
+        check(2, &pool, None, Operation::Withdraw).unwrap();
+    }
+
+    #[test]
+    fn same_slot_deposits_block_withdrawals_under_the_policy() {
+        let mut pool = test_pool();
+        let mut account = test_account();
+        account.last_deposit_slot = 40;
+        check_not_deposited_in_slot(&pool, &account, 40).unwrap();
+
+        pool.call_policy = CallPolicy::NoDepositThenWithdraw.flag();
+        assert_eq!(
+            check_not_deposited_in_slot(&pool, &account, 40),
+            Err(ErrorCode::DepositThenWithdraw.into())
+        );
+        check_not_deposited_in_slot(&pool, &account, 41).unwrap();
+
+        // Credits count as deposits.
+        account.credit(5, 41).unwrap();
+        assert_eq!(
+            check_not_deposited_in_slot(&pool, &account, 41),
+            Err(ErrorCode::DepositThenWithdraw.into())
+        );
+    }
+}
@@ -10,7 +10,10 @@ pub struct AdminPause<'info> {
     pub admin: Signer<'info>,
 }
 
-pub fn admin_pause(ctx: Context<AdminPause>) -> Result<()> {
-    ctx.accounts.pool.paused = true;
+pub fn handler(ctx: Context<AdminPause>, mask: u8) -> Result<()> {
+    require!(mask & !PrivacyPool::PAUSE_ALL == 0, ErrorCode::InvalidPauseMask);
+    ctx.accounts
+        .pool
+        .set_paused(mask, true, Clock::get()?.slot);
     Ok(())
 }
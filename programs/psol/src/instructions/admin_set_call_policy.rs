@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::state::PrivacyPool;
+
+#[derive(Accounts)]
+pub struct AdminSetCallPolicy<'info> {
+    #[account(mut, has_one = admin @ ErrorCode::Unauthorized)]
+    pub pool: Account<'info, PrivacyPool>,
+    pub admin: Signer<'info>,
+}
+
+/// Replaces the enforced `CallPolicy` flags with `mask`. `ApprovedCallersOnly` without a
+/// `CallerAllowlist` leaves only direct calls open.
+pub fn handler(ctx: Context<AdminSetCallPolicy>, mask: u8) -> Result<()> {
+    require!(
+        mask & !PrivacyPool::CALL_POLICY_ALL == 0,
+        ErrorCode::InvalidCallPolicy
+    );
+    ctx.accounts.pool.call_policy = mask;
+    emit!(CallPolicyUpdated {
+        pool: ctx.accounts.pool.key(),
+        call_policy: mask,
+    });
+    Ok(())
+}
+
+#[event]
+pub struct CallPolicyUpdated {
+    pub pool: Pubkey,
+    pub call_policy: u8,
+}
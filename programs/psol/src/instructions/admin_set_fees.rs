@@ -10,7 +10,7 @@ pub struct AdminSetFees<'info> {
     pub admin: Signer<'info>,
 }
 
-pub fn admin_set_fees(ctx: Context<AdminSetFees>, fee_bps: u16) -> Result<()> {
+pub fn handler(ctx: Context<AdminSetFees>, fee_bps: u16) -> Result<()> {
     require!(fee_bps <= 10_000, ErrorCode::FeeTooHigh);
     ctx.accounts.pool.fee_bps = fee_bps;
     Ok(())
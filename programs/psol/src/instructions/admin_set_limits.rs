@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::state::PrivacyPool;
+
+#[derive(Accounts)]
+pub struct AdminSetLimits<'info> {
+    #[account(mut, has_one = admin @ ErrorCode::Unauthorized)]
+    pub pool: Account<'info, PrivacyPool>,
+    pub admin: Signer<'info>,
+}
+
+pub fn handler(
+    ctx: Context<AdminSetLimits>,
+    max_withdrawal: u64,
+    window_slots: u64,
+    window_limit: u64,
+) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    pool.max_withdrawal = max_withdrawal;
+    pool.window_slots = window_slots;
+    pool.window_limit = window_limit;
+    // Restart the window so a shorter period cannot inherit stale counters.
+    pool.window_start = Clock::get()?.slot;
+    pool.window_withdrawn = 0;
+    Ok(())
+}
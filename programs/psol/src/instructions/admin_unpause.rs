@@ -10,7 +10,10 @@ pub struct AdminUnpause<'info> {
     pub admin: Signer<'info>,
 }
 
-pub fn admin_unpause(ctx: Context<AdminUnpause>) -> Result<()> {
-    ctx.accounts.pool.paused = false;
+pub fn handler(ctx: Context<AdminUnpause>, mask: u8) -> Result<()> {
+    require!(mask & !PrivacyPool::PAUSE_ALL == 0, ErrorCode::InvalidPauseMask);
+    ctx.accounts
+        .pool
+        .set_paused(mask, false, Clock::get()?.slot);
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,215 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::solana_program::{system_instruction, sysvar};
+
+use crate::crypto;
+use crate::error::ErrorCode;
+use crate::guard;
+use crate::instructions::batch_withdraw::create_nullifier_set;
+use crate::instructions::withdraw_private::{NullifierInserted, WithdrawalLimitHit};
+use crate::merkle::NonMembershipProof;
+use crate::state::{
+    CallerAllowlist, NullifierArchive, NullifierTree, Operation, PrivacyAccount, PrivacyPool,
+    StorageBackend,
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct AggregatedWithdrawal {
+    pub amount: u64,
+    pub nullifier: [u8; 32],
+    /// Same meaning as `withdraw_private`'s `nullifier_proof`.
+    pub nullifier_proof: Option<NonMembershipProof>,
+}
+
+#[derive(Accounts)]
+pub struct AggregateWithdraw<'info> {
+    #[account(mut, seeds = [b"privacy_pool"], bump = pool.bump)]
+    pub pool: Account<'info, PrivacyPool>,
+    /// CHECK: vault PDA
+    #[account(mut, seeds = [b"vault", pool.key().as_ref()], bump = pool.vault_bump)]
+    pub vault: UncheckedAccount<'info>,
+    /// Set for `StorageBackend::Accounts` pools.
+    #[account(
+        seeds = [b"nullifier_archive", pool.key().as_ref()],
+        bump = nullifier_archive.bump
+    )]
+    pub nullifier_archive: Option<Account<'info, NullifierArchive>>,
+    /// Set for `StorageBackend::Compressed` pools.
+    #[account(mut, address = pool.nullifier_tree)]
+    pub nullifier_tree: Option<AccountLoader<'info, NullifierTree>>,
+    /// Submits the aggregate and pays the fees and any `NullifierSet` rent. Each withdrawn
+    /// account's owner co-signs the transaction alongside it.
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+    /// CHECK: instructions sysvar, read by the call policy checks
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    /// Required while the pool enforces `CallPolicy::ApprovedCallersOnly`.
+    #[account(seeds = [b"caller_allowlist", pool.key().as_ref()], bump = caller_allowlist.bump)]
+    pub caller_allowlist: Option<Account<'info, CallerAllowlist>>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Withdrawals from several privacy accounts, authorised by one SnarkPack aggregate proof.
+///
+/// Remaining accounts, per item in order: the writable `PrivacyAccount`, its owner as a
+/// signer, the writable recipient, and on `Accounts` pools the `NullifierSet` PDA to
+/// create. Each privacy account may appear once. Either every withdrawal lands or the
+/// instruction fails.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, AggregateWithdraw<'info>>,
+    items: Vec<AggregatedWithdrawal>,
+    aggregate_proof: Vec<u8>,
+) -> Result<()> {
+    ctx.accounts.pool.check_not_paused(Operation::Withdraw)?;
+    guard::check_call_policy(
+        &ctx.accounts.pool,
+        ctx.accounts.caller_allowlist.as_deref(),
+        &ctx.accounts.instructions_sysvar,
+        Operation::Withdraw,
+    )?;
+    guard::enter(&mut ctx.accounts.pool)?;
+    require!(
+        !items.is_empty() && items.len() <= crypto::MAX_AGGREGATED_PROOFS,
+        ErrorCode::InvalidBatchSize
+    );
+
+    let backend = ctx.accounts.pool.storage_backend;
+    let stride = match backend {
+        StorageBackend::Accounts => 4,
+        StorageBackend::Compressed => 3,
+    };
+    require!(
+        ctx.remaining_accounts.len() == items.len() * stride,
+        ErrorCode::InvalidBatchAccounts
+    );
+
+    let slot = Clock::get()?.slot;
+    let mut total: u64 = 0;
+    let mut privacy_accounts = Vec::with_capacity(items.len());
+    let mut public_inputs = Vec::with_capacity(items.len());
+    for (i, (item, group)) in items
+        .iter()
+        .zip(ctx.remaining_accounts.chunks(stride))
+        .enumerate()
+    {
+        require!(item.amount > 0, ErrorCode::InvalidAmount);
+        require!(
+            items[..i]
+                .iter()
+                .all(|other| other.nullifier != item.nullifier),
+            ErrorCode::NullifierAlreadyUsed
+        );
+        require!(
+            privacy_accounts
+                .iter()
+                .all(|a: &Account<PrivacyAccount>| a.key() != group[0].key()),
+            ErrorCode::InvalidBatchAccounts
+        );
+        total = total
+            .checked_add(item.amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        let privacy_account = Account::<PrivacyAccount>::try_from(&group[0])?;
+        require!(
+            group[1].is_signer && *group[1].key == privacy_account.owner,
+            ErrorCode::Unauthorized
+        );
+        guard::check_not_deposited_in_slot(&ctx.accounts.pool, &privacy_account, slot)?;
+        public_inputs.push(crypto::aggregated_withdrawal_inputs(
+            &privacy_account.commitment,
+            &privacy_account.spend_key,
+            &item.nullifier,
+            group[2].key,
+            item.amount,
+        ));
+        privacy_accounts.push(privacy_account);
+    }
+    require!(
+        crypto::verify_aggregate_proof(&aggregate_proof, &public_inputs)?,
+        ErrorCode::InvalidProof
+    );
+
+    if let Err(e) = ctx.accounts.pool.record_withdrawal(total, slot) {
+        emit!(WithdrawalLimitHit {
+            pool: ctx.accounts.pool.key(),
+            amount: total,
+            window_withdrawn: ctx.accounts.pool.withdrawn_in_window(slot),
+            slot,
+        });
+        return Err(e);
+    }
+
+    let pool_key = ctx.accounts.pool.key();
+    match (
+        backend,
+        &ctx.accounts.nullifier_archive,
+        &ctx.accounts.nullifier_tree,
+    ) {
+        (StorageBackend::Accounts, Some(archive), None) => {
+            for (item, group) in items.iter().zip(ctx.remaining_accounts.chunks(stride)) {
+                archive.check_not_archived(&item.nullifier, item.nullifier_proof.as_ref())?;
+                create_nullifier_set(
+                    &group[3],
+                    &ctx.accounts.relayer,
+                    &ctx.accounts.system_program,
+                    pool_key,
+                    item.nullifier,
+                    slot,
+                )?;
+            }
+        }
+        (StorageBackend::Compressed, None, Some(tree)) => {
+            let mut tree_data = tree.load_mut()?;
+            for item in &items {
+                let nullifier_proof = item
+                    .nullifier_proof
+                    .as_ref()
+                    .ok_or(ErrorCode::InvalidMerkleProof)?;
+                let index = tree_data.insert(&pool_key, item.nullifier, nullifier_proof)?;
+                emit!(NullifierInserted {
+                    pool: pool_key,
+                    tree: tree.key(),
+                    nullifier: item.nullifier,
+                    index,
+                    low_index: nullifier_proof.low_index,
+                });
+            }
+        }
+        _ => return err!(ErrorCode::StorageBackendMismatch),
+    }
+
+    let seeds: &[&[u8]] = &[b"vault", pool_key.as_ref(), &[ctx.accounts.pool.vault_bump]];
+    let mut net_total: u64 = 0;
+    for ((item, group), privacy_account) in items
+        .iter()
+        .zip(ctx.remaining_accounts.chunks(stride))
+        .zip(privacy_accounts.iter_mut())
+    {
+        privacy_account.withdraw(item.amount, slot)?;
+        privacy_account.exit(&crate::ID)?;
+
+        let (net_amount, _) = ctx.accounts.pool.apply_fee(item.amount)?;
+        net_total = net_total
+            .checked_add(net_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        invoke_signed(
+            &system_instruction::transfer(&ctx.accounts.vault.key(), group[2].key, net_amount),
+            &[
+                ctx.accounts.vault.to_account_info(),
+                group[2].clone(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+            &[seeds],
+        )?;
+    }
+    ctx.accounts.pool.unlock(net_total)?;
+
+    msg!(
+        "Relayed {} withdrawals totalling {} lamports",
+        items.len(),
+        net_total
+    );
+    ctx.accounts.pool.leave();
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::merkle::NonMembershipProof;
+use crate::state::{NullifierArchive, NullifierSet, PrivacyPool};
+
+/// Permissionless crank: folds an old `NullifierSet` into the pool's archive tree and
+/// closes it, refunding rent to whoever paid for it.
+#[derive(Accounts)]
+pub struct ArchiveNullifier<'info> {
+    #[account(seeds = [b"privacy_pool"], bump = pool.bump)]
+    pub pool: Account<'info, PrivacyPool>,
+    #[account(
+        mut,
+        seeds = [b"nullifier_archive", pool.key().as_ref()],
+        bump = nullifier_archive.bump
+    )]
+    pub nullifier_archive: Account<'info, NullifierArchive>,
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"nullifier", pool.key().as_ref(), nullifier_set.nullifier.as_ref()],
+        bump = nullifier_set.bump,
+        has_one = payer @ ErrorCode::Unauthorized
+    )]
+    pub nullifier_set: Account<'info, NullifierSet>,
+    /// CHECK: rent refund target, checked against `nullifier_set.payer`
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+    pub cranker: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<ArchiveNullifier>, archive_proof: NonMembershipProof) -> Result<()> {
+    let nullifier_set = &ctx.accounts.nullifier_set;
+    let slot = Clock::get()?.slot;
+    require!(
+        slot >= nullifier_set.slot.saturating_add(NullifierSet::ARCHIVE_MIN_AGE_SLOTS),
+        ErrorCode::NullifierTooRecent
+    );
+
+    ctx.accounts
+        .nullifier_archive
+        .insert(nullifier_set.nullifier, &archive_proof)?;
+
+    emit!(NullifierArchived {
+        pool: ctx.accounts.pool.key(),
+        nullifier: nullifier_set.nullifier,
+        index: ctx.accounts.nullifier_archive.next_index - 1,
+        root: ctx.accounts.nullifier_archive.root,
+    });
+    Ok(())
+}
+
+/// Lets off-chain mirrors of the archive tree replay insertions in order.
+#[event]
+pub struct NullifierArchived {
+    pub pool: Pubkey,
+    pub nullifier: [u8; 32],
+    pub index: u64,
+    pub root: [u8; 32],
+}
@@ -0,0 +1,151 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::system_program::{self, Transfer};
+
+use crate::crypto;
+use crate::error::ErrorCode;
+use crate::guard;
+use crate::instructions::deposit_private::{record_commitments, PrivateDeposit};
+use crate::state::*;
+
+/// Largest number of items accepted by `batch_deposit` and `batch_withdraw`.
+pub const MAX_BATCH_SIZE: usize = 8;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DepositItem {
+    pub amount: u64,
+    pub encrypted_amount: [u8; 64],
+    pub proof: Vec<u8>,
+}
+
+#[derive(Accounts)]
+pub struct BatchDeposit<'info> {
+    #[account(
+        mut,
+        seeds = [b"privacy_pool"],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, PrivacyPool>,
+
+    /// CHECK: vault PDA
+    #[account(mut, seeds = [b"vault", pool.key().as_ref()], bump = pool.vault_bump)]
+    pub vault: UncheckedAccount<'info>,
+
+    /// Set for `StorageBackend::Accounts` pools.
+    #[account(
+        mut,
+        seeds = [b"commitment", pool.key().as_ref()],
+        bump = pool.commitment_bump
+    )]
+    pub commitment_registry: Option<Account<'info, CommitmentRegistry>>,
+
+    /// Set for `StorageBackend::Compressed` pools.
+    #[account(mut, address = pool.commitment_tree)]
+    pub commitment_tree: Option<AccountLoader<'info, CommitmentTree>>,
+
+    #[account(
+        mut,
+        constraint = privacy_account.owner == user.key() @ ErrorCode::Unauthorized
+    )]
+    pub privacy_account: Account<'info, PrivacyAccount>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: instructions sysvar, read by the call policy checks
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    /// Required while the pool enforces `CallPolicy::ApprovedCallersOnly`.
+    #[account(seeds = [b"caller_allowlist", pool.key().as_ref()], bump = caller_allowlist.bump)]
+    pub caller_allowlist: Option<Account<'info, CallerAllowlist>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Several deposits into one privacy account with a single vault transfer. Either every
+/// item is credited or the instruction fails.
+pub fn handler(ctx: Context<BatchDeposit>, items: Vec<DepositItem>) -> Result<()> {
+    ctx.accounts.pool.check_not_paused(Operation::Deposit)?;
+    guard::check_call_policy(
+        &ctx.accounts.pool,
+        ctx.accounts.caller_allowlist.as_deref(),
+        &ctx.accounts.instructions_sysvar,
+        Operation::Deposit,
+    )?;
+    guard::enter(&mut ctx.accounts.pool)?;
+    require!(
+        !items.is_empty() && items.len() <= MAX_BATCH_SIZE,
+        ErrorCode::InvalidBatchSize
+    );
+
+    // Each deposit extends the account's commitment chain, so item `i` is proven
+    // against the commitment produced by item `i - 1`.
+    let mut commitment = ctx.accounts.privacy_account.commitment;
+    let mut commitments = Vec::with_capacity(items.len());
+    let mut public_inputs = Vec::with_capacity(items.len());
+    let mut total: u64 = 0;
+    for item in &items {
+        require!(item.amount > 0, ErrorCode::InvalidAmount);
+        total = total
+            .checked_add(item.amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        public_inputs.push(vec![commitment]);
+        commitment = crypto::pedersen_commit(item.amount, &commitment);
+        commitments.push(commitment);
+    }
+    let proofs: Vec<&[u8]> = items.iter().map(|item| item.proof.as_slice()).collect();
+    require!(
+        crypto::verify_proof_batch(&proofs, &public_inputs)?,
+        ErrorCode::InvalidProof
+    );
+
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.user.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+            },
+        ),
+        total,
+    )?;
+    ctx.accounts.pool.lock(total)?;
+
+    record_commitments(
+        &ctx.accounts.pool,
+        &mut ctx.accounts.commitment_registry,
+        &ctx.accounts.commitment_tree,
+        &commitments,
+    )?;
+
+    let slot = Clock::get()?.slot;
+    let pool_key = ctx.accounts.pool.key();
+    let privacy_account = &mut ctx.accounts.privacy_account;
+    for item in &items {
+        privacy_account.encrypted_balance =
+            crypto::add_encrypted(&privacy_account.encrypted_balance, &item.encrypted_amount)?;
+        emit!(PrivateDeposit {
+            pool: pool_key,
+            privacy_account: privacy_account.key(),
+            amount: item.amount,
+            encrypted_amount: item.encrypted_amount,
+            slot,
+        });
+    }
+    privacy_account.commitment = commitment;
+    privacy_account.total_deposits = privacy_account
+        .total_deposits
+        .checked_add(total)
+        .ok_or(ErrorCode::MathOverflow)?;
+    privacy_account.nonce = privacy_account
+        .nonce
+        .checked_add(items.len() as u64)
+        .ok_or(ErrorCode::MathOverflow)?;
+    privacy_account.last_update = slot;
+    privacy_account.last_deposit_slot = slot;
+
+    msg!("Deposited {} lamports in {} items", total, items.len());
+    ctx.accounts.pool.leave();
+    Ok(())
+}
@@ -0,0 +1,293 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::solana_program::{system_instruction, sysvar};
+use anchor_lang::system_program::{self, Allocate, Assign, CreateAccount, Transfer};
+
+use crate::crypto;
+use crate::error::ErrorCode;
+use crate::guard;
+use crate::instructions::batch_deposit::MAX_BATCH_SIZE;
+use crate::instructions::withdraw_private::{NullifierInserted, WithdrawalLimitHit};
+use crate::merkle::NonMembershipProof;
+use crate::state::{
+    CallerAllowlist, NullifierArchive, NullifierSet, NullifierTree, Operation, PrivacyAccount,
+    PrivacyPool, StorageBackend,
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct WithdrawItem {
+    pub amount: u64,
+    pub nullifier: [u8; 32],
+    pub proof: Vec<u8>,
+    /// Same meaning as `withdraw_private`'s `nullifier_proof`.
+    pub nullifier_proof: Option<NonMembershipProof>,
+}
+
+#[derive(Accounts)]
+pub struct BatchWithdraw<'info> {
+    #[account(mut, seeds = [b"privacy_pool"], bump = pool.bump)]
+    pub pool: Account<'info, PrivacyPool>,
+    /// CHECK: vault PDA
+    #[account(mut, seeds = [b"vault", pool.key().as_ref()], bump = pool.vault_bump)]
+    pub vault: UncheckedAccount<'info>,
+    /// Set for `StorageBackend::Accounts` pools.
+    #[account(
+        seeds = [b"nullifier_archive", pool.key().as_ref()],
+        bump = nullifier_archive.bump
+    )]
+    pub nullifier_archive: Option<Account<'info, NullifierArchive>>,
+    /// Set for `StorageBackend::Compressed` pools.
+    #[account(mut, address = pool.nullifier_tree)]
+    pub nullifier_tree: Option<AccountLoader<'info, NullifierTree>>,
+    #[account(mut, has_one = owner)]
+    pub privacy_account: Account<'info, PrivacyAccount>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    /// CHECK: recipient for SOL withdrawal
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+    /// CHECK: instructions sysvar, read by the call policy checks
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    /// Required while the pool enforces `CallPolicy::ApprovedCallersOnly`.
+    #[account(seeds = [b"caller_allowlist", pool.key().as_ref()], bump = caller_allowlist.bump)]
+    pub caller_allowlist: Option<Account<'info, CallerAllowlist>>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Several withdrawals from one privacy account with a single vault transfer. Either
+/// every nullifier is spent or the instruction fails.
+///
+/// For `Accounts` pools the remaining accounts are the writable `NullifierSet` PDAs at
+/// `["nullifier", pool, nullifier]`, one per item in item order; they are created here.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, BatchWithdraw<'info>>,
+    items: Vec<WithdrawItem>,
+) -> Result<()> {
+    ctx.accounts.pool.check_not_paused(Operation::Withdraw)?;
+    guard::check_call_policy(
+        &ctx.accounts.pool,
+        ctx.accounts.caller_allowlist.as_deref(),
+        &ctx.accounts.instructions_sysvar,
+        Operation::Withdraw,
+    )?;
+    guard::enter(&mut ctx.accounts.pool)?;
+    require!(
+        !items.is_empty() && items.len() <= MAX_BATCH_SIZE,
+        ErrorCode::InvalidBatchSize
+    );
+
+    let mut total: u64 = 0;
+    for (i, item) in items.iter().enumerate() {
+        require!(item.amount > 0, ErrorCode::InvalidAmount);
+        require!(
+            items[..i]
+                .iter()
+                .all(|other| other.nullifier != item.nullifier),
+            ErrorCode::NullifierAlreadyUsed
+        );
+        total = total
+            .checked_add(item.amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+    }
+
+    let slot = Clock::get()?.slot;
+    if let Err(e) = ctx.accounts.pool.record_withdrawal(total, slot) {
+        emit!(WithdrawalLimitHit {
+            pool: ctx.accounts.pool.key(),
+            amount: total,
+            window_withdrawn: ctx.accounts.pool.withdrawn_in_window(slot),
+            slot,
+        });
+        return Err(e);
+    }
+
+    let commitment = ctx.accounts.privacy_account.commitment;
+    let spend_key = ctx.accounts.privacy_account.spend_key;
+    let proofs: Vec<&[u8]> = items.iter().map(|item| item.proof.as_slice()).collect();
+    let public_inputs: Vec<Vec<[u8; 32]>> = items
+        .iter()
+        .map(|item| {
+            crypto::withdrawal_inputs(&commitment, &spend_key, &item.nullifier, item.amount)
+        })
+        .collect();
+    require!(
+        crypto::verify_proof_batch(&proofs, &public_inputs)?,
+        ErrorCode::InvalidProof
+    );
+
+    let pool_key = ctx.accounts.pool.key();
+    match (
+        ctx.accounts.pool.storage_backend,
+        &ctx.accounts.nullifier_archive,
+        &ctx.accounts.nullifier_tree,
+    ) {
+        (StorageBackend::Accounts, Some(archive), None) => {
+            require!(
+                ctx.remaining_accounts.len() == items.len(),
+                ErrorCode::InvalidBatchAccounts
+            );
+            for (item, record) in items.iter().zip(ctx.remaining_accounts) {
+                archive.check_not_archived(&item.nullifier, item.nullifier_proof.as_ref())?;
+                create_nullifier_set(
+                    record,
+                    &ctx.accounts.owner,
+                    &ctx.accounts.system_program,
+                    pool_key,
+                    item.nullifier,
+                    slot,
+                )?;
+            }
+        }
+        (StorageBackend::Compressed, None, Some(tree)) => {
+            require!(
+                ctx.remaining_accounts.is_empty(),
+                ErrorCode::InvalidBatchAccounts
+            );
+            let mut tree_data = tree.load_mut()?;
+            for item in &items {
+                let nullifier_proof = item
+                    .nullifier_proof
+                    .as_ref()
+                    .ok_or(ErrorCode::InvalidMerkleProof)?;
+                let index = tree_data.insert(&pool_key, item.nullifier, nullifier_proof)?;
+                emit!(NullifierInserted {
+                    pool: pool_key,
+                    tree: tree.key(),
+                    nullifier: item.nullifier,
+                    index,
+                    low_index: nullifier_proof.low_index,
+                });
+            }
+        }
+        _ => return err!(ErrorCode::StorageBackendMismatch),
+    }
+
+    let privacy_account = &mut ctx.accounts.privacy_account;
+    guard::check_not_deposited_in_slot(&ctx.accounts.pool, privacy_account, slot)?;
+    let mut net_total: u64 = 0;
+    let mut fee_total: u64 = 0;
+    for item in &items {
+        privacy_account.withdraw(item.amount, slot)?;
+        // Fees are taken per item so batching does not change what each withdrawal pays.
+        let (net_amount, fee) = ctx.accounts.pool.apply_fee(item.amount)?;
+        net_total = net_total
+            .checked_add(net_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        fee_total = fee_total.checked_add(fee).ok_or(ErrorCode::MathOverflow)?;
+    }
+
+    ctx.accounts.pool.unlock(net_total)?;
+
+    let seeds: &[&[u8]] = &[b"vault", pool_key.as_ref(), &[ctx.accounts.pool.vault_bump]];
+    invoke_signed(
+        &system_instruction::transfer(
+            &ctx.accounts.vault.key(),
+            &ctx.accounts.recipient.key(),
+            net_total,
+        ),
+        &[
+            ctx.accounts.vault.to_account_info(),
+            ctx.accounts.recipient.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        ],
+        &[seeds],
+    )?;
+
+    msg!(
+        "Withdrew {} lamports in {} items (fee: {})",
+        net_total,
+        items.len(),
+        fee_total
+    );
+    ctx.accounts.pool.leave();
+    Ok(())
+}
+
+/// Creates the `NullifierSet` PDA for `nullifier` at `record`, as `init` does for
+/// `withdraw_private`. An existing record means the nullifier is already spent.
+pub(crate) fn create_nullifier_set<'info>(
+    record: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    pool: Pubkey,
+    nullifier: [u8; 32],
+    slot: u64,
+) -> Result<()> {
+    let (expected, bump) = Pubkey::find_program_address(
+        &[b"nullifier", pool.as_ref(), nullifier.as_ref()],
+        &crate::ID,
+    );
+    require_keys_eq!(record.key(), expected, ErrorCode::InvalidBatchAccounts);
+    require!(
+        record.data_is_empty() && record.owner == &System::id(),
+        ErrorCode::NullifierAlreadyUsed
+    );
+
+    let space = NullifierSet::SIZE;
+    let rent = Rent::get()?.minimum_balance(space);
+    let signer: &[&[u8]] = &[b"nullifier", pool.as_ref(), nullifier.as_ref(), &[bump]];
+    let system = system_program.to_account_info();
+    if record.lamports() == 0 {
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                system,
+                CreateAccount {
+                    from: payer.to_account_info(),
+                    to: record.clone(),
+                },
+                &[signer],
+            ),
+            rent,
+            space as u64,
+            &crate::ID,
+        )?;
+    } else {
+        // Pre-funded address: top up, then allocate and assign like Anchor's `init`.
+        let shortfall = rent.saturating_sub(record.lamports());
+        if shortfall > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    system.clone(),
+                    Transfer {
+                        from: payer.to_account_info(),
+                        to: record.clone(),
+                    },
+                ),
+                shortfall,
+            )?;
+        }
+        system_program::allocate(
+            CpiContext::new_with_signer(
+                system.clone(),
+                Allocate {
+                    account_to_allocate: record.clone(),
+                },
+                &[signer],
+            ),
+            space as u64,
+        )?;
+        system_program::assign(
+            CpiContext::new_with_signer(
+                system,
+                Assign {
+                    account_to_assign: record.clone(),
+                },
+                &[signer],
+            ),
+            &crate::ID,
+        )?;
+    }
+
+    let nullifier_set = NullifierSet {
+        version: NullifierSet::VERSION,
+        pool,
+        nullifier,
+        payer: payer.key(),
+        slot,
+        bump,
+        reserved: [0u8; 16],
+    };
+    let mut data = record.try_borrow_mut_data()?;
+    nullifier_set.try_serialize(&mut &mut data[..])
+}
@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::instructions::initialize_pool::init_storage_backend;
+use crate::state::{
+    CommitmentTree, NullifierArchive, NullifierTree, PoolMigration, PrivacyPool, StorageBackend,
+};
+
+#[derive(Accounts)]
+pub struct BeginPoolMigration<'info> {
+    #[account(
+        mut,
+        seeds = [b"privacy_pool"],
+        bump = pool.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub pool: Account<'info, PrivacyPool>,
+    #[account(
+        init,
+        payer = admin,
+        space = PoolMigration::SIZE,
+        seeds = [b"migration", pool.key().as_ref()],
+        bump
+    )]
+    pub migration: Account<'info, PoolMigration>,
+    /// Legacy pools predate the archive, so it is created here when missing.
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = NullifierArchive::SIZE,
+        seeds = [b"nullifier_archive", pool.key().as_ref()],
+        bump
+    )]
+    pub nullifier_archive: Account<'info, NullifierArchive>,
+    /// Required when migrating to `StorageBackend::Compressed`; pre-allocated by the client.
+    #[account(zero)]
+    pub commitment_tree: Option<AccountLoader<'info, CommitmentTree>>,
+    /// Required when migrating to `StorageBackend::Compressed`; pre-allocated by the client.
+    #[account(zero)]
+    pub nullifier_tree: Option<AccountLoader<'info, NullifierTree>>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Starts moving a legacy pool onto `storage_backend`. Normal operations stay refused
+/// until `migrate_pool` has processed every registry entry.
+pub fn handler(ctx: Context<BeginPoolMigration>, storage_backend: StorageBackend) -> Result<()> {
+    require!(
+        ctx.accounts.pool.storage_version == PrivacyPool::LEGACY_STORAGE_VERSION,
+        ErrorCode::PoolAlreadyMigrated
+    );
+
+    let pool_key = ctx.accounts.pool.key();
+    if ctx.accounts.nullifier_archive.pool == Pubkey::default() {
+        ctx.accounts
+            .nullifier_archive
+            .initialize(pool_key, ctx.bumps.nullifier_archive)?;
+    }
+
+    init_storage_backend(
+        &mut ctx.accounts.pool,
+        storage_backend,
+        &ctx.accounts.commitment_tree,
+        &ctx.accounts.nullifier_tree,
+    )?;
+
+    let migration = &mut ctx.accounts.migration;
+    migration.pool = pool_key;
+    migration.target = storage_backend;
+    migration.commitments_migrated = 0;
+    migration.nullifiers_migrated = 0;
+    migration.bump = ctx.bumps.migration;
+    Ok(())
+}
@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+
+use crate::crypto;
+use crate::error::ErrorCode;
+use crate::state::{PendingDeposits, PrivacyAccount};
+
+#[derive(Accounts)]
+pub struct ClaimPendingDeposits<'info> {
+    #[account(
+        mut,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub privacy_account: Account<'info, PrivacyAccount>,
+    #[account(
+        mut,
+        seeds = [b"pending_deposits", privacy_account.key().as_ref()],
+        bump = pending_deposits.bump
+    )]
+    pub pending_deposits: Account<'info, PendingDeposits>,
+    pub owner: Signer<'info>,
+}
+
+/// Folds the deposits others made with `deposit_for` into the owner's balance, as one
+/// deposit of their total. The lamports are already locked in the pool.
+pub fn handler(ctx: Context<ClaimPendingDeposits>) -> Result<()> {
+    let (amount, encrypted_amount, count) = ctx.accounts.pending_deposits.take()?;
+    let privacy_account = &mut ctx.accounts.privacy_account;
+    privacy_account.encrypted_balance =
+        crypto::add_encrypted(&privacy_account.encrypted_balance, &encrypted_amount)?;
+    privacy_account.commitment = crypto::pedersen_commit(amount, &privacy_account.commitment);
+    privacy_account.total_deposits = privacy_account
+        .total_deposits
+        .checked_add(amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+    privacy_account.nonce = privacy_account.nonce.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+    let slot = Clock::get()?.slot;
+    privacy_account.last_update = slot;
+    privacy_account.last_deposit_slot = slot;
+
+    emit!(PendingDepositsClaimed {
+        privacy_account: privacy_account.key(),
+        amount,
+        count,
+        slot,
+    });
+    Ok(())
+}
+
+#[event]
+pub struct PendingDepositsClaimed {
+    pub privacy_account: Pubkey,
+    pub amount: u64,
+    pub count: u64,
+    pub slot: u64,
+}
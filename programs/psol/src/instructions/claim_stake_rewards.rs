@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::state::{PrivacyAccount, StakeStrategy};
+
+#[derive(Accounts)]
+pub struct ClaimStakeRewards<'info> {
+    #[account(mut, has_one = rewards_authority @ ErrorCode::Unauthorized)]
+    pub stake_strategy: Account<'info, StakeStrategy>,
+    pub rewards_authority: Signer<'info>,
+    /// The rewards authority's privacy account, credited with the claim.
+    #[account(
+        mut,
+        constraint = beneficiary.owner == rewards_authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub beneficiary: Option<Account<'info, PrivacyAccount>>,
+}
+
+/// Hands the harvested rewards to the rewards authority, which attributes them (psol_token
+/// adds them to its exchange rate). Lamports do not move; they are already in
+/// `total_locked`. Returns the amount claimed.
+pub fn handler(ctx: Context<ClaimStakeRewards>) -> Result<u64> {
+    let strategy = &mut ctx.accounts.stake_strategy;
+    let amount = std::mem::take(&mut strategy.unclaimed_rewards);
+    if let Some(beneficiary) = &mut ctx.accounts.beneficiary {
+        beneficiary.credit(amount, Clock::get()?.slot)?;
+    }
+    emit!(StakeRewardsClaimed {
+        pool: strategy.pool,
+        rewards_authority: strategy.rewards_authority,
+        amount,
+    });
+    Ok(amount)
+}
+
+#[event]
+pub struct StakeRewardsClaimed {
+    pub pool: Pubkey,
+    pub rewards_authority: Pubkey,
+    pub amount: u64,
+}
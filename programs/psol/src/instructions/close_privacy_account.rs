@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+use crate::crypto;
+use crate::error::ErrorCode;
+use crate::state::PrivacyAccount;
+
+#[derive(Accounts)]
+pub struct ClosePrivacyAccount<'info> {
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub privacy_account: Account<'info, PrivacyAccount>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+/// Closes the account once `proof` shows its encrypted balance is zero.
+pub fn handler(ctx: Context<ClosePrivacyAccount>, proof: Vec<u8>) -> Result<()> {
+    let privacy_account = &ctx.accounts.privacy_account;
+    require!(
+        crypto::verify_zero_balance_proof(
+            &privacy_account.encrypted_balance,
+            &privacy_account.view_key,
+            &proof,
+        ),
+        ErrorCode::InvalidProof
+    );
+
+    emit!(PrivacyAccountClosed {
+        privacy_account: privacy_account.key(),
+        owner: privacy_account.owner,
+    });
+    Ok(())
+}
+
+#[event]
+pub struct PrivacyAccountClosed {
+    pub privacy_account: Pubkey,
+    pub owner: Pubkey,
+}
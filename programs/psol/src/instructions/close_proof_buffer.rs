@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::state::ProofBuffer;
+
+/// Abandons a buffer at any stage, refunding its rent to the owner.
+#[derive(Accounts)]
+pub struct CloseProofBuffer<'info> {
+    #[account(
+        mut,
+        close = owner,
+        seeds = [
+            b"proof_buffer",
+            proof_buffer.pool.as_ref(),
+            owner.key().as_ref(),
+            proof_buffer.nullifier.as_ref()
+        ],
+        bump = proof_buffer.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub proof_buffer: Account<'info, ProofBuffer>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+pub fn handler(_ctx: Context<CloseProofBuffer>) -> Result<()> {
+    Ok(())
+}
@@ -0,0 +1,169 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::system_program::{self, Transfer};
+
+use crate::crypto;
+use crate::error::ErrorCode;
+use crate::events::MemoAttached;
+use crate::guard;
+use crate::instructions::deposit_private::{record_commitments, PrivateDeposit};
+use crate::state::*;
+
+/// Deposit on behalf of another user, for programs calling psol through CPI. The
+/// `depositor` funds it and signs; the owner of `privacy_account` does not. The deposit
+/// is queued in `pending_deposits` until the owner claims it.
+#[derive(Accounts)]
+pub struct DepositFor<'info> {
+    #[account(
+        mut,
+        seeds = [b"privacy_pool"],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, PrivacyPool>,
+
+    /// CHECK: vault PDA
+    #[account(mut, seeds = [b"vault", pool.key().as_ref()], bump = pool.vault_bump)]
+    pub vault: UncheckedAccount<'info>,
+
+    /// Set for `StorageBackend::Accounts` pools.
+    #[account(
+        mut,
+        seeds = [b"commitment", pool.key().as_ref()],
+        bump = pool.commitment_bump
+    )]
+    pub commitment_registry: Option<Account<'info, CommitmentRegistry>>,
+
+    /// Set for `StorageBackend::Compressed` pools.
+    #[account(mut, address = pool.commitment_tree)]
+    pub commitment_tree: Option<AccountLoader<'info, CommitmentTree>>,
+
+    /// Beneficiary; any privacy account, including stealth accounts. Left unchanged.
+    #[account(mut)]
+    pub privacy_account: Account<'info, PrivacyAccount>,
+
+    /// Pays `amount` into the vault. Usually the user, passed through by the calling
+    /// program, or a system-owned PDA of that program.
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    /// CHECK: instructions sysvar, read by the call policy checks
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    /// Required while the pool enforces `CallPolicy::ApprovedCallersOnly`.
+    #[account(seeds = [b"caller_allowlist", pool.key().as_ref()], bump = caller_allowlist.bump)]
+    pub caller_allowlist: Option<Account<'info, CallerAllowlist>>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Created by the first deposit for `privacy_account`, at the depositor's expense.
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        space = PendingDeposits::SIZE,
+        seeds = [b"pending_deposits", privacy_account.key().as_ref()],
+        bump
+    )]
+    pub pending_deposits: Account<'info, PendingDeposits>,
+}
+
+/// Queues `amount` for `privacy_account`. `commitment` extends the pending chain,
+/// `pedersen_commit(amount, head)`, which the caller computes from public state; it fails
+/// with `CommitmentMismatch` if another deposit landed since the caller read the head.
+pub fn handler(
+    ctx: Context<DepositFor>,
+    amount: u64,
+    commitment: [u8; 32],
+    encrypted_amount: [u8; 64],
+    proof: Vec<u8>,
+    memo: Option<Vec<u8>>,
+) -> Result<()> {
+    ctx.accounts.pool.check_not_paused(Operation::Deposit)?;
+    guard::check_call_policy(
+        &ctx.accounts.pool,
+        ctx.accounts.caller_allowlist.as_deref(),
+        &ctx.accounts.instructions_sysvar,
+        Operation::Deposit,
+    )?;
+    guard::enter(&mut ctx.accounts.pool)?;
+    require!(amount > 0, ErrorCode::InvalidAmount);
+    if let Some(memo) = memo.as_deref() {
+        crypto::validate_memo(memo)?;
+    }
+
+    let privacy_account = &ctx.accounts.privacy_account;
+    let pending = &mut ctx.accounts.pending_deposits;
+    if pending.version == 0 {
+        pending.version = PendingDeposits::VERSION;
+        pending.privacy_account = privacy_account.key();
+        pending.bump = ctx.bumps.pending_deposits;
+        pending.commitment = PendingDeposits::head(&privacy_account.key(), None);
+    }
+    pending.push(amount, commitment, &encrypted_amount)?;
+    require!(
+        crypto::verify_deposit_proof(
+            &commitment,
+            &encrypted_amount,
+            &privacy_account.view_key,
+            amount,
+            &proof,
+        ),
+        ErrorCode::InvalidProof
+    );
+
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.depositor.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+    ctx.accounts.pool.lock(amount)?;
+
+    record_commitments(
+        &ctx.accounts.pool,
+        &mut ctx.accounts.commitment_registry,
+        &ctx.accounts.commitment_tree,
+        &[commitment],
+    )?;
+
+    let slot = Clock::get()?.slot;
+    emit!(PrivateDeposit {
+        pool: ctx.accounts.pool.key(),
+        privacy_account: privacy_account.key(),
+        amount,
+        encrypted_amount,
+        slot,
+    });
+    emit!(DepositedFor {
+        pool: ctx.accounts.pool.key(),
+        privacy_account: privacy_account.key(),
+        depositor: ctx.accounts.depositor.key(),
+        commitment,
+    });
+
+    if let Some(memo) = memo {
+        emit!(MemoAttached {
+            pool: ctx.accounts.pool.key(),
+            recipient_account: privacy_account.key(),
+            memo,
+        });
+    }
+
+    msg!("Deposited {} lamports for {}", amount, privacy_account.owner);
+    ctx.accounts.pool.leave();
+    Ok(())
+}
+
+/// Emitted alongside `PrivateDeposit` when the deposit was funded by someone other than
+/// the account owner.
+#[event]
+pub struct DepositedFor {
+    pub pool: Pubkey,
+    pub privacy_account: Pubkey,
+    pub depositor: Pubkey,
+    pub commitment: [u8; 32],
+}
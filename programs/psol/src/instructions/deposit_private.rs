@@ -1,65 +1,181 @@
 use crate::crypto;
+use crate::guard;
 use crate::error::ErrorCode;
+use crate::events::MemoAttached;
 use crate::state::*;
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::system_program::{self, Transfer};
 
 #[derive(Accounts)]
-pub struct TransferPrivate<'info> {
+pub struct DepositPrivate<'info> {
     #[account(
+        mut,
         seeds = [b"privacy_pool"],
         bump = pool.bump
     )]
     pub pool: Account<'info, PrivacyPool>,
 
+    /// CHECK: vault PDA
+    #[account(mut, seeds = [b"vault", pool.key().as_ref()], bump = pool.vault_bump)]
+    pub vault: UncheckedAccount<'info>,
+
+    /// Set for `StorageBackend::Accounts` pools.
     #[account(
         mut,
-        seeds = [b"privacy_account", sender.key().as_ref()],
-        bump = sender_account.bump,
-        constraint = sender_account.owner == sender.key() @ ErrorCode::Unauthorized
+        seeds = [b"commitment", pool.key().as_ref()],
+        bump = pool.commitment_bump
     )]
-    pub sender_account: Account<'info, PrivacyAccount>,
+    pub commitment_registry: Option<Account<'info, CommitmentRegistry>>,
+
+    /// Set for `StorageBackend::Compressed` pools.
+    #[account(mut, address = pool.commitment_tree)]
+    pub commitment_tree: Option<AccountLoader<'info, CommitmentTree>>,
 
     #[account(
         mut,
-        seeds = [b"privacy_account", recipient.key().as_ref()],
-        bump = recipient_account.bump
+        constraint = privacy_account.owner == user.key() @ ErrorCode::Unauthorized
     )]
-    pub recipient_account: Account<'info, PrivacyAccount>,
+    pub privacy_account: Account<'info, PrivacyAccount>,
 
     #[account(mut)]
-    pub sender: Signer<'info>,
+    pub user: Signer<'info>,
+
+    /// CHECK: instructions sysvar, read by the call policy checks
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    /// Required while the pool enforces `CallPolicy::ApprovedCallersOnly`.
+    #[account(seeds = [b"caller_allowlist", pool.key().as_ref()], bump = caller_allowlist.bump)]
+    pub caller_allowlist: Option<Account<'info, CallerAllowlist>>,
 
-    /// CHECK: Recipient can be any account
-    pub recipient: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 pub fn handler(
-    ctx: Context<TransferPrivate>,
+    ctx: Context<DepositPrivate>,
+    amount: u64,
     encrypted_amount: [u8; 64],
     proof: Vec<u8>,
+    memo: Option<Vec<u8>>,
 ) -> Result<()> {
-    let sender_account = &mut ctx.accounts.sender_account;
-    let recipient_account = &mut ctx.accounts.recipient_account;
+    ctx.accounts.pool.check_not_paused(Operation::Deposit)?;
+    guard::check_call_policy(
+        &ctx.accounts.pool,
+        ctx.accounts.caller_allowlist.as_deref(),
+        &ctx.accounts.instructions_sysvar,
+        Operation::Deposit,
+    )?;
+    guard::enter(&mut ctx.accounts.pool)?;
+    require!(amount > 0, ErrorCode::InvalidAmount);
+    if let Some(memo) = memo.as_deref() {
+        crypto::validate_memo(memo)?;
+    }
 
+    let privacy_account = &mut ctx.accounts.privacy_account;
     require!(
-        crypto::verify_transfer_proof(
-            &sender_account.encrypted_balance,
-            &encrypted_amount,
-            &sender_account.commitment,
-            &proof,
-        ),
+        crypto::verify_proof(&proof, &[privacy_account.commitment])?,
         ErrorCode::InvalidProof
     );
 
-    sender_account.encrypted_balance =
-        crypto::subtract_encrypted(&sender_account.encrypted_balance, &encrypted_amount)?;
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.user.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+    ctx.accounts.pool.lock(amount)?;
+
+    let commitment = crypto::pedersen_commit(amount, &privacy_account.commitment);
+    record_commitments(
+        &ctx.accounts.pool,
+        &mut ctx.accounts.commitment_registry,
+        &ctx.accounts.commitment_tree,
+        &[commitment],
+    )?;
+
+    privacy_account.encrypted_balance =
+        crypto::add_encrypted(&privacy_account.encrypted_balance, &encrypted_amount)?;
+    privacy_account.commitment = commitment;
+    privacy_account.total_deposits = privacy_account
+        .total_deposits
+        .checked_add(amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+    privacy_account.nonce = privacy_account.nonce.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+    let slot = Clock::get()?.slot;
+    privacy_account.last_update = slot;
+    privacy_account.last_deposit_slot = slot;
 
-    recipient_account.encrypted_balance =
-        crypto::add_encrypted(&recipient_account.encrypted_balance, &encrypted_amount)?;
+    emit!(PrivateDeposit {
+        pool: ctx.accounts.pool.key(),
+        privacy_account: privacy_account.key(),
+        amount,
+        encrypted_amount,
+        slot,
+    });
 
-    sender_account.last_update = Clock::get()?.slot;
-    recipient_account.last_update = Clock::get()?.slot;
+    if let Some(memo) = memo {
+        emit!(MemoAttached {
+            pool: ctx.accounts.pool.key(),
+            recipient_account: privacy_account.key(),
+            memo,
+        });
+    }
 
-    msg!("Private transfer completed");
+    msg!("Deposited {} lamports", amount);
+    ctx.accounts.pool.leave();
     Ok(())
 }
+
+/// Appends `commitments` to the store selected by the pool's `StorageBackend`.
+pub(crate) fn record_commitments<'info>(
+    pool: &Account<'info, PrivacyPool>,
+    registry: &mut Option<Account<'info, CommitmentRegistry>>,
+    tree: &Option<AccountLoader<'info, CommitmentTree>>,
+    commitments: &[[u8; 32]],
+) -> Result<()> {
+    let pool_key = pool.key();
+    match (pool.storage_backend, registry, tree) {
+        (StorageBackend::Accounts, Some(registry), None) => {
+            for commitment in commitments {
+                registry.add_commitment(&pool_key, *commitment)?;
+            }
+        }
+        (StorageBackend::Compressed, None, Some(tree)) => {
+            let mut tree_data = tree.load_mut()?;
+            for commitment in commitments {
+                let index = tree_data.append(&pool_key, *commitment)?;
+                emit!(CommitmentAppended {
+                    pool: pool_key,
+                    tree: tree.key(),
+                    index,
+                    commitment: *commitment,
+                });
+            }
+        }
+        _ => return err!(ErrorCode::StorageBackendMismatch),
+    }
+    Ok(())
+}
+
+#[event]
+pub struct PrivateDeposit {
+    pub pool: Pubkey,
+    pub privacy_account: Pubkey,
+    pub amount: u64,
+    pub encrypted_amount: [u8; 64],
+    pub slot: u64,
+}
+
+/// Leaf record for `CommitmentTree`; indexers replay these to rebuild the tree off chain.
+#[event]
+pub struct CommitmentAppended {
+    pub pool: Pubkey,
+    pub tree: Pubkey,
+    pub index: u64,
+    pub commitment: [u8; 32],
+}
@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+
+use crate::error::ErrorCode;
+use crate::guard;
+use crate::state::{Operation, PrivacyAccount, PrivacyPool};
+
+/// Adds yield to the vault. Permissionless: the lamports are locked in the pool and
+/// credited to the payer's own privacy account if one is passed, so only callers that
+/// track their share of it (psol_token's exchange rate) can attribute them.
+#[derive(Accounts)]
+pub struct DepositRewards<'info> {
+    #[account(mut, seeds = [b"privacy_pool"], bump = pool.bump)]
+    pub pool: Account<'info, PrivacyPool>,
+    /// CHECK: vault PDA
+    #[account(mut, seeds = [b"vault", pool.key().as_ref()], bump = pool.vault_bump)]
+    pub vault: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// The payer's privacy account, credited with `amount`.
+    #[account(mut, constraint = beneficiary.owner == payer.key() @ ErrorCode::Unauthorized)]
+    pub beneficiary: Option<Account<'info, PrivacyAccount>>,
+}
+
+pub fn handler(ctx: Context<DepositRewards>, amount: u64) -> Result<()> {
+    ctx.accounts.pool.check_not_paused(Operation::Deposit)?;
+    require!(amount > 0, ErrorCode::InvalidAmount);
+    guard::enter(&mut ctx.accounts.pool)?;
+
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.payer.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+    ctx.accounts.pool.lock(amount)?;
+    if let Some(beneficiary) = &mut ctx.accounts.beneficiary {
+        beneficiary.credit(amount, Clock::get()?.slot)?;
+    }
+
+    emit!(RewardsDeposited {
+        pool: ctx.accounts.pool.key(),
+        payer: ctx.accounts.payer.key(),
+        amount,
+    });
+    ctx.accounts.pool.leave();
+    Ok(())
+}
+
+#[event]
+pub struct RewardsDeposited {
+    pub pool: Pubkey,
+    pub payer: Pubkey,
+    pub amount: u64,
+}
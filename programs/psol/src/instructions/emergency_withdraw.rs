@@ -0,0 +1,84 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::solana_program::system_instruction;
+
+use crate::error::ErrorCode;
+use crate::guard;
+use crate::state::{PrivacyAccount, PrivacyPool};
+
+/// Escape hatch for when withdrawals have been paused for longer than
+/// `PrivacyPool::emergency_delay_slots`. Pays out the owner's provable balance
+/// (`total_deposits - total_withdrawals`) without a proof, fee or admin action.
+///
+/// Accounts that have sent a private transfer (`transfers_out > 0`) can never use the
+/// exit, since their deposit totals overstate what they hold; their balance stays in the
+/// pool until withdrawals are unpaused and is then withdrawn with a proof.
+#[derive(Accounts)]
+pub struct EmergencyWithdraw<'info> {
+    #[account(mut, seeds = [b"privacy_pool"], bump = pool.bump)]
+    pub pool: Account<'info, PrivacyPool>,
+    /// CHECK: vault PDA
+    #[account(mut, seeds = [b"vault", pool.key().as_ref()], bump = pool.vault_bump)]
+    pub vault: UncheckedAccount<'info>,
+    #[account(mut, has_one = owner @ ErrorCode::Unauthorized)]
+    pub privacy_account: Account<'info, PrivacyAccount>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<EmergencyWithdraw>) -> Result<()> {
+    let slot = Clock::get()?.slot;
+    ctx.accounts.pool.check_emergency_exit_open(slot)?;
+    guard::enter(&mut ctx.accounts.pool)?;
+
+    let privacy_account = &mut ctx.accounts.privacy_account;
+    require!(
+        privacy_account.transfers_out == 0,
+        ErrorCode::EmergencyExitIneligible
+    );
+    let amount = privacy_account.provable_balance()?;
+    require!(amount > 0, ErrorCode::InsufficientBalance);
+
+    privacy_account.total_withdrawals = privacy_account
+        .total_withdrawals
+        .checked_add(amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+    privacy_account.encrypted_balance = [0u8; 64];
+    privacy_account.last_update = slot;
+
+    ctx.accounts.pool.unlock(amount)?;
+
+    let pool_key = ctx.accounts.pool.key();
+    let seeds: &[&[u8]] = &[b"vault", pool_key.as_ref(), &[ctx.accounts.pool.vault_bump]];
+    invoke_signed(
+        &system_instruction::transfer(
+            &ctx.accounts.vault.key(),
+            &ctx.accounts.owner.key(),
+            amount,
+        ),
+        &[
+            ctx.accounts.vault.to_account_info(),
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        ],
+        &[seeds],
+    )?;
+
+    emit!(EmergencyWithdrawal {
+        pool: pool_key,
+        owner: ctx.accounts.owner.key(),
+        amount,
+        slot,
+    });
+    ctx.accounts.pool.leave();
+    Ok(())
+}
+
+#[event]
+pub struct EmergencyWithdrawal {
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub slot: u64,
+}
@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{PrivacyPool, StakeStrategy};
+
+/// Permissionless crank adding what the stake accounts earned since the last crank to
+/// `total_locked` and to the strategy's unclaimed rewards.
+#[derive(Accounts)]
+pub struct HarvestStakeRewards<'info> {
+    #[account(mut, seeds = [b"privacy_pool"], bump = pool.bump)]
+    pub pool: Account<'info, PrivacyPool>,
+    #[account(
+        mut,
+        seeds = [b"stake_strategy", pool.key().as_ref()],
+        bump = stake_strategy.bump,
+        has_one = pool
+    )]
+    pub stake_strategy: Account<'info, StakeStrategy>,
+    /// CHECK: main stake account PDA; only its balance is read
+    #[account(seeds = [b"stake", pool.key().as_ref()], bump = stake_strategy.stake_bump)]
+    pub main_stake: UncheckedAccount<'info>,
+    /// CHECK: transient stake account PDA; only its balance is read
+    #[account(
+        seeds = [b"transient_stake", pool.key().as_ref()],
+        bump = stake_strategy.transient_bump
+    )]
+    pub transient_stake: UncheckedAccount<'info>,
+}
+
+pub fn handler(ctx: Context<HarvestStakeRewards>) -> Result<()> {
+    let strategy = &mut ctx.accounts.stake_strategy;
+    let amount = strategy.record_balances(
+        ctx.accounts.main_stake.lamports(),
+        ctx.accounts.transient_stake.lamports(),
+    )?;
+    ctx.accounts.pool.lock(amount)?;
+
+    emit!(StakeRewardsHarvested {
+        pool: ctx.accounts.pool.key(),
+        amount,
+        unclaimed_rewards: strategy.unclaimed_rewards,
+    });
+    Ok(())
+}
+
+#[event]
+pub struct StakeRewardsHarvested {
+    pub pool: Pubkey,
+    pub amount: u64,
+    pub unclaimed_rewards: u64,
+}
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::state::{CallerAllowlist, PrivacyPool};
+
+#[derive(Accounts)]
+pub struct InitCallerAllowlist<'info> {
+    #[account(has_one = admin @ ErrorCode::Unauthorized)]
+    pub pool: Account<'info, PrivacyPool>,
+    #[account(
+        init,
+        payer = admin,
+        space = CallerAllowlist::SIZE,
+        seeds = [b"caller_allowlist", pool.key().as_ref()],
+        bump
+    )]
+    pub caller_allowlist: Account<'info, CallerAllowlist>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<InitCallerAllowlist>) -> Result<()> {
+    let allowlist = &mut ctx.accounts.caller_allowlist;
+    allowlist.version = CallerAllowlist::VERSION;
+    allowlist.pool = ctx.accounts.pool.key();
+    allowlist.bump = ctx.bumps.caller_allowlist;
+    allowlist.programs = Vec::new();
+    Ok(())
+}
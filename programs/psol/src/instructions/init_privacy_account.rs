@@ -7,7 +7,7 @@ pub struct InitPrivacyAccount<'info> {
     #[account(
         init,
         payer = owner,
-        space = 8 + PrivacyAccount::SPACE,
+        space = PrivacyAccount::SIZE,
         seeds = [b"privacy", owner.key().as_ref()],
         bump
     )]
@@ -17,8 +17,25 @@ pub struct InitPrivacyAccount<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn init_privacy_account(ctx: Context<InitPrivacyAccount>) -> Result<()> {
-    ctx.accounts.privacy_account.owner = ctx.accounts.owner.key();
-    ctx.accounts.privacy_account.balance = 0;
+pub fn handler(
+    ctx: Context<InitPrivacyAccount>,
+    spend_key: [u8; 32],
+    view_key: [u8; 32],
+) -> Result<()> {
+    let privacy_account = &mut ctx.accounts.privacy_account;
+    privacy_account.version = PrivacyAccount::VERSION;
+    privacy_account.owner = ctx.accounts.owner.key();
+    privacy_account.encrypted_balance = [0u8; 64];
+    privacy_account.commitment = [0u8; 32];
+    privacy_account.spend_key = spend_key;
+    privacy_account.view_key = view_key;
+    privacy_account.nonce = 0;
+    privacy_account.total_deposits = 0;
+    privacy_account.total_withdrawals = 0;
+    privacy_account.last_update = Clock::get()?.slot;
+    privacy_account.bump = ctx.bumps.privacy_account;
+    privacy_account.transfers_out = 0;
+    privacy_account.transfers_in = 0;
+    privacy_account.last_deposit_slot = 0;
     Ok(())
-}
\ No newline at end of file
+}
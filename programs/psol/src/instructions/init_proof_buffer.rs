@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::state::{PrivacyPool, ProofBuffer, VerificationStage};
+
+#[derive(Accounts)]
+#[instruction(nullifier: [u8; 32], proof_len: u32, public_inputs: Vec<[u8; 32]>)]
+pub struct InitProofBuffer<'info> {
+    #[account(seeds = [b"privacy_pool"], bump = pool.bump)]
+    pub pool: Account<'info, PrivacyPool>,
+    #[account(
+        init,
+        payer = owner,
+        space = ProofBuffer::space(proof_len as usize, public_inputs.len()),
+        seeds = [b"proof_buffer", pool.key().as_ref(), owner.key().as_ref(), nullifier.as_ref()],
+        bump
+    )]
+    pub proof_buffer: Account<'info, ProofBuffer>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Opens a buffer for a proof too large to pass to `withdraw_private` directly.
+pub fn handler(
+    ctx: Context<InitProofBuffer>,
+    nullifier: [u8; 32],
+    proof_len: u32,
+    public_inputs: Vec<[u8; 32]>,
+) -> Result<()> {
+    require!(
+        proof_len > 0 && proof_len as usize <= ProofBuffer::MAX_PROOF_LEN,
+        ErrorCode::ProofTooLarge
+    );
+    require!(
+        !public_inputs.is_empty() && public_inputs.len() <= ProofBuffer::MAX_PUBLIC_INPUTS,
+        ErrorCode::ProofTooLarge
+    );
+
+    let proof_buffer = &mut ctx.accounts.proof_buffer;
+    proof_buffer.owner = ctx.accounts.owner.key();
+    proof_buffer.pool = ctx.accounts.pool.key();
+    proof_buffer.nullifier = nullifier;
+    proof_buffer.stage = VerificationStage::Uploading;
+    proof_buffer.proof_len = proof_len;
+    proof_buffer.step = 0;
+    proof_buffer.accumulator = [0u8; 32];
+    proof_buffer.public_inputs = public_inputs;
+    proof_buffer.proof = Vec::with_capacity(proof_len as usize);
+    proof_buffer.bump = ctx.bumps.proof_buffer;
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::vote;
+
+use crate::error::ErrorCode;
+use crate::state::{PrivacyPool, StakeStrategy, TransientState};
+
+#[derive(Accounts)]
+pub struct InitStakeStrategy<'info> {
+    #[account(has_one = admin @ ErrorCode::Unauthorized)]
+    pub pool: Account<'info, PrivacyPool>,
+    #[account(
+        init,
+        payer = admin,
+        space = StakeStrategy::SIZE,
+        seeds = [b"stake_strategy", pool.key().as_ref()],
+        bump
+    )]
+    pub stake_strategy: Account<'info, StakeStrategy>,
+    /// CHECK: main stake account PDA; created by `rebalance_stake`
+    #[account(seeds = [b"stake", pool.key().as_ref()], bump)]
+    pub main_stake: UncheckedAccount<'info>,
+    /// CHECK: transient stake account PDA; created by `rebalance_stake`
+    #[account(seeds = [b"transient_stake", pool.key().as_ref()], bump)]
+    pub transient_stake: UncheckedAccount<'info>,
+    /// CHECK: validator vote account stake is delegated to
+    #[account(owner = vote::program::ID)]
+    pub vote_account: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<InitStakeStrategy>,
+    target_bps: u16,
+    min_buffer: u64,
+    rewards_authority: Pubkey,
+) -> Result<()> {
+    let strategy = &mut ctx.accounts.stake_strategy;
+    strategy.version = StakeStrategy::VERSION;
+    strategy.pool = ctx.accounts.pool.key();
+    strategy.vote_account = ctx.accounts.vote_account.key();
+    strategy.set_target(target_bps, min_buffer)?;
+    strategy.staked = 0;
+    strategy.transient = 0;
+    strategy.transient_state = TransientState::Idle;
+    strategy.rewards_authority = rewards_authority;
+    strategy.unclaimed_rewards = 0;
+    strategy.bump = ctx.bumps.stake_strategy;
+    strategy.stake_bump = ctx.bumps.main_stake;
+    strategy.transient_bump = ctx.bumps.transient_stake;
+    strategy.reserved = [0u8; 32];
+    Ok(())
+}
@@ -39,6 +39,23 @@ pub struct InitializePool<'info> {
     )]
     pub nullifier_registry: Account<'info, NullifierRegistry>,
 
+    #[account(
+        init,
+        payer = admin,
+        space = NullifierArchive::SIZE,
+        seeds = [b"nullifier_archive", pool.key().as_ref()],
+        bump
+    )]
+    pub nullifier_archive: Account<'info, NullifierArchive>,
+
+    /// Required for `StorageBackend::Compressed`; pre-allocated by the client.
+    #[account(zero)]
+    pub commitment_tree: Option<AccountLoader<'info, CommitmentTree>>,
+
+    /// Required for `StorageBackend::Compressed`; pre-allocated by the client.
+    #[account(zero)]
+    pub nullifier_tree: Option<AccountLoader<'info, NullifierTree>>,
+
     #[account(mut)]
     pub admin: Signer<'info>,
 
@@ -46,19 +63,38 @@ pub struct InitializePool<'info> {
     pub rent: Sysvar<'info, Rent>,
 }
 
-pub fn handler(ctx: Context<InitializePool>, fee_bps: u16) -> Result<()> {
+pub fn handler(
+    ctx: Context<InitializePool>,
+    fee_bps: u16,
+    emergency_delay_slots: u64,
+    storage_backend: StorageBackend,
+) -> Result<()> {
     require!(fee_bps <= 10_000, ErrorCode::FeeTooHigh);
+    require!(
+        emergency_delay_slots >= PrivacyPool::MIN_EMERGENCY_DELAY_SLOTS,
+        ErrorCode::EmergencyDelayTooShort
+    );
 
     let pool = &mut ctx.accounts.pool;
 
+    pool.version = PrivacyPool::VERSION;
     pool.admin = ctx.accounts.admin.key();
     pool.vault_bump = ctx.bumps.vault;
     pool.commitment_bump = ctx.bumps.commitment_registry;
     pool.nullifier_bump = ctx.bumps.nullifier_registry;
-    pool.paused = false;
+    pool.pause_flags = 0;
     pool.fee_bps = fee_bps;
     pool.total_locked = 0;
     pool.bump = ctx.bumps.pool;
+    pool.max_withdrawal = 0;
+    pool.window_slots = 0;
+    pool.window_limit = 0;
+    pool.window_start = 0;
+    pool.window_withdrawn = 0;
+    pool.withdraw_paused_at = 0;
+    pool.emergency_delay_slots = emergency_delay_slots;
+    pool.auditor_key = [0u8; 32];
+    pool.storage_version = PrivacyPool::STORAGE_VERSION;
 
     ctx.accounts.commitment_registry.pool = pool.key();
     ctx.accounts.commitment_registry.count = 0;
@@ -66,5 +102,40 @@ pub fn handler(ctx: Context<InitializePool>, fee_bps: u16) -> Result<()> {
     ctx.accounts.nullifier_registry.pool = pool.key();
     ctx.accounts.nullifier_registry.count = 0;
 
+    ctx.accounts
+        .nullifier_archive
+        .initialize(pool.key(), ctx.bumps.nullifier_archive)?;
+
+    init_storage_backend(
+        pool,
+        storage_backend,
+        &ctx.accounts.commitment_tree,
+        &ctx.accounts.nullifier_tree,
+    )?;
+
+    Ok(())
+}
+
+/// Records `storage_backend` on the pool and, for `Compressed`, initialises the supplied
+/// tree accounts. Exactly the accounts the backend needs must be present.
+pub(crate) fn init_storage_backend<'info>(
+    pool: &mut Account<'info, PrivacyPool>,
+    storage_backend: StorageBackend,
+    commitment_tree: &Option<AccountLoader<'info, CommitmentTree>>,
+    nullifier_tree: &Option<AccountLoader<'info, NullifierTree>>,
+) -> Result<()> {
+    pool.storage_backend = storage_backend;
+    pool.commitment_tree = Pubkey::default();
+    pool.nullifier_tree = Pubkey::default();
+    match (storage_backend, commitment_tree, nullifier_tree) {
+        (StorageBackend::Accounts, None, None) => {}
+        (StorageBackend::Compressed, Some(commitment_tree), Some(nullifier_tree)) => {
+            commitment_tree.load_init()?.initialize(pool.key());
+            nullifier_tree.load_init()?.initialize(pool.key())?;
+            pool.commitment_tree = commitment_tree.key();
+            pool.nullifier_tree = nullifier_tree.key();
+        }
+        _ => return err!(ErrorCode::StorageBackendMismatch),
+    }
     Ok(())
 }
@@ -0,0 +1,156 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::instructions::batch_withdraw::create_nullifier_set;
+use crate::instructions::deposit_private::CommitmentAppended;
+use crate::instructions::withdraw_private::NullifierInserted;
+use crate::merkle::NonMembershipProof;
+use crate::state::{
+    read_legacy_registry, CommitmentRegistry, CommitmentTree, NullifierRegistry, NullifierTree,
+    PoolMigration, PrivacyPool, StorageBackend,
+};
+
+#[derive(Accounts)]
+pub struct MigratePool<'info> {
+    #[account(
+        mut,
+        seeds = [b"privacy_pool"],
+        bump = pool.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub pool: Account<'info, PrivacyPool>,
+    #[account(
+        mut,
+        seeds = [b"migration", pool.key().as_ref()],
+        bump = migration.bump
+    )]
+    pub migration: Account<'info, PoolMigration>,
+    /// CHECK: legacy registry, read in place by `read_legacy_registry`
+    #[account(seeds = [b"commitment", pool.key().as_ref()], bump = pool.commitment_bump)]
+    pub commitment_registry: UncheckedAccount<'info>,
+    /// CHECK: legacy registry, read in place by `read_legacy_registry`
+    #[account(seeds = [b"nullifier", pool.key().as_ref()], bump = pool.nullifier_bump)]
+    pub nullifier_registry: UncheckedAccount<'info>,
+    /// Set when migrating to `StorageBackend::Compressed`.
+    #[account(mut, address = pool.commitment_tree)]
+    pub commitment_tree: Option<AccountLoader<'info, CommitmentTree>>,
+    /// Set when migrating to `StorageBackend::Compressed`.
+    #[account(mut, address = pool.nullifier_tree)]
+    pub nullifier_tree: Option<AccountLoader<'info, NullifierTree>>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Moves the next chunk of legacy registry entries into the target backend. Commitments
+/// go first, then nullifiers; the call that moves the last entry marks the pool migrated
+/// and closes the `PoolMigration` account.
+///
+/// Nullifiers to move in this call are given by `nullifier_proofs` for `Compressed`
+/// targets (one non-membership proof per entry, in registry order) or by the remaining
+/// accounts for `Accounts` targets (the `NullifierSet` PDAs to create, in registry order).
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, MigratePool<'info>>,
+    nullifier_proofs: Vec<NonMembershipProof>,
+) -> Result<()> {
+    let pool_key = ctx.accounts.pool.key();
+    let migration = &mut ctx.accounts.migration;
+
+    let commitments_done = match (migration.target, &ctx.accounts.commitment_tree) {
+        // The registry stays the commitment store for `Accounts` pools.
+        (StorageBackend::Accounts, None) => true,
+        (StorageBackend::Compressed, Some(tree)) => {
+            let (count, commitments) = read_legacy_registry::<CommitmentRegistry>(
+                &ctx.accounts.commitment_registry,
+                &pool_key,
+                migration.commitments_migrated,
+                PoolMigration::CHUNK,
+            )?;
+            let mut tree_data = tree.load_mut()?;
+            for commitment in commitments {
+                let index = tree_data.append(&pool_key, commitment)?;
+                emit!(CommitmentAppended {
+                    pool: pool_key,
+                    tree: tree.key(),
+                    index,
+                    commitment,
+                });
+                migration.commitments_migrated += 1;
+            }
+            migration.commitments_migrated == count
+        }
+        _ => return err!(ErrorCode::StorageBackendMismatch),
+    };
+
+    // Nullifiers only move once every commitment has, keeping each call bounded.
+    let mut nullifiers_done = false;
+    if commitments_done {
+        let batch = match migration.target {
+            StorageBackend::Accounts => ctx.remaining_accounts.len(),
+            StorageBackend::Compressed => nullifier_proofs.len(),
+        };
+        require!(batch <= PoolMigration::CHUNK, ErrorCode::InvalidBatchSize);
+        let (count, nullifiers) = read_legacy_registry::<NullifierRegistry>(
+            &ctx.accounts.nullifier_registry,
+            &pool_key,
+            migration.nullifiers_migrated,
+            batch,
+        )?;
+        require!(nullifiers.len() == batch, ErrorCode::InvalidBatchAccounts);
+
+        match (migration.target, &ctx.accounts.nullifier_tree) {
+            (StorageBackend::Accounts, None) => {
+                let slot = Clock::get()?.slot;
+                for (nullifier, record) in nullifiers.iter().zip(ctx.remaining_accounts) {
+                    create_nullifier_set(
+                        record,
+                        &ctx.accounts.admin,
+                        &ctx.accounts.system_program,
+                        pool_key,
+                        *nullifier,
+                        slot,
+                    )?;
+                }
+            }
+            (StorageBackend::Compressed, Some(tree)) => {
+                let mut tree_data = tree.load_mut()?;
+                for (nullifier, proof) in nullifiers.iter().zip(&nullifier_proofs) {
+                    let index = tree_data.insert(&pool_key, *nullifier, proof)?;
+                    emit!(NullifierInserted {
+                        pool: pool_key,
+                        tree: tree.key(),
+                        nullifier: *nullifier,
+                        index,
+                        low_index: proof.low_index,
+                    });
+                }
+            }
+            _ => return err!(ErrorCode::StorageBackendMismatch),
+        }
+        migration.nullifiers_migrated += batch as u64;
+        nullifiers_done = migration.nullifiers_migrated == count;
+    }
+
+    let complete = commitments_done && nullifiers_done;
+    emit!(PoolMigrationProgress {
+        pool: pool_key,
+        commitments_migrated: migration.commitments_migrated,
+        nullifiers_migrated: migration.nullifiers_migrated,
+        complete,
+    });
+    if complete {
+        ctx.accounts.pool.storage_version = PrivacyPool::STORAGE_VERSION;
+        ctx.accounts
+            .migration
+            .close(ctx.accounts.admin.to_account_info())?;
+    }
+    Ok(())
+}
+
+#[event]
+pub struct PoolMigrationProgress {
+    pub pool: Pubkey,
+    pub commitments_migrated: u64,
+    pub nullifiers_migrated: u64,
+    pub complete: bool,
+}
@@ -1,17 +1,80 @@
+// Every module exports a `handler`; lib.rs calls them by path, so the clash is harmless.
+#![allow(ambiguous_glob_reexports)]
+
 pub mod admin_pause;
+pub mod admin_set_call_policy;
 pub mod admin_set_fees;
+pub mod admin_set_limits;
 pub mod admin_unpause;
+pub mod aggregate_withdraw;
+pub mod archive_nullifier;
+pub mod batch_deposit;
+pub mod batch_withdraw;
+pub mod begin_pool_migration;
+pub mod claim_pending_deposits;
+pub mod claim_stake_rewards;
+pub mod close_privacy_account;
+pub mod close_proof_buffer;
+pub mod deposit_for;
 pub mod deposit_private;
+pub mod deposit_rewards;
+pub mod emergency_withdraw;
+pub mod harvest_stake_rewards;
+pub mod init_caller_allowlist;
 pub mod init_privacy_account;
+pub mod init_proof_buffer;
+pub mod init_stake_strategy;
 pub mod initialize_pool;
+pub mod migrate_pool;
+pub mod rebalance_stake;
+pub mod register_auditor_key;
+pub mod rotate_encryption_key;
+pub mod rotate_spend_key;
+pub mod set_stake_strategy;
+pub mod settle_stake;
 pub mod transfer_private;
+pub mod transfer_stealth;
+pub mod update_caller_allowlist;
+pub mod upgrade_account;
+pub mod verify_proof_buffer;
 pub mod withdraw_private;
+pub mod write_proof_chunk;
 
 pub use admin_pause::*;
+pub use admin_set_call_policy::*;
 pub use admin_set_fees::*;
+pub use admin_set_limits::*;
 pub use admin_unpause::*;
+pub use aggregate_withdraw::*;
+pub use archive_nullifier::*;
+pub use batch_deposit::*;
+pub use batch_withdraw::*;
+pub use begin_pool_migration::*;
+pub use claim_pending_deposits::*;
+pub use claim_stake_rewards::*;
+pub use close_privacy_account::*;
+pub use close_proof_buffer::*;
+pub use deposit_for::*;
 pub use deposit_private::*;
+pub use deposit_rewards::*;
+pub use emergency_withdraw::*;
+pub use harvest_stake_rewards::*;
+pub use init_caller_allowlist::*;
 pub use init_privacy_account::*;
+pub use init_proof_buffer::*;
+pub use init_stake_strategy::*;
 pub use initialize_pool::*;
+pub use migrate_pool::*;
+pub use rebalance_stake::*;
+pub use register_auditor_key::*;
+pub use rotate_encryption_key::*;
+pub use rotate_spend_key::*;
+pub use set_stake_strategy::*;
+pub use settle_stake::*;
 pub use transfer_private::*;
+pub use transfer_stealth::*;
+pub use update_caller_allowlist::*;
+pub use upgrade_account::*;
+pub use verify_proof_buffer::*;
 pub use withdraw_private::*;
+pub use write_proof_chunk::*;
@@ -0,0 +1,204 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::{invoke, invoke_signed};
+use anchor_lang::solana_program::stake::{
+    self,
+    instruction as stake_instruction,
+    state::{Authorized, Lockup, StakeStateV2},
+};
+use anchor_lang::solana_program::{system_instruction, sysvar};
+
+use crate::error::ErrorCode;
+use crate::state::{PrivacyPool, StakeAction, StakeStrategy, TransientState};
+
+/// Permissionless crank moving the delegated amount one step towards
+/// `StakeStrategy::target_staked`. Fails with `NothingToRebalance` when already there or
+/// while a transient account awaits `settle_stake`. Delegating fails with `PoolPaused`
+/// while staking or withdrawals are paused; unstaking is always allowed.
+#[derive(Accounts)]
+pub struct RebalanceStake<'info> {
+    #[account(seeds = [b"privacy_pool"], bump = pool.bump)]
+    pub pool: Account<'info, PrivacyPool>,
+    #[account(
+        mut,
+        seeds = [b"stake_strategy", pool.key().as_ref()],
+        bump = stake_strategy.bump,
+        has_one = pool,
+        has_one = vote_account
+    )]
+    pub stake_strategy: Account<'info, StakeStrategy>,
+    /// CHECK: vault PDA; staker and withdrawer of both stake accounts
+    #[account(mut, seeds = [b"vault", pool.key().as_ref()], bump = pool.vault_bump)]
+    pub vault: UncheckedAccount<'info>,
+    /// CHECK: main stake account PDA
+    #[account(mut, seeds = [b"stake", pool.key().as_ref()], bump = stake_strategy.stake_bump)]
+    pub main_stake: UncheckedAccount<'info>,
+    /// CHECK: transient stake account PDA
+    #[account(
+        mut,
+        seeds = [b"transient_stake", pool.key().as_ref()],
+        bump = stake_strategy.transient_bump
+    )]
+    pub transient_stake: UncheckedAccount<'info>,
+    /// CHECK: matched against the strategy
+    pub vote_account: UncheckedAccount<'info>,
+    pub clock: Sysvar<'info, Clock>,
+    pub rent: Sysvar<'info, Rent>,
+    /// CHECK: stake history sysvar
+    #[account(address = sysvar::stake_history::ID)]
+    pub stake_history: UncheckedAccount<'info>,
+    /// CHECK: stake config account
+    #[account(address = STAKE_CONFIG_ID)]
+    pub stake_config: UncheckedAccount<'info>,
+    /// CHECK: native stake program
+    #[account(address = stake::program::ID)]
+    pub stake_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[allow(deprecated)]
+pub(crate) const STAKE_CONFIG_ID: Pubkey = stake::config::ID;
+
+pub fn handler(ctx: Context<RebalanceStake>) -> Result<()> {
+    let rent = &ctx.accounts.rent;
+    let vault = ctx.accounts.vault.to_account_info();
+    let liquid = vault.lamports().saturating_sub(rent.minimum_balance(0));
+    let reserve = rent.minimum_balance(StakeStateV2::size_of());
+    let action = ctx.accounts.stake_strategy.plan_rebalance(
+        ctx.accounts.pool.total_locked,
+        liquid,
+        reserve,
+    );
+
+    let pool_key = ctx.accounts.pool.key();
+    let vault_bump = [ctx.accounts.pool.vault_bump];
+    let vault_seeds: &[&[u8]] = &[b"vault", pool_key.as_ref(), &vault_bump];
+    let stake_bump = [ctx.accounts.stake_strategy.stake_bump];
+    let stake_seeds: &[&[u8]] = &[b"stake", pool_key.as_ref(), &stake_bump];
+    let transient_bump = [ctx.accounts.stake_strategy.transient_bump];
+    let transient_seeds: &[&[u8]] = &[b"transient_stake", pool_key.as_ref(), &transient_bump];
+
+    let stake_program = ctx.accounts.stake_program.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+    let strategy = &mut ctx.accounts.stake_strategy;
+    match action {
+        StakeAction::None => return err!(ErrorCode::NothingToRebalance),
+        StakeAction::Stake(lamports) => {
+            ctx.accounts.pool.check_can_stake()?;
+            // The first delegation creates the main account; later ones go through the
+            // transient account and are merged once active.
+            let (target, seeds) = if strategy.staked == 0 {
+                (ctx.accounts.main_stake.to_account_info(), stake_seeds)
+            } else {
+                (ctx.accounts.transient_stake.to_account_info(), transient_seeds)
+            };
+            fund_stake_account(&vault, &target, &system_program, lamports, vault_seeds, seeds)?;
+            invoke(
+                &stake_instruction::initialize(
+                    target.key,
+                    &Authorized::auto(vault.key),
+                    &Lockup::default(),
+                ),
+                &[target.clone(), ctx.accounts.rent.to_account_info(), stake_program.clone()],
+            )?;
+            invoke_signed(
+                &stake_instruction::delegate_stake(
+                    target.key,
+                    vault.key,
+                    ctx.accounts.vote_account.key,
+                ),
+                &[
+                    target.clone(),
+                    ctx.accounts.vote_account.to_account_info(),
+                    ctx.accounts.clock.to_account_info(),
+                    ctx.accounts.stake_history.to_account_info(),
+                    ctx.accounts.stake_config.to_account_info(),
+                    vault.clone(),
+                    stake_program.clone(),
+                ],
+                &[vault_seeds],
+            )?;
+            if strategy.staked == 0 {
+                strategy.staked = target.lamports();
+            } else {
+                strategy.transient = target.lamports();
+                strategy.transient_state = TransientState::Activating;
+            }
+        }
+        StakeAction::Unstake(lamports) => {
+            // Split destinations of active stake must already hold the rent reserve.
+            let transient = ctx.accounts.transient_stake.to_account_info();
+            let main = ctx.accounts.main_stake.to_account_info();
+            fund_stake_account(
+                &vault,
+                &transient,
+                &system_program,
+                reserve,
+                vault_seeds,
+                transient_seeds,
+            )?;
+            // The first two instructions allocate and assign `transient`, done above.
+            let split = stake_instruction::split(main.key, vault.key, lamports, transient.key);
+            invoke_signed(
+                &split[2],
+                &[main, transient.clone(), vault.clone(), stake_program.clone()],
+                &[vault_seeds],
+            )?;
+            invoke_signed(
+                &stake_instruction::deactivate_stake(transient.key, vault.key),
+                &[transient.clone(), ctx.accounts.clock.to_account_info(), vault, stake_program],
+                &[vault_seeds],
+            )?;
+            strategy.staked -= lamports;
+            strategy.transient = transient.lamports();
+            strategy.transient_state = TransientState::Deactivating;
+        }
+    }
+
+    emit!(StakeRebalanced {
+        pool: pool_key,
+        staked: strategy.staked,
+        transient: strategy.transient,
+        transient_state: strategy.transient_state,
+    });
+    Ok(())
+}
+
+/// Tops `account` up to `lamports` from the vault and hands it to the stake program. Uses
+/// transfer, allocate and assign rather than `create_account`, so lamports sent to the PDA
+/// beforehand cannot block it.
+pub(crate) fn fund_stake_account<'info>(
+    vault: &AccountInfo<'info>,
+    account: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    lamports: u64,
+    vault_seeds: &[&[u8]],
+    account_seeds: &[&[u8]],
+) -> Result<()> {
+    let shortfall = lamports.saturating_sub(account.lamports());
+    if shortfall > 0 {
+        invoke_signed(
+            &system_instruction::transfer(vault.key, account.key, shortfall),
+            &[vault.clone(), account.clone(), system_program.clone()],
+            &[vault_seeds],
+        )?;
+    }
+    invoke_signed(
+        &system_instruction::allocate(account.key, StakeStateV2::size_of() as u64),
+        &[account.clone(), system_program.clone()],
+        &[account_seeds],
+    )?;
+    invoke_signed(
+        &system_instruction::assign(account.key, &stake::program::ID),
+        &[account.clone(), system_program.clone()],
+        &[account_seeds],
+    )?;
+    Ok(())
+}
+
+#[event]
+pub struct StakeRebalanced {
+    pub pool: Pubkey,
+    pub staked: u64,
+    pub transient: u64,
+    pub transient_state: TransientState,
+}
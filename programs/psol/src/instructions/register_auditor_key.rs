@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::state::PrivacyPool;
+
+#[derive(Accounts)]
+pub struct RegisterAuditorKey<'info> {
+    #[account(mut, has_one = admin @ ErrorCode::Unauthorized)]
+    pub pool: Account<'info, PrivacyPool>,
+    pub admin: Signer<'info>,
+}
+
+/// Sets the pool-level auditor view key. Passing all zeroes removes it.
+pub fn handler(ctx: Context<RegisterAuditorKey>, auditor_key: [u8; 32]) -> Result<()> {
+    ctx.accounts.pool.auditor_key = auditor_key;
+    emit!(AuditorKeyRegistered {
+        pool: ctx.accounts.pool.key(),
+        auditor_key,
+    });
+    Ok(())
+}
+
+#[event]
+pub struct AuditorKeyRegistered {
+    pub pool: Pubkey,
+    pub auditor_key: [u8; 32],
+}
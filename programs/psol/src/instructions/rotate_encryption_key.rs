@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::state::PrivacyAccount;
+
+#[derive(Accounts)]
+pub struct RotateEncryptionKey<'info> {
+    #[account(
+        mut,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub privacy_account: Account<'info, PrivacyAccount>,
+    pub owner: Signer<'info>,
+}
+
+/// Re-keys the account's view key. The proof shows `new_encrypted_balance` under
+/// `new_view_key` holds the same value as the current balance under the old key,
+/// so the swap can happen without revealing the balance. The spend key is unchanged;
+/// `rotate_spend_key` replaces it, in the same transaction when a device is lost.
+pub fn handler(
+    ctx: Context<RotateEncryptionKey>,
+    new_view_key: [u8; 32],
+    new_encrypted_balance: [u8; 64],
+    proof: Vec<u8>,
+) -> Result<()> {
+    let privacy_account = &mut ctx.accounts.privacy_account;
+    let old_view_key = privacy_account.rotate_view_key(
+        new_view_key,
+        new_encrypted_balance,
+        &proof,
+        Clock::get()?.slot,
+    )?;
+
+    emit!(EncryptionKeyRotated {
+        privacy_account: privacy_account.key(),
+        old_view_key,
+        new_view_key,
+        nonce: privacy_account.nonce,
+    });
+    Ok(())
+}
+
+#[event]
+pub struct EncryptionKeyRotated {
+    pub privacy_account: Pubkey,
+    pub old_view_key: [u8; 32],
+    pub new_view_key: [u8; 32],
+    pub nonce: u64,
+}
@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::state::PrivacyAccount;
+
+#[derive(Accounts)]
+pub struct RotateSpendKey<'info> {
+    #[account(
+        mut,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub privacy_account: Account<'info, PrivacyAccount>,
+    pub owner: Signer<'info>,
+}
+
+/// Re-keys the account's spend key. The proof, made with the current spend key, hands the
+/// account's commitment over to `new_spend_key`; the balance ciphertext is encrypted to the
+/// view key and stays as it is.
+pub fn handler(
+    ctx: Context<RotateSpendKey>,
+    new_spend_key: [u8; 32],
+    proof: Vec<u8>,
+) -> Result<()> {
+    let privacy_account = &mut ctx.accounts.privacy_account;
+    let old_spend_key =
+        privacy_account.rotate_spend_key(new_spend_key, &proof, Clock::get()?.slot)?;
+
+    emit!(SpendKeyRotated {
+        privacy_account: privacy_account.key(),
+        old_spend_key,
+        new_spend_key,
+        nonce: privacy_account.nonce,
+    });
+    Ok(())
+}
+
+#[event]
+pub struct SpendKeyRotated {
+    pub privacy_account: Pubkey,
+    pub old_spend_key: [u8; 32],
+    pub new_spend_key: [u8; 32],
+    pub nonce: u64,
+}
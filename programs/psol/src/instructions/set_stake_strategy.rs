@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::state::{PrivacyPool, StakeStrategy};
+
+#[derive(Accounts)]
+pub struct SetStakeStrategy<'info> {
+    #[account(has_one = admin @ ErrorCode::Unauthorized)]
+    pub pool: Account<'info, PrivacyPool>,
+    #[account(mut, has_one = pool)]
+    pub stake_strategy: Account<'info, StakeStrategy>,
+    pub admin: Signer<'info>,
+}
+
+/// Takes effect at the next `rebalance_stake`; a zero `target_bps` unwinds all stake.
+pub fn handler(
+    ctx: Context<SetStakeStrategy>,
+    target_bps: u16,
+    min_buffer: u64,
+    rewards_authority: Pubkey,
+) -> Result<()> {
+    let strategy = &mut ctx.accounts.stake_strategy;
+    strategy.set_target(target_bps, min_buffer)?;
+    strategy.rewards_authority = rewards_authority;
+    Ok(())
+}
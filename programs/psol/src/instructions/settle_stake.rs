@@ -0,0 +1,99 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::solana_program::stake::{self, instruction as stake_instruction};
+use anchor_lang::solana_program::sysvar;
+
+use crate::error::ErrorCode;
+use crate::instructions::harvest_stake_rewards::StakeRewardsHarvested;
+use crate::state::{PrivacyPool, StakeStrategy, TransientState};
+
+/// Permissionless crank finishing the transient account's move: merges activated stake
+/// into the main account, or withdraws deactivated stake to the vault. The stake program
+/// rejects either until the epoch boundary has passed.
+#[derive(Accounts)]
+pub struct SettleStake<'info> {
+    #[account(mut, seeds = [b"privacy_pool"], bump = pool.bump)]
+    pub pool: Account<'info, PrivacyPool>,
+    #[account(
+        mut,
+        seeds = [b"stake_strategy", pool.key().as_ref()],
+        bump = stake_strategy.bump,
+        has_one = pool
+    )]
+    pub stake_strategy: Account<'info, StakeStrategy>,
+    /// CHECK: vault PDA; staker and withdrawer of both stake accounts
+    #[account(mut, seeds = [b"vault", pool.key().as_ref()], bump = pool.vault_bump)]
+    pub vault: UncheckedAccount<'info>,
+    /// CHECK: main stake account PDA
+    #[account(mut, seeds = [b"stake", pool.key().as_ref()], bump = stake_strategy.stake_bump)]
+    pub main_stake: UncheckedAccount<'info>,
+    /// CHECK: transient stake account PDA
+    #[account(
+        mut,
+        seeds = [b"transient_stake", pool.key().as_ref()],
+        bump = stake_strategy.transient_bump
+    )]
+    pub transient_stake: UncheckedAccount<'info>,
+    pub clock: Sysvar<'info, Clock>,
+    /// CHECK: stake history sysvar
+    #[account(address = sysvar::stake_history::ID)]
+    pub stake_history: UncheckedAccount<'info>,
+    /// CHECK: native stake program
+    #[account(address = stake::program::ID)]
+    pub stake_program: UncheckedAccount<'info>,
+}
+
+pub fn handler(ctx: Context<SettleStake>) -> Result<()> {
+    let strategy = &mut ctx.accounts.stake_strategy;
+    let state = strategy.transient_state;
+    require!(state != TransientState::Idle, ErrorCode::NoTransientStake);
+
+    // Count anything earned while in flight before the transient balance moves.
+    let main = ctx.accounts.main_stake.to_account_info();
+    let transient = ctx.accounts.transient_stake.to_account_info();
+    let rewards = strategy.record_balances(main.lamports(), transient.lamports())?;
+    ctx.accounts.pool.lock(rewards)?;
+
+    let pool_key = ctx.accounts.pool.key();
+    let vault_bump = [ctx.accounts.pool.vault_bump];
+    let vault_seeds: &[&[u8]] = &[b"vault", pool_key.as_ref(), &vault_bump];
+    let vault = ctx.accounts.vault.to_account_info();
+    let clock = ctx.accounts.clock.to_account_info();
+    let stake_history = ctx.accounts.stake_history.to_account_info();
+    let stake_program = ctx.accounts.stake_program.to_account_info();
+    if state == TransientState::Activating {
+        let merge = stake_instruction::merge(main.key, transient.key, vault.key);
+        invoke_signed(
+            &merge[0],
+            &[main, transient, clock, stake_history, vault, stake_program],
+            &[vault_seeds],
+        )?;
+        strategy.staked = strategy
+            .staked
+            .checked_add(strategy.transient)
+            .ok_or(ErrorCode::MathOverflow)?;
+    } else {
+        invoke_signed(
+            &stake_instruction::withdraw(
+                transient.key,
+                vault.key,
+                vault.key,
+                strategy.transient,
+                None,
+            ),
+            &[transient, vault.clone(), clock, stake_history, vault, stake_program],
+            &[vault_seeds],
+        )?;
+    }
+    strategy.transient = 0;
+    strategy.transient_state = TransientState::Idle;
+
+    if rewards > 0 {
+        emit!(StakeRewardsHarvested {
+            pool: pool_key,
+            amount: rewards,
+            unclaimed_rewards: strategy.unclaimed_rewards,
+        });
+    }
+    Ok(())
+}
@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
 use crate::crypto;
-use crate::state::{PrivacyAccount, PrivacyPool};
+use crate::events::MemoAttached;
+use crate::state::{Operation, PrivacyAccount, PrivacyPool};
 use crate::error::ErrorCode;
 
 #[derive(Accounts)]
@@ -12,18 +13,18 @@ pub struct TransferPrivate<'info> {
     )]
     pub pool: Account<'info, PrivacyPool>,
 
+    /// The sender's privacy account or one of their stealth accounts.
     #[account(
         mut,
-        seeds = [b"privacy_account", sender.key().as_ref()],
-        bump = sender_account.bump,
         constraint = sender_account.owner == sender.key() @ ErrorCode::Unauthorized
     )]
     pub sender_account: Account<'info, PrivacyAccount>,
 
     #[account(
         mut,
-        seeds = [b"privacy_account", recipient.key().as_ref()],
-        bump = recipient_account.bump
+        seeds = [b"privacy", recipient.key().as_ref()],
+        bump = recipient_account.bump,
+        constraint = recipient_account.key() != sender_account.key() @ ErrorCode::SelfTransfer
     )]
     pub recipient_account: Account<'info, PrivacyAccount>,
 
@@ -38,8 +39,19 @@ pub fn handler(
     ctx: Context<TransferPrivate>,
     encrypted_amount: [u8; 64],
     proof: Vec<u8>,
+    memo: Option<Vec<u8>>,
+    auditor_amount: Option<[u8; 64]>,
 ) -> Result<()> {
-    ctx.accounts.pool.check_not_paused()?;
+    ctx.accounts.pool.check_not_paused(Operation::Transfer)?;
+    if ctx.accounts.pool.has_auditor() {
+        require!(
+            auditor_amount.is_some(),
+            ErrorCode::AuditorCiphertextRequired
+        );
+    }
+    if let Some(memo) = memo.as_deref() {
+        crypto::validate_memo(memo)?;
+    }
 
     let sender_account = &mut ctx.accounts.sender_account;
     let recipient_account = &mut ctx.accounts.recipient_account;
@@ -49,6 +61,7 @@ pub fn handler(
             &sender_account.encrypted_balance,
             &encrypted_amount,
             &sender_account.commitment,
+            &sender_account.spend_key,
             &proof,
         ),
         ErrorCode::InvalidProof
@@ -61,11 +74,49 @@ pub fn handler(
         crypto::add_encrypted(&recipient_account.encrypted_balance, &encrypted_amount)?;
 
     sender_account.nonce = sender_account.nonce.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+    sender_account.transfers_out = sender_account
+        .transfers_out
+        .checked_add(1)
+        .ok_or(ErrorCode::MathOverflow)?;
     recipient_account.nonce = recipient_account.nonce.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+    recipient_account.transfers_in = recipient_account
+        .transfers_in
+        .checked_add(1)
+        .ok_or(ErrorCode::MathOverflow)?;
 
-    sender_account.last_update = Clock::get()?.slot;
-    recipient_account.last_update = Clock::get()?.slot;
+    let slot = Clock::get()?.slot;
+    sender_account.last_update = slot;
+    recipient_account.last_update = slot;
+
+    emit!(PrivateTransfer {
+        pool: ctx.accounts.pool.key(),
+        sender_account: sender_account.key(),
+        recipient_account: recipient_account.key(),
+        encrypted_amount,
+        auditor_amount,
+        slot,
+    });
+
+    if let Some(memo) = memo {
+        emit!(MemoAttached {
+            pool: ctx.accounts.pool.key(),
+            recipient_account: ctx.accounts.recipient_account.key(),
+            memo,
+        });
+    }
 
     msg!("Private transfer completed");
     Ok(())
 }
+
+/// `encrypted_amount` is encrypted to the recipient's `view_key`; `auditor_amount`
+/// carries the same amount encrypted to the pool's `auditor_key` when one is registered.
+#[event]
+pub struct PrivateTransfer {
+    pub pool: Pubkey,
+    pub sender_account: Pubkey,
+    pub recipient_account: Pubkey,
+    pub encrypted_amount: [u8; 64],
+    pub auditor_amount: Option<[u8; 64]>,
+    pub slot: u64,
+}
@@ -0,0 +1,141 @@
+use anchor_lang::prelude::*;
+use crate::crypto;
+use crate::events::MemoAttached;
+use crate::instructions::transfer_private::PrivateTransfer;
+use crate::state::{Operation, PrivacyAccount, PrivacyPool};
+use crate::error::ErrorCode;
+
+/// Private transfer to a one-time stealth address. The recipient account is created at
+/// `["stealth", pool, one_time_address]`, so the recipient's published keys never appear on
+/// chain, and a sender cannot claim the `["privacy", owner]` account of any key it names.
+/// `view_key` is the one-time view public key from `stealth::derive_payment`; the secret
+/// derived from the shared secret stays off chain.
+#[derive(Accounts)]
+#[instruction(one_time_address: Pubkey)]
+pub struct TransferStealth<'info> {
+    #[account(
+        seeds = [b"privacy_pool"],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, PrivacyPool>,
+
+    #[account(
+        mut,
+        constraint = sender_account.owner == sender.key() @ ErrorCode::Unauthorized
+    )]
+    pub sender_account: Account<'info, PrivacyAccount>,
+
+    #[account(
+        init,
+        payer = sender,
+        space = PrivacyAccount::SIZE,
+        seeds = [b"stealth", pool.key().as_ref(), one_time_address.as_ref()],
+        bump
+    )]
+    pub recipient_account: Account<'info, PrivacyAccount>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn handler(
+    ctx: Context<TransferStealth>,
+    one_time_address: Pubkey,
+    ephemeral_pubkey: [u8; 32],
+    view_tag: u8,
+    view_key: [u8; 32],
+    encrypted_amount: [u8; 64],
+    proof: Vec<u8>,
+    memo: Option<Vec<u8>>,
+    auditor_amount: Option<[u8; 64]>,
+) -> Result<()> {
+    ctx.accounts.pool.check_not_paused(Operation::Transfer)?;
+    if ctx.accounts.pool.has_auditor() {
+        require!(
+            auditor_amount.is_some(),
+            ErrorCode::AuditorCiphertextRequired
+        );
+    }
+    if let Some(memo) = memo.as_deref() {
+        crypto::validate_memo(memo)?;
+    }
+
+    let sender_account = &mut ctx.accounts.sender_account;
+    let recipient_account = &mut ctx.accounts.recipient_account;
+
+    require!(
+        crypto::verify_transfer_proof(
+            &sender_account.encrypted_balance,
+            &encrypted_amount,
+            &sender_account.commitment,
+            &sender_account.spend_key,
+            &proof,
+        ),
+        ErrorCode::InvalidProof
+    );
+
+    let slot = Clock::get()?.slot;
+
+    recipient_account.version = PrivacyAccount::VERSION;
+    recipient_account.owner = one_time_address;
+    recipient_account.encrypted_balance = encrypted_amount;
+    recipient_account.commitment = [0u8; 32];
+    recipient_account.spend_key = one_time_address.to_bytes();
+    recipient_account.view_key = view_key;
+    recipient_account.nonce = 0;
+    recipient_account.total_deposits = 0;
+    recipient_account.total_withdrawals = 0;
+    recipient_account.last_update = slot;
+    recipient_account.bump = ctx.bumps.recipient_account;
+    recipient_account.transfers_out = 0;
+    recipient_account.transfers_in = 1;
+    recipient_account.last_deposit_slot = 0;
+
+    sender_account.encrypted_balance =
+        crypto::subtract_encrypted(&sender_account.encrypted_balance, &encrypted_amount)?;
+    sender_account.nonce = sender_account.nonce.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+    sender_account.transfers_out = sender_account
+        .transfers_out
+        .checked_add(1)
+        .ok_or(ErrorCode::MathOverflow)?;
+    sender_account.last_update = slot;
+
+    emit!(PrivateTransfer {
+        pool: ctx.accounts.pool.key(),
+        sender_account: sender_account.key(),
+        recipient_account: recipient_account.key(),
+        encrypted_amount,
+        auditor_amount,
+        slot,
+    });
+    emit!(StealthPayment {
+        pool: ctx.accounts.pool.key(),
+        recipient_account: recipient_account.key(),
+        one_time_address,
+        ephemeral_pubkey,
+        view_tag,
+    });
+    if let Some(memo) = memo {
+        emit!(MemoAttached {
+            pool: ctx.accounts.pool.key(),
+            recipient_account: recipient_account.key(),
+            memo,
+        });
+    }
+
+    msg!("Stealth transfer completed");
+    Ok(())
+}
+
+/// Scanned by wallets: `view_tag` filters candidates before the full address check.
+#[event]
+pub struct StealthPayment {
+    pub pool: Pubkey,
+    pub recipient_account: Pubkey,
+    pub one_time_address: Pubkey,
+    pub ephemeral_pubkey: [u8; 32],
+    pub view_tag: u8,
+}
@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::state::{CallerAllowlist, PrivacyPool};
+
+#[derive(Accounts)]
+pub struct UpdateCallerAllowlist<'info> {
+    #[account(has_one = admin @ ErrorCode::Unauthorized)]
+    pub pool: Account<'info, PrivacyPool>,
+    #[account(
+        mut,
+        seeds = [b"caller_allowlist", pool.key().as_ref()],
+        bump = caller_allowlist.bump
+    )]
+    pub caller_allowlist: Account<'info, CallerAllowlist>,
+    pub admin: Signer<'info>,
+}
+
+/// Approves `program` to deposit and withdraw through CPI, or revokes it when `approved`
+/// is false.
+pub fn handler(ctx: Context<UpdateCallerAllowlist>, program: Pubkey, approved: bool) -> Result<()> {
+    let allowlist = &mut ctx.accounts.caller_allowlist;
+    if approved {
+        allowlist.insert(program)?;
+    } else {
+        allowlist.remove(&program);
+    }
+    emit!(CallerAllowlistUpdated {
+        pool: ctx.accounts.pool.key(),
+        program,
+        approved,
+    });
+    Ok(())
+}
+
+#[event]
+pub struct CallerAllowlistUpdated {
+    pub pool: Pubkey,
+    pub program: Pubkey,
+    pub approved: bool,
+}
@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use crate::error::ErrorCode;
+use crate::state::{
+    layout_version, rewrite_account, PrivacyAccount, PrivacyAccountV0, PrivacyPool, PrivacyPoolV0,
+};
+
+/// Rewrites a `PrivacyPool` or `PrivacyAccount` stored under an older layout into the
+/// current one. Permissionless: the conversion is fixed, and `payer` only covers the rent
+/// for the larger account.
+#[derive(Accounts)]
+pub struct UpgradeAccount<'info> {
+    /// CHECK: type and layout version are read from the data by the handler
+    #[account(mut, owner = crate::ID)]
+    pub account: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<UpgradeAccount>) -> Result<()> {
+    let account = ctx.accounts.account.to_account_info();
+    let mut upgraded = Vec::new();
+    let (from_version, to_version) = {
+        let data = account.try_borrow_data()?;
+        if data.starts_with(&PrivacyPool::DISCRIMINATOR) {
+            let from_version = layout_version::<PrivacyPool>(&data, PrivacyPoolV0::SIZE)?;
+            PrivacyPool::decode_any_version(&data, Clock::get()?.slot)?
+                .try_serialize(&mut upgraded)?;
+            (from_version, PrivacyPool::VERSION)
+        } else if data.starts_with(&PrivacyAccount::DISCRIMINATOR) {
+            let from_version =
+                layout_version::<PrivacyAccount>(&data, PrivacyAccountV0::SIZE)?;
+            PrivacyAccount::decode_any_version(&data)?.try_serialize(&mut upgraded)?;
+            (from_version, PrivacyAccount::VERSION)
+        } else {
+            return err!(ErrorCode::UnsupportedAccountType);
+        }
+    };
+    require!(from_version != to_version, ErrorCode::AccountAlreadyCurrent);
+
+    rewrite_account(
+        &account,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &upgraded,
+    )?;
+
+    emit!(AccountUpgraded {
+        account: account.key(),
+        from_version,
+        to_version,
+    });
+    Ok(())
+}
+
+#[event]
+pub struct AccountUpgraded {
+    pub account: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+}
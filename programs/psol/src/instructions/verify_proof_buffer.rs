@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+
+use crate::crypto;
+use crate::error::ErrorCode;
+use crate::state::{ProofBuffer, VerificationStage};
+
+/// Permissionless: each call advances verification of an uploaded proof by one step.
+#[derive(Accounts)]
+pub struct VerifyProofBuffer<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"proof_buffer",
+            proof_buffer.pool.as_ref(),
+            proof_buffer.owner.as_ref(),
+            proof_buffer.nullifier.as_ref()
+        ],
+        bump = proof_buffer.bump
+    )]
+    pub proof_buffer: Account<'info, ProofBuffer>,
+}
+
+pub fn handler(ctx: Context<VerifyProofBuffer>) -> Result<()> {
+    let proof_buffer: &mut ProofBuffer = &mut ctx.accounts.proof_buffer;
+    require!(
+        proof_buffer.stage == VerificationStage::Verifying,
+        ErrorCode::ProofBufferNotReady
+    );
+
+    let verified = crypto::verify_proof_step(
+        &proof_buffer.proof,
+        &proof_buffer.public_inputs,
+        &mut proof_buffer.step,
+        &mut proof_buffer.accumulator,
+    )?;
+    if verified {
+        proof_buffer.stage = VerificationStage::Verified;
+    }
+
+    msg!(
+        "Proof buffer step {} (verified: {})",
+        proof_buffer.step,
+        verified
+    );
+    Ok(())
+}
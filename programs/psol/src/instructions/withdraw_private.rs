@@ -1,57 +1,158 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::program::invoke_signed;
-use anchor_lang::solana_program::system_instruction;
+use anchor_lang::solana_program::{system_instruction, sysvar};
 
+use crate::crypto;
 use crate::error::ErrorCode;
-use crate::state::{NullifierRegistry, PrivacyAccount, PrivacyPool};
+use crate::guard;
+use crate::merkle::NonMembershipProof;
+use crate::state::{
+    CallerAllowlist, NullifierArchive, NullifierSet, NullifierTree, Operation, PrivacyAccount,
+    PrivacyPool, ProofBuffer, StorageBackend,
+};
 
 #[derive(Accounts)]
+#[instruction(amount: u64, nullifier: [u8; 32])]
 pub struct WithdrawPrivate<'info> {
-    #[account(mut)]
+    #[account(mut, seeds = [b"privacy_pool"], bump = pool.bump)]
     pub pool: Account<'info, PrivacyPool>,
     /// CHECK: vault PDA
     #[account(mut, seeds = [b"vault", pool.key().as_ref()], bump = pool.vault_bump)]
     pub vault: UncheckedAccount<'info>,
+    /// Set for `StorageBackend::Accounts` pools, together with `nullifier_archive`.
+    #[account(
+        init,
+        payer = owner,
+        space = NullifierSet::SIZE,
+        seeds = [b"nullifier", pool.key().as_ref(), nullifier.as_ref()],
+        bump
+    )]
+    pub nullifier_set: Option<Account<'info, NullifierSet>>,
+    #[account(
+        seeds = [b"nullifier_archive", pool.key().as_ref()],
+        bump = nullifier_archive.bump
+    )]
+    pub nullifier_archive: Option<Account<'info, NullifierArchive>>,
+    /// Set for `StorageBackend::Compressed` pools.
+    #[account(mut, address = pool.nullifier_tree)]
+    pub nullifier_tree: Option<AccountLoader<'info, NullifierTree>>,
+    /// Verified proof staged with `init_proof_buffer`; replaces `proof` and is closed here.
     #[account(
         mut,
-        seeds = [b"nullifier", pool.key().as_ref()],
-        bump = pool.nullifier_bump
+        close = owner,
+        seeds = [b"proof_buffer", pool.key().as_ref(), owner.key().as_ref(), nullifier.as_ref()],
+        bump = proof_buffer.bump
     )]
-    pub nullifier_registry: Account<'info, NullifierRegistry>,
+    pub proof_buffer: Option<Account<'info, ProofBuffer>>,
     #[account(mut, has_one = owner)]
     pub privacy_account: Account<'info, PrivacyAccount>,
+    #[account(mut)]
     pub owner: Signer<'info>,
     /// CHECK: recipient for SOL withdrawal
     #[account(mut)]
     pub recipient: UncheckedAccount<'info>,
+    /// CHECK: recorded as `nullifier_set.payer`, where `archive_nullifier` refunds the rent;
+    /// defaults to `owner`. Lets PDA callers such as psol-token name the user who funded it.
+    pub rent_refund: Option<UncheckedAccount<'info>>,
+    /// CHECK: instructions sysvar, read by the call policy checks
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    /// Required while the pool enforces `CallPolicy::ApprovedCallersOnly`.
+    #[account(seeds = [b"caller_allowlist", pool.key().as_ref()], bump = caller_allowlist.bump)]
+    pub caller_allowlist: Option<Account<'info, CallerAllowlist>>,
     pub system_program: Program<'info, System>,
 }
 
-pub fn withdraw_private(
+/// `proof` may be empty when a verified `proof_buffer` is supplied instead.
+/// `nullifier_proof` shows `nullifier` is absent from the archive (`Accounts` pools,
+/// optional while the archive is empty) or from the nullifier tree (`Compressed` pools,
+/// always required, against any recent root).
+pub fn handler(
     ctx: Context<WithdrawPrivate>,
     amount: u64,
     nullifier: [u8; 32],
+    proof: Vec<u8>,
+    nullifier_proof: Option<NonMembershipProof>,
 ) -> Result<()> {
-    ctx.accounts.pool.check_not_paused()?;
-    ctx.accounts
-        .nullifier_registry
-        .register(&ctx.accounts.pool.key(), nullifier)?;
+    ctx.accounts.pool.check_not_paused(Operation::Withdraw)?;
+    guard::check_call_policy(
+        &ctx.accounts.pool,
+        ctx.accounts.caller_allowlist.as_deref(),
+        &ctx.accounts.instructions_sysvar,
+        Operation::Withdraw,
+    )?;
+    guard::enter(&mut ctx.accounts.pool)?;
+    require!(amount > 0, ErrorCode::InvalidAmount);
+
+    let slot = Clock::get()?.slot;
+    if let Err(e) = ctx.accounts.pool.record_withdrawal(amount, slot) {
+        emit!(WithdrawalLimitHit {
+            pool: ctx.accounts.pool.key(),
+            amount,
+            window_withdrawn: ctx.accounts.pool.withdrawn_in_window(slot),
+            slot,
+        });
+        return Err(e);
+    }
 
-    ctx.accounts.privacy_account.withdraw(amount)?;
-    let (net_amount, _) = ctx.accounts.pool.apply_fee(amount)?;
+    let privacy_account = &mut ctx.accounts.privacy_account;
+    let public_inputs = crypto::withdrawal_inputs(
+        &privacy_account.commitment,
+        &privacy_account.spend_key,
+        &nullifier,
+        amount,
+    );
+    match &ctx.accounts.proof_buffer {
+        Some(proof_buffer) => proof_buffer.check_verified_for(&nullifier, &public_inputs)?,
+        None => require!(
+            crypto::verify_proof(&proof, &public_inputs)?,
+            ErrorCode::InvalidProof
+        ),
+    }
+
+    let pool_key = ctx.accounts.pool.key();
+    match (
+        ctx.accounts.pool.storage_backend,
+        &mut ctx.accounts.nullifier_set,
+        &ctx.accounts.nullifier_archive,
+        &ctx.accounts.nullifier_tree,
+    ) {
+        (StorageBackend::Accounts, Some(nullifier_set), Some(archive), None) => {
+            archive.check_not_archived(&nullifier, nullifier_proof.as_ref())?;
+            nullifier_set.version = NullifierSet::VERSION;
+            nullifier_set.pool = pool_key;
+            nullifier_set.nullifier = nullifier;
+            nullifier_set.payer = ctx
+                .accounts
+                .rent_refund
+                .as_ref()
+                .map_or(ctx.accounts.owner.key(), |a| a.key());
+            nullifier_set.slot = slot;
+            nullifier_set.bump = ctx.bumps.nullifier_set.ok_or(ErrorCode::InvalidNullifier)?;
+        }
+        (StorageBackend::Compressed, None, None, Some(tree)) => {
+            let nullifier_proof = nullifier_proof.ok_or(ErrorCode::InvalidMerkleProof)?;
+            let index = tree
+                .load_mut()?
+                .insert(&pool_key, nullifier, &nullifier_proof)?;
+            emit!(NullifierInserted {
+                pool: pool_key,
+                tree: tree.key(),
+                nullifier,
+                index,
+                low_index: nullifier_proof.low_index,
+            });
+        }
+        _ => return err!(ErrorCode::StorageBackendMismatch),
+    }
 
-    ctx.accounts.pool.total_locked = ctx
-        .accounts
-        .pool
-        .total_locked
-        .checked_sub(net_amount)
-        .ok_or(ErrorCode::MathOverflow)?;
+    guard::check_not_deposited_in_slot(&ctx.accounts.pool, privacy_account, slot)?;
+    privacy_account.withdraw(amount, slot)?;
 
-    let seeds: &[&[u8]] = &[
-        b"vault",
-        ctx.accounts.pool.key().as_ref(),
-        &[ctx.accounts.pool.vault_bump],
-    ];
+    let (net_amount, fee) = ctx.accounts.pool.apply_fee(amount)?;
+    ctx.accounts.pool.unlock(net_amount)?;
+
+    let seeds: &[&[u8]] = &[b"vault", pool_key.as_ref(), &[ctx.accounts.pool.vault_bump]];
     invoke_signed(
         &system_instruction::transfer(
             &ctx.accounts.vault.key(),
@@ -66,5 +167,26 @@ pub fn withdraw_private(
         &[seeds],
     )?;
 
+    msg!("Withdrew {} lamports (fee: {})", net_amount, fee);
+    ctx.accounts.pool.leave();
     Ok(())
-}
\ No newline at end of file
+}
+
+#[event]
+pub struct WithdrawalLimitHit {
+    pub pool: Pubkey,
+    pub amount: u64,
+    pub window_withdrawn: u64,
+    pub slot: u64,
+}
+
+/// Leaf record for `NullifierTree`: `nullifier` was appended at `index` and the leaf at
+/// `low_index` now links to it. Indexers replay these to rebuild the tree off chain.
+#[event]
+pub struct NullifierInserted {
+    pub pool: Pubkey,
+    pub tree: Pubkey,
+    pub nullifier: [u8; 32],
+    pub index: u64,
+    pub low_index: u64,
+}
@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::state::ProofBuffer;
+
+#[derive(Accounts)]
+pub struct WriteProofChunk<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"proof_buffer",
+            proof_buffer.pool.as_ref(),
+            owner.key().as_ref(),
+            proof_buffer.nullifier.as_ref()
+        ],
+        bump = proof_buffer.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub proof_buffer: Account<'info, ProofBuffer>,
+    pub owner: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<WriteProofChunk>, offset: u32, data: Vec<u8>) -> Result<()> {
+    ctx.accounts.proof_buffer.write_chunk(offset, &data)
+}
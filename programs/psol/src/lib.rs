@@ -1,13 +1,26 @@
+// `#[program]` generates a CPI client function per instruction at the crate root, with the
+// instruction's full argument list, so the lint cannot be scoped to those functions.
+#![allow(clippy::too_many_arguments)]
+
 use anchor_lang::prelude::*;
 
 pub mod crypto;
 pub mod error;
+pub mod events;
+pub mod guard;
 pub mod instructions;
+pub mod merkle;
 pub mod state;
+#[cfg(not(target_os = "solana"))]
+pub mod stealth;
+#[cfg(not(target_os = "solana"))]
+pub mod viewing;
 
 pub use instructions::*;
 pub use state::*;
 pub use error::ErrorCode;
+pub use events::*;
+pub use merkle::NonMembershipProof;
 
 // keep your current program id here
 declare_id!("2dJdyxoGmAoJLsZh7h8ma8xeyoaj7uiHFgrsgUAQMojv");
@@ -16,15 +29,43 @@ declare_id!("2dJdyxoGmAoJLsZh7h8ma8xeyoaj7uiHFgrsgUAQMojv");
 pub mod psol {
     use super::*;
 
-    pub fn initialize_pool(ctx: Context<InitializePool>, fee_bps: u16) -> Result<()> {
-        instructions::initialize_pool::handler(ctx, fee_bps)
+    pub fn initialize_pool(
+        ctx: Context<InitializePool>,
+        fee_bps: u16,
+        emergency_delay_slots: u64,
+        storage_backend: StorageBackend,
+    ) -> Result<()> {
+        instructions::initialize_pool::handler(ctx, fee_bps, emergency_delay_slots, storage_backend)
     }
 
     pub fn init_privacy_account(
         ctx: Context<InitPrivacyAccount>,
-        encryption_key: [u8; 32],
+        spend_key: [u8; 32],
+        view_key: [u8; 32],
+    ) -> Result<()> {
+        instructions::init_privacy_account::handler(ctx, spend_key, view_key)
+    }
+
+    pub fn rotate_encryption_key(
+        ctx: Context<RotateEncryptionKey>,
+        new_view_key: [u8; 32],
+        new_encrypted_balance: [u8; 64],
+        proof: Vec<u8>,
     ) -> Result<()> {
-        instructions::init_privacy_account::handler(ctx, encryption_key)
+        instructions::rotate_encryption_key::handler(
+            ctx,
+            new_view_key,
+            new_encrypted_balance,
+            proof,
+        )
+    }
+
+    pub fn rotate_spend_key(
+        ctx: Context<RotateSpendKey>,
+        new_spend_key: [u8; 32],
+        proof: Vec<u8>,
+    ) -> Result<()> {
+        instructions::rotate_spend_key::handler(ctx, new_spend_key, proof)
     }
 
     pub fn deposit_private(
@@ -32,8 +73,28 @@ pub mod psol {
         amount: u64,
         encrypted_amount: [u8; 64],
         proof: Vec<u8>,
+        memo: Option<Vec<u8>>,
+    ) -> Result<()> {
+        instructions::deposit_private::handler(ctx, amount, encrypted_amount, proof, memo)
+    }
+
+    pub fn deposit_for(
+        ctx: Context<DepositFor>,
+        amount: u64,
+        commitment: [u8; 32],
+        encrypted_amount: [u8; 64],
+        proof: Vec<u8>,
+        memo: Option<Vec<u8>>,
     ) -> Result<()> {
-        instructions::deposit_private::handler(ctx, amount, encrypted_amount, proof)
+        instructions::deposit_for::handler(ctx, amount, commitment, encrypted_amount, proof, memo)
+    }
+
+    pub fn claim_pending_deposits(ctx: Context<ClaimPendingDeposits>) -> Result<()> {
+        instructions::claim_pending_deposits::handler(ctx)
+    }
+
+    pub fn deposit_rewards(ctx: Context<DepositRewards>, amount: u64) -> Result<()> {
+        instructions::deposit_rewards::handler(ctx, amount)
     }
 
     pub fn withdraw_private(
@@ -41,27 +102,198 @@ pub mod psol {
         amount: u64,
         nullifier: [u8; 32],
         proof: Vec<u8>,
+        nullifier_proof: Option<NonMembershipProof>,
     ) -> Result<()> {
-        instructions::withdraw_private::handler(ctx, amount, nullifier, proof)
+        instructions::withdraw_private::handler(ctx, amount, nullifier, proof, nullifier_proof)
+    }
+
+    pub fn batch_deposit(ctx: Context<BatchDeposit>, items: Vec<DepositItem>) -> Result<()> {
+        instructions::batch_deposit::handler(ctx, items)
+    }
+
+    pub fn batch_withdraw<'info>(
+        ctx: Context<'_, '_, '_, 'info, BatchWithdraw<'info>>,
+        items: Vec<WithdrawItem>,
+    ) -> Result<()> {
+        instructions::batch_withdraw::handler(ctx, items)
+    }
+
+    pub fn aggregate_withdraw<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AggregateWithdraw<'info>>,
+        items: Vec<AggregatedWithdrawal>,
+        aggregate_proof: Vec<u8>,
+    ) -> Result<()> {
+        instructions::aggregate_withdraw::handler(ctx, items, aggregate_proof)
+    }
+
+    pub fn init_proof_buffer(
+        ctx: Context<InitProofBuffer>,
+        nullifier: [u8; 32],
+        proof_len: u32,
+        public_inputs: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::init_proof_buffer::handler(ctx, nullifier, proof_len, public_inputs)
+    }
+
+    pub fn write_proof_chunk(
+        ctx: Context<WriteProofChunk>,
+        offset: u32,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        instructions::write_proof_chunk::handler(ctx, offset, data)
+    }
+
+    pub fn verify_proof_buffer(ctx: Context<VerifyProofBuffer>) -> Result<()> {
+        instructions::verify_proof_buffer::handler(ctx)
+    }
+
+    pub fn close_proof_buffer(ctx: Context<CloseProofBuffer>) -> Result<()> {
+        instructions::close_proof_buffer::handler(ctx)
+    }
+
+    pub fn archive_nullifier(
+        ctx: Context<ArchiveNullifier>,
+        archive_proof: NonMembershipProof,
+    ) -> Result<()> {
+        instructions::archive_nullifier::handler(ctx, archive_proof)
+    }
+
+    pub fn close_privacy_account(ctx: Context<ClosePrivacyAccount>, proof: Vec<u8>) -> Result<()> {
+        instructions::close_privacy_account::handler(ctx, proof)
     }
 
     pub fn transfer_private(
         ctx: Context<TransferPrivate>,
         encrypted_amount: [u8; 64],
         proof: Vec<u8>,
+        memo: Option<Vec<u8>>,
+        auditor_amount: Option<[u8; 64]>,
     ) -> Result<()> {
-        instructions::transfer_private::handler(ctx, encrypted_amount, proof)
+        instructions::transfer_private::handler(ctx, encrypted_amount, proof, memo, auditor_amount)
+    }
+
+    pub fn emergency_withdraw(ctx: Context<EmergencyWithdraw>) -> Result<()> {
+        instructions::emergency_withdraw::handler(ctx)
     }
 
-    pub fn admin_pause(ctx: Context<AdminPause>) -> Result<()> {
-        instructions::admin_pause::handler(ctx)
+    #[allow(clippy::too_many_arguments)]
+    pub fn transfer_stealth(
+        ctx: Context<TransferStealth>,
+        one_time_address: Pubkey,
+        ephemeral_pubkey: [u8; 32],
+        view_tag: u8,
+        view_key: [u8; 32],
+        encrypted_amount: [u8; 64],
+        proof: Vec<u8>,
+        memo: Option<Vec<u8>>,
+        auditor_amount: Option<[u8; 64]>,
+    ) -> Result<()> {
+        instructions::transfer_stealth::handler(
+            ctx,
+            one_time_address,
+            ephemeral_pubkey,
+            view_tag,
+            view_key,
+            encrypted_amount,
+            proof,
+            memo,
+            auditor_amount,
+        )
     }
 
-    pub fn admin_unpause(ctx: Context<AdminUnpause>) -> Result<()> {
-        instructions::admin_unpause::handler(ctx)
+    pub fn admin_pause(ctx: Context<AdminPause>, mask: u8) -> Result<()> {
+        instructions::admin_pause::handler(ctx, mask)
+    }
+
+    pub fn admin_unpause(ctx: Context<AdminUnpause>, mask: u8) -> Result<()> {
+        instructions::admin_unpause::handler(ctx, mask)
     }
 
     pub fn admin_set_fees(ctx: Context<AdminSetFees>, fee_bps: u16) -> Result<()> {
         instructions::admin_set_fees::handler(ctx, fee_bps)
     }
+
+    pub fn register_auditor_key(
+        ctx: Context<RegisterAuditorKey>,
+        auditor_key: [u8; 32],
+    ) -> Result<()> {
+        instructions::register_auditor_key::handler(ctx, auditor_key)
+    }
+
+    pub fn admin_set_call_policy(ctx: Context<AdminSetCallPolicy>, mask: u8) -> Result<()> {
+        instructions::admin_set_call_policy::handler(ctx, mask)
+    }
+
+    pub fn init_caller_allowlist(ctx: Context<InitCallerAllowlist>) -> Result<()> {
+        instructions::init_caller_allowlist::handler(ctx)
+    }
+
+    pub fn update_caller_allowlist(
+        ctx: Context<UpdateCallerAllowlist>,
+        program: Pubkey,
+        approved: bool,
+    ) -> Result<()> {
+        instructions::update_caller_allowlist::handler(ctx, program, approved)
+    }
+
+    pub fn begin_pool_migration(
+        ctx: Context<BeginPoolMigration>,
+        storage_backend: StorageBackend,
+    ) -> Result<()> {
+        instructions::begin_pool_migration::handler(ctx, storage_backend)
+    }
+
+    pub fn migrate_pool<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MigratePool<'info>>,
+        nullifier_proofs: Vec<NonMembershipProof>,
+    ) -> Result<()> {
+        instructions::migrate_pool::handler(ctx, nullifier_proofs)
+    }
+
+    pub fn admin_set_limits(
+        ctx: Context<AdminSetLimits>,
+        max_withdrawal: u64,
+        window_slots: u64,
+        window_limit: u64,
+    ) -> Result<()> {
+        instructions::admin_set_limits::handler(ctx, max_withdrawal, window_slots, window_limit)
+    }
+
+    pub fn upgrade_account(ctx: Context<UpgradeAccount>) -> Result<()> {
+        instructions::upgrade_account::handler(ctx)
+    }
+
+    pub fn init_stake_strategy(
+        ctx: Context<InitStakeStrategy>,
+        target_bps: u16,
+        min_buffer: u64,
+        rewards_authority: Pubkey,
+    ) -> Result<()> {
+        instructions::init_stake_strategy::handler(ctx, target_bps, min_buffer, rewards_authority)
+    }
+
+    pub fn set_stake_strategy(
+        ctx: Context<SetStakeStrategy>,
+        target_bps: u16,
+        min_buffer: u64,
+        rewards_authority: Pubkey,
+    ) -> Result<()> {
+        instructions::set_stake_strategy::handler(ctx, target_bps, min_buffer, rewards_authority)
+    }
+
+    pub fn rebalance_stake(ctx: Context<RebalanceStake>) -> Result<()> {
+        instructions::rebalance_stake::handler(ctx)
+    }
+
+    pub fn settle_stake(ctx: Context<SettleStake>) -> Result<()> {
+        instructions::settle_stake::handler(ctx)
+    }
+
+    pub fn harvest_stake_rewards(ctx: Context<HarvestStakeRewards>) -> Result<()> {
+        instructions::harvest_stake_rewards::handler(ctx)
+    }
+
+    pub fn claim_stake_rewards(ctx: Context<ClaimStakeRewards>) -> Result<u64> {
+        instructions::claim_stake_rewards::handler(ctx)
+    }
 }
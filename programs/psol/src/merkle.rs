@@ -0,0 +1,375 @@
+//! Indexed and concurrent Merkle tree primitives.
+//!
+//! Leaves form a linked list sorted by value, so non-membership of `v` is proven by
+//! the "low leaf" whose value is below `v` and whose successor is above it (or is the
+//! end of the list). Hashes are SHA-256; values compare as big-endian integers and the
+//! all-zero value is reserved for the sentinel leaf at index 0.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+use crate::error::ErrorCode;
+
+pub fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    hashv(&[left, right]).to_bytes()
+}
+
+/// Root of an empty subtree at each level, `zeros[0]` being the empty leaf.
+pub fn zero_hashes<const DEPTH: usize>() -> [[u8; 32]; DEPTH] {
+    let mut zeros = [[0u8; 32]; DEPTH];
+    for level in 1..DEPTH {
+        zeros[level] = hash_pair(&zeros[level - 1], &zeros[level - 1]);
+    }
+    zeros
+}
+
+/// Hashes `leaf` at `index` up `path`, returning the node at every level followed by the root.
+pub fn path_nodes(leaf: [u8; 32], index: u64, path: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    let mut nodes = Vec::with_capacity(path.len() + 1);
+    let mut node = leaf;
+    for (level, sibling) in path.iter().enumerate() {
+        nodes.push(node);
+        node = if (index >> level) & 1 == 0 {
+            hash_pair(&node, sibling)
+        } else {
+            hash_pair(sibling, &node)
+        };
+    }
+    nodes.push(node);
+    nodes
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct IndexedLeaf {
+    pub value: [u8; 32],
+    pub next_index: u64,
+    /// Successor value; all zero when this is the largest leaf.
+    pub next_value: [u8; 32],
+}
+
+impl IndexedLeaf {
+    pub fn hash(&self) -> [u8; 32] {
+        hashv(&[&self.value, &self.next_index.to_le_bytes(), &self.next_value]).to_bytes()
+    }
+
+    /// True when `value` would sit directly after this leaf, i.e. it is not in the tree.
+    pub fn is_low_leaf_for(&self, value: &[u8; 32]) -> bool {
+        self.value < *value && (self.next_value == [0u8; 32] || *value < self.next_value)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct NonMembershipProof {
+    /// Root the proof was built against.
+    pub root: [u8; 32],
+    pub low_leaf: IndexedLeaf,
+    pub low_index: u64,
+    pub path: Vec<[u8; 32]>,
+}
+
+impl NonMembershipProof {
+    /// Checks the low leaf brackets `value` and hashes up to `self.root`.
+    pub fn verify(&self, depth: usize, value: &[u8; 32]) -> Result<()> {
+        require!(self.path.len() == depth, ErrorCode::InvalidMerkleProof);
+        require!(
+            self.low_leaf.is_low_leaf_for(value),
+            ErrorCode::NullifierAlreadyUsed
+        );
+        let nodes = path_nodes(self.low_leaf.hash(), self.low_index, &self.path);
+        require!(nodes[depth] == self.root, ErrorCode::InvalidMerkleProof);
+        Ok(())
+    }
+}
+
+/* ============================================================
+   Concurrent Merkle tree (SPL account-compression model)
+   ============================================================ */
+
+pub const TREE_DEPTH: usize = 20;
+pub const CHANGELOG_SIZE: usize = 8;
+
+/// Nodes along the path of one leaf change, `path[0]` being the new leaf.
+#[zero_copy]
+pub struct ChangeLog {
+    pub root: [u8; 32],
+    pub path: [[u8; 32]; TREE_DEPTH],
+    pub index: u64,
+}
+
+/// Append/replace tree that accepts proofs against any of the last `CHANGELOG_SIZE`
+/// roots. Proofs against an older root are fast-forwarded through the changelog, so
+/// concurrent writers only conflict when they touch the same leaf. Leaves themselves
+/// are not stored; indexers rebuild them from emitted events.
+#[zero_copy]
+pub struct ConcurrentMerkleTree {
+    pub sequence_number: u64,
+    pub active_index: u64,
+    pub buffer_size: u64,
+    pub next_index: u64,
+    /// Rightmost left-hand node at each level, used to append without a path.
+    pub filled_subtrees: [[u8; 32]; TREE_DEPTH],
+    pub change_logs: [ChangeLog; CHANGELOG_SIZE],
+}
+
+impl ConcurrentMerkleTree {
+    pub fn initialize(&mut self) {
+        let zeros = zero_hashes::<TREE_DEPTH>();
+        self.sequence_number = 0;
+        self.active_index = 0;
+        self.buffer_size = 1;
+        self.next_index = 0;
+        self.filled_subtrees = zeros;
+        self.change_logs[0] = ChangeLog {
+            root: hash_pair(&zeros[TREE_DEPTH - 1], &zeros[TREE_DEPTH - 1]),
+            path: zeros,
+            index: 0,
+        };
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        self.change_logs[self.active_index as usize].root
+    }
+
+    fn find_root(&self, root: &[u8; 32]) -> Option<usize> {
+        (0..self.buffer_size as usize)
+            .map(|back| (self.active_index as usize + CHANGELOG_SIZE - back) % CHANGELOG_SIZE)
+            .find(|&i| self.change_logs[i].root == *root)
+    }
+
+    /// Verifies `leaf` at `index` under `root` and rewrites `proof` to the current root.
+    /// Fails if the leaf itself changed after `root`.
+    fn fast_forward(
+        &self,
+        root: &[u8; 32],
+        leaf: [u8; 32],
+        index: u64,
+        proof: &mut [[u8; 32]; TREE_DEPTH],
+    ) -> Result<()> {
+        require!(index < self.next_index, ErrorCode::InvalidMerkleProof);
+        let start = self.find_root(root).ok_or(ErrorCode::StaleMerkleRoot)?;
+        require!(
+            path_nodes(leaf, index, proof)[TREE_DEPTH] == *root,
+            ErrorCode::InvalidMerkleProof
+        );
+
+        let mut i = start;
+        while i != self.active_index as usize {
+            i = (i + 1) % CHANGELOG_SIZE;
+            let change = &self.change_logs[i];
+            require!(change.index != index, ErrorCode::MerkleLeafChanged);
+            let level = (63 - (index ^ change.index).leading_zeros()) as usize;
+            proof[level] = change.path[level];
+        }
+        Ok(())
+    }
+
+    fn push_change(&mut self, nodes: &[[u8; 32]], index: u64) {
+        let mut path = [[0u8; 32]; TREE_DEPTH];
+        path.copy_from_slice(&nodes[..TREE_DEPTH]);
+        self.active_index = (self.active_index + 1) % CHANGELOG_SIZE as u64;
+        self.buffer_size = (self.buffer_size + 1).min(CHANGELOG_SIZE as u64);
+        self.sequence_number += 1;
+        self.change_logs[self.active_index as usize] = ChangeLog {
+            root: nodes[TREE_DEPTH],
+            path,
+            index,
+        };
+    }
+
+    /// Checks that `leaf` is currently at `index`, given a proof against a recent root.
+    pub fn prove_leaf(
+        &self,
+        root: &[u8; 32],
+        leaf: [u8; 32],
+        index: u64,
+        proof: &[[u8; 32]],
+    ) -> Result<()> {
+        let mut proof: [[u8; 32]; TREE_DEPTH] =
+            proof.try_into().map_err(|_| ErrorCode::InvalidMerkleProof)?;
+        self.fast_forward(root, leaf, index, &mut proof)
+    }
+
+    pub fn set_leaf(
+        &mut self,
+        root: &[u8; 32],
+        prev_leaf: [u8; 32],
+        new_leaf: [u8; 32],
+        index: u64,
+        proof: &[[u8; 32]],
+    ) -> Result<()> {
+        let mut proof: [[u8; 32]; TREE_DEPTH] =
+            proof.try_into().map_err(|_| ErrorCode::InvalidMerkleProof)?;
+        self.fast_forward(root, prev_leaf, index, &mut proof)?;
+
+        let nodes = path_nodes(new_leaf, index, &proof);
+        for (level, node) in nodes.iter().take(TREE_DEPTH).enumerate() {
+            let frontier = self.next_index >> level;
+            if frontier & 1 == 1 && index >> level == frontier - 1 {
+                self.filled_subtrees[level] = *node;
+            }
+        }
+        self.push_change(&nodes, index);
+        Ok(())
+    }
+
+    /// Appends `leaf` and returns its index.
+    pub fn append(&mut self, leaf: [u8; 32]) -> Result<u64> {
+        let index = self.next_index;
+        require!(
+            index < (1u64 << TREE_DEPTH),
+            ErrorCode::CommitmentRegistryFull
+        );
+        let zeros = zero_hashes::<TREE_DEPTH>();
+        let mut proof = [[0u8; 32]; TREE_DEPTH];
+        for level in 0..TREE_DEPTH {
+            proof[level] = if (index >> level) & 1 == 1 {
+                self.filled_subtrees[level]
+            } else {
+                zeros[level]
+            };
+        }
+
+        let nodes = path_nodes(leaf, index, &proof);
+        for (level, node) in nodes.iter().take(TREE_DEPTH).enumerate() {
+            if (index >> level) & 1 == 0 {
+                self.filled_subtrees[level] = *node;
+            }
+        }
+        self.push_change(&nodes, index);
+        self.next_index += 1;
+        Ok(index)
+    }
+
+    /// Indexed-tree insertion: relinks the low leaf to `value` and appends `value`'s leaf.
+    pub fn insert_indexed(&mut self, value: [u8; 32], proof: &NonMembershipProof) -> Result<u64> {
+        require!(value != [0u8; 32], ErrorCode::InvalidNullifier);
+        let low = proof.low_leaf;
+        require!(low.is_low_leaf_for(&value), ErrorCode::NullifierAlreadyUsed);
+
+        let new_index = self.next_index;
+        let updated_low = IndexedLeaf {
+            value: low.value,
+            next_index: new_index,
+            next_value: value,
+        };
+        self.set_leaf(
+            &proof.root,
+            low.hash(),
+            updated_low.hash(),
+            proof.low_index,
+            &proof.path,
+        )?;
+        self.append(
+            IndexedLeaf {
+                value,
+                next_index: low.next_index,
+                next_value: low.next_value,
+            }
+            .hash(),
+        )
+    }
+}
+
+/// Off-chain mirror of a plain append tree, used by clients to rebuild proofs from events.
+#[cfg(not(target_os = "solana"))]
+#[derive(Clone, Debug)]
+pub struct MerkleTreeMirror {
+    pub depth: usize,
+    pub leaves: Vec<[u8; 32]>,
+}
+
+#[cfg(not(target_os = "solana"))]
+impl MerkleTreeMirror {
+    pub fn new(depth: usize) -> Self {
+        Self {
+            depth,
+            leaves: Vec::new(),
+        }
+    }
+
+    fn level_nodes(&self) -> Vec<Vec<[u8; 32]>> {
+        let mut zero = [0u8; 32];
+        let mut levels = vec![self.leaves.clone()];
+        for level in 0..self.depth {
+            let below = &levels[level];
+            let mut above: Vec<[u8; 32]> = below
+                .chunks(2)
+                .map(|pair| hash_pair(&pair[0], pair.get(1).unwrap_or(&zero)))
+                .collect();
+            zero = hash_pair(&zero, &zero);
+            if above.is_empty() {
+                above.push(zero);
+            }
+            levels.push(above);
+        }
+        levels
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        self.level_nodes()[self.depth][0]
+    }
+
+    pub fn path(&self, index: u64) -> Vec<[u8; 32]> {
+        let levels = self.level_nodes();
+        let mut zero = [0u8; 32];
+        let mut path = Vec::with_capacity(self.depth);
+        for (level, nodes) in levels.iter().take(self.depth).enumerate() {
+            let sibling = ((index >> level) ^ 1) as usize;
+            path.push(*nodes.get(sibling).unwrap_or(&zero));
+            zero = hash_pair(&zero, &zero);
+        }
+        path
+    }
+}
+
+/// Off-chain mirror of an indexed tree, used by clients and crankers to build proofs.
+#[cfg(not(target_os = "solana"))]
+#[derive(Clone, Debug)]
+pub struct IndexedMerkleTree {
+    pub leaves: Vec<IndexedLeaf>,
+    tree: MerkleTreeMirror,
+}
+
+#[cfg(not(target_os = "solana"))]
+impl IndexedMerkleTree {
+    /// A tree holding only the sentinel leaf, matching a freshly initialised on-chain tree.
+    pub fn new(depth: usize) -> Self {
+        let sentinel = IndexedLeaf::default();
+        let mut tree = MerkleTreeMirror::new(depth);
+        tree.leaves.push(sentinel.hash());
+        Self {
+            leaves: vec![sentinel],
+            tree,
+        }
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        self.tree.root()
+    }
+
+    pub fn non_membership_proof(&self, value: &[u8; 32]) -> Option<NonMembershipProof> {
+        let low_index = self.leaves.iter().position(|l| l.is_low_leaf_for(value))?;
+        Some(NonMembershipProof {
+            root: self.root(),
+            low_leaf: self.leaves[low_index],
+            low_index: low_index as u64,
+            path: self.tree.path(low_index as u64),
+        })
+    }
+
+    /// Mirrors an on-chain insertion of `value`.
+    pub fn insert(&mut self, value: [u8; 32]) -> Option<()> {
+        let low_index = self.leaves.iter().position(|l| l.is_low_leaf_for(&value))?;
+        let low = self.leaves[low_index];
+        let new_index = self.leaves.len() as u64;
+        self.leaves.push(IndexedLeaf {
+            value,
+            next_index: low.next_index,
+            next_value: low.next_value,
+        });
+        self.leaves[low_index].next_index = new_index;
+        self.leaves[low_index].next_value = value;
+        self.tree.leaves = self.leaves.iter().map(IndexedLeaf::hash).collect();
+        Some(())
+    }
+}
@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+
+/// Programs allowed to deposit and withdraw through CPI while the pool enforces
+/// `CallPolicy::ApprovedCallersOnly`, at `["caller_allowlist", pool]`. Kept sorted.
+#[account]
+pub struct CallerAllowlist {
+    pub version: u8,
+    pub pool: Pubkey,
+    pub bump: u8,
+    pub programs: Vec<Pubkey>,
+}
+
+impl CallerAllowlist {
+    pub const MAX_PROGRAMS: usize = 16;
+
+    pub const SIZE: usize = 8  // discriminator
+        + 1                    // version
+        + 32                   // pool
+        + 1                    // bump
+        + 4 + 32 * Self::MAX_PROGRAMS; // programs
+
+    pub const VERSION: u8 = 1;
+
+    pub fn contains(&self, program: &Pubkey) -> bool {
+        self.programs.binary_search(program).is_ok()
+    }
+
+    /// Adds `program`; a no-op if it is already listed.
+    pub fn insert(&mut self, program: Pubkey) -> Result<()> {
+        if let Err(index) = self.programs.binary_search(&program) {
+            require!(
+                self.programs.len() < Self::MAX_PROGRAMS,
+                ErrorCode::CallerAllowlistFull
+            );
+            self.programs.insert(index, program);
+        }
+        Ok(())
+    }
+
+    /// Removes `program`; a no-op if it is not listed.
+    pub fn remove(&mut self, program: &Pubkey) {
+        if let Ok(index) = self.programs.binary_search(program) {
+            self.programs.remove(index);
+        }
+    }
+}
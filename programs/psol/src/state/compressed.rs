@@ -0,0 +1,120 @@
+use crate::error::ErrorCode;
+use crate::merkle::{ConcurrentMerkleTree, IndexedLeaf, NonMembershipProof};
+use anchor_lang::prelude::*;
+
+/// Append-only tree of deposit commitments for `StorageBackend::Compressed` pools.
+///
+/// Like SPL account-compression trees, the client allocates it (zeroed, owned by this
+/// program) in the same transaction as `initialize_pool`.
+#[account(zero_copy)]
+pub struct CommitmentTree {
+    pub pool: Pubkey,
+    pub tree: ConcurrentMerkleTree,
+}
+
+impl CommitmentTree {
+    pub const SIZE: usize = 8 + std::mem::size_of::<CommitmentTree>();
+
+    pub fn initialize(&mut self, pool: Pubkey) {
+        self.pool = pool;
+        self.tree.initialize();
+    }
+
+    pub fn append(&mut self, pool: &Pubkey, commitment: [u8; 32]) -> Result<u64> {
+        require_keys_eq!(self.pool, *pool, ErrorCode::InvalidRegistry);
+        self.tree.append(commitment)
+    }
+}
+
+/// Indexed tree of spent nullifiers for `StorageBackend::Compressed` pools.
+///
+/// Replaces the per-nullifier PDAs: a withdrawal inserts its nullifier with a
+/// non-membership proof against any recent root, so no rent is locked per spend.
+#[account(zero_copy)]
+pub struct NullifierTree {
+    pub pool: Pubkey,
+    pub tree: ConcurrentMerkleTree,
+}
+
+impl NullifierTree {
+    pub const SIZE: usize = 8 + std::mem::size_of::<NullifierTree>();
+
+    /// Seeds the tree with the all-zero sentinel leaf at index 0.
+    pub fn initialize(&mut self, pool: Pubkey) -> Result<()> {
+        self.pool = pool;
+        self.tree.initialize();
+        self.tree.append(IndexedLeaf::default().hash())?;
+        Ok(())
+    }
+
+    /// Marks `nullifier` spent, failing if it is already present. Returns the new leaf index.
+    pub fn insert(
+        &mut self,
+        pool: &Pubkey,
+        nullifier: [u8; 32],
+        proof: &NonMembershipProof,
+    ) -> Result<u64> {
+        require_keys_eq!(self.pool, *pool, ErrorCode::InvalidRegistry);
+        self.tree.insert_indexed(nullifier, proof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merkle::{IndexedMerkleTree, MerkleTreeMirror, TREE_DEPTH};
+    use bytemuck::Zeroable;
+
+    #[test]
+    fn compressed_trees_accept_concurrent_proofs() {
+        let pool = Pubkey::new_unique();
+
+        let mut commitments = CommitmentTree::zeroed();
+        commitments.initialize(pool);
+        let mut commitment_mirror = MerkleTreeMirror::new(TREE_DEPTH);
+        assert_eq!(commitments.tree.root(), commitment_mirror.root());
+        for i in 1..=5u8 {
+            let index = commitments.append(&pool, [i; 32]).unwrap();
+            assert_eq!(index, u64::from(i) - 1);
+            commitment_mirror.leaves.push([i; 32]);
+            assert_eq!(commitments.tree.root(), commitment_mirror.root());
+        }
+        // A leaf proven against an older root is fast-forwarded to the current one.
+        let old_root = commitment_mirror.root();
+        let old_path = commitment_mirror.path(1);
+        commitments.append(&pool, [6u8; 32]).unwrap();
+        commitments.tree.prove_leaf(&old_root, [2u8; 32], 1, &old_path).unwrap();
+        assert!(commitments.append(&Pubkey::default(), [7u8; 32]).is_err());
+
+        let mut nullifiers = NullifierTree::zeroed();
+        nullifiers.initialize(pool).unwrap();
+        let mut mirror = IndexedMerkleTree::new(TREE_DEPTH);
+        assert_eq!(nullifiers.tree.root(), mirror.root());
+        for n in [[50u8; 32], [10u8; 32], [200u8; 32]] {
+            nullifiers
+                .insert(&pool, n, &mirror.non_membership_proof(&n).unwrap())
+                .unwrap();
+            mirror.insert(n).unwrap();
+            assert_eq!(nullifiers.tree.root(), mirror.root());
+        }
+
+        // Two withdrawals built against the same root land in either order when their
+        // low leaves differ.
+        let (a, b) = ([20u8; 32], [100u8; 32]);
+        let proof_a = mirror.non_membership_proof(&a).unwrap();
+        let proof_b = mirror.non_membership_proof(&b).unwrap();
+        nullifiers.insert(&pool, b, &proof_b).unwrap();
+        nullifiers.insert(&pool, a, &proof_a).unwrap();
+        mirror.insert(b).unwrap();
+        mirror.insert(a).unwrap();
+        assert_eq!(nullifiers.tree.root(), mirror.root());
+
+        // Sharing a low leaf is a conflict, and a spent nullifier cannot be inserted again.
+        let (c, d) = ([30u8; 32], [40u8; 32]);
+        let proof_c = mirror.non_membership_proof(&c).unwrap();
+        let proof_d = mirror.non_membership_proof(&d).unwrap();
+        nullifiers.insert(&pool, c, &proof_c).unwrap();
+        assert!(nullifiers.insert(&pool, d, &proof_d).is_err());
+        assert!(nullifiers.insert(&pool, c, &proof_c).is_err());
+    }
+}
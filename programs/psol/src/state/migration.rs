@@ -0,0 +1,108 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use crate::error::ErrorCode;
+use crate::state::StorageBackend;
+
+/// Progress of moving a legacy pool's flat registries into its storage backend, at
+/// `["migration", pool]`. Closed to the admin when migration completes.
+///
+/// Unversioned and without reserved space: it exists only while a migration runs, so a
+/// layout change only needs running migrations finished before the upgrade.
+#[account]
+pub struct PoolMigration {
+    pub pool: Pubkey,
+    pub target: StorageBackend,
+    /// `CommitmentRegistry` entries appended to the `CommitmentTree` so far.
+    pub commitments_migrated: u64,
+    /// `NullifierRegistry` entries recorded in the new backend so far.
+    pub nullifiers_migrated: u64,
+    pub bump: u8,
+}
+
+impl PoolMigration {
+    /// Most registry entries moved per `migrate_pool` call.
+    pub const CHUNK: usize = 16;
+
+    pub const SIZE: usize = 8  // discriminator
+        + 32                   // pool
+        + 1                    // target
+        + 8                    // commitments_migrated
+        + 8                    // nullifiers_migrated
+        + 1;                   // bump
+}
+
+/// Reads up to `max` entries from `start` of a legacy flat registry (`CommitmentRegistry`
+/// or `NullifierRegistry`) straight from account data, so the 32 KiB array is never
+/// deserialised. Returns the registry's entry count and the entries read.
+pub fn read_legacy_registry<T: Discriminator>(
+    info: &AccountInfo,
+    pool: &Pubkey,
+    start: u64,
+    max: usize,
+) -> Result<(u64, Vec<[u8; 32]>)> {
+    const HEADER: usize = 8 + 32 + 8;
+    require_keys_eq!(*info.owner, crate::ID, ErrorCode::InvalidRegistry);
+    let data = info.try_borrow_data()?;
+    require!(
+        data.len() >= HEADER && data[..8] == T::DISCRIMINATOR && data[8..40] == pool.to_bytes(),
+        ErrorCode::InvalidRegistry
+    );
+
+    let mut count_bytes = [0u8; 8];
+    count_bytes.copy_from_slice(&data[40..HEADER]);
+    let count = u64::from_le_bytes(count_bytes);
+    let end = count.min(start.saturating_add(max as u64));
+    require!(
+        data.len() >= HEADER + 32 * end as usize,
+        ErrorCode::InvalidRegistry
+    );
+
+    let entries = (start..end)
+        .map(|i| {
+            let offset = HEADER + 32 * i as usize;
+            let mut entry = [0u8; 32];
+            entry.copy_from_slice(&data[offset..offset + 32]);
+            entry
+        })
+        .collect();
+    Ok((count, entries))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{CommitmentRegistry, NullifierRegistry};
+
+    #[test]
+    fn legacy_registries_read_in_chunks() {
+        let pool = Pubkey::new_unique();
+        let mut data = CommitmentRegistry::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(pool.as_ref());
+        data.extend_from_slice(&5u64.to_le_bytes());
+        for i in 0..CommitmentRegistry::MAX {
+            data.extend_from_slice(&[i as u8; 32]);
+        }
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let info = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &crate::ID,
+            false,
+            0,
+        );
+
+        let (count, first) =
+            read_legacy_registry::<CommitmentRegistry>(&info, &pool, 0, 3).unwrap();
+        assert_eq!((count, first), (5, vec![[0u8; 32], [1u8; 32], [2u8; 32]]));
+        let (_, rest) = read_legacy_registry::<CommitmentRegistry>(&info, &pool, 3, 16).unwrap();
+        assert_eq!(rest, vec![[3u8; 32], [4u8; 32]]);
+
+        // Wrong account type or pool is rejected.
+        assert!(read_legacy_registry::<NullifierRegistry>(&info, &pool, 0, 3).is_err());
+        assert!(read_legacy_registry::<CommitmentRegistry>(&info, &key, 0, 3).is_err());
+    }
+}
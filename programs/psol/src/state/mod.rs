@@ -1,17 +1,27 @@
-pub mod initialize_pool;
-pub mod init_privacy_account;
-pub mod deposit_private;
-pub mod withdraw_private;
-pub mod transfer_private;
-pub mod admin_pause;
-pub mod admin_unpause;
-pub mod admin_set_fees;
+pub mod caller_allowlist;
+pub mod commitment_registry;
+pub mod compressed;
+pub mod migration;
+pub mod nullifier_archive;
+pub mod nullifier_registry;
+pub mod nullifier_set;
+pub mod pending_deposits;
+pub mod pool;
+pub mod privacy_accounts;
+pub mod proof_buffer;
+pub mod stake_strategy;
+pub mod versioning;
 
-pub use initialize_pool::*;
-pub use init_privacy_account::*;
-pub use deposit_private::*;
-pub use withdraw_private::*;
-pub use transfer_private::*;
-pub use admin_pause::*;
-pub use admin_unpause::*;
-pub use admin_set_fees::*;
+pub use caller_allowlist::*;
+pub use commitment_registry::*;
+pub use compressed::*;
+pub use migration::*;
+pub use nullifier_archive::*;
+pub use nullifier_registry::*;
+pub use nullifier_set::*;
+pub use pending_deposits::*;
+pub use pool::*;
+pub use privacy_accounts::*;
+pub use proof_buffer::*;
+pub use stake_strategy::*;
+pub use versioning::*;
@@ -0,0 +1,250 @@
+use anchor_lang::prelude::*;
+use crate::error::ErrorCode;
+use crate::merkle::{self, IndexedLeaf, NonMembershipProof};
+
+/// One archive insertion: the root it replaced and the nullifier it added.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ArchiveChange {
+    pub root: [u8; 32],
+    pub nullifier: [u8; 32],
+}
+
+/// Indexed Merkle tree of nullifiers whose `NullifierSet` PDA has been closed.
+///
+/// A nullifier lives either in its PDA or in this tree, never neither: archiving
+/// inserts and closes in one instruction, and withdrawals prove non-membership
+/// against a recent root before creating a new PDA.
+#[account]
+pub struct NullifierArchive {
+    pub version: u8,
+    pub pool: Pubkey,
+    pub root: [u8; 32],
+    pub next_index: u64,
+    /// Rightmost left-hand node at each level, used to append without a path.
+    pub filled_subtrees: [[u8; 32]; 20],
+    pub bump: u8,
+    /// Ring buffer of the last `ROOT_HISTORY_SIZE` insertions, oldest overwritten first.
+    pub recent_changes: [ArchiveChange; 16],
+    /// Insertions recorded in `recent_changes` since initialization.
+    pub change_count: u64,
+    /// Zeroed space for future fields, so they can be added without a realloc.
+    pub reserved: [u8; 32],
+}
+
+impl NullifierArchive {
+    pub const DEPTH: usize = 20;
+    /// Archive insertions a withdrawal proof may lag behind.
+    pub const ROOT_HISTORY_SIZE: usize = 16;
+    pub const SIZE: usize = 8                   // discriminator
+        + 1                                     // version
+        + 32                                    // pool
+        + 32                                    // root
+        + 8                                     // next_index
+        + (32 * Self::DEPTH)                    // filled_subtrees
+        + 1                                     // bump
+        + (64 * Self::ROOT_HISTORY_SIZE)        // recent_changes
+        + 8                                     // change_count
+        + 32;                                   // reserved
+
+    pub const VERSION: u8 = 1;
+
+    /// Seeds the tree with the all-zero sentinel leaf at index 0.
+    pub fn initialize(&mut self, pool: Pubkey, bump: u8) -> Result<()> {
+        self.version = Self::VERSION;
+        self.pool = pool;
+        self.bump = bump;
+        self.next_index = 0;
+        self.filled_subtrees = [[0u8; 32]; Self::DEPTH];
+        self.recent_changes = [ArchiveChange::default(); Self::ROOT_HISTORY_SIZE];
+        self.change_count = 0;
+        self.append(IndexedLeaf::default().hash())
+    }
+
+    /// Only the sentinel is present, so nothing can have been archived.
+    pub fn is_empty(&self) -> bool {
+        self.next_index <= 1
+    }
+
+    pub fn check_not_archived(
+        &self,
+        nullifier: &[u8; 32],
+        proof: Option<&NonMembershipProof>,
+    ) -> Result<()> {
+        require!(*nullifier != [0u8; 32], ErrorCode::InvalidNullifier);
+        if self.is_empty() {
+            return Ok(());
+        }
+        let proof = proof.ok_or(ErrorCode::InvalidMerkleProof)?;
+        if proof.root != self.root {
+            self.check_root_still_valid(&proof.root, nullifier)?;
+        }
+        proof.verify(Self::DEPTH, nullifier)
+    }
+
+    /// A proof against a root replaced by one of the recent insertions still shows
+    /// non-membership, unless that insertion or a later one archived `nullifier` itself.
+    fn check_root_still_valid(&self, root: &[u8; 32], nullifier: &[u8; 32]) -> Result<()> {
+        let recorded = self.change_count.min(Self::ROOT_HISTORY_SIZE as u64) as usize;
+        for back in 1..=recorded {
+            let slot = (self.change_count as usize - back) % Self::ROOT_HISTORY_SIZE;
+            let change = &self.recent_changes[slot];
+            require!(change.nullifier != *nullifier, ErrorCode::StaleMerkleRoot);
+            if change.root == *root {
+                return Ok(());
+            }
+        }
+        err!(ErrorCode::StaleMerkleRoot)
+    }
+
+    /// Inserts `nullifier`, rewriting its low leaf along `proof.path` and appending the new leaf.
+    pub fn insert(&mut self, nullifier: [u8; 32], proof: &NonMembershipProof) -> Result<()> {
+        require!(nullifier != [0u8; 32], ErrorCode::InvalidNullifier);
+        require!(proof.root == self.root, ErrorCode::StaleMerkleRoot);
+        proof.verify(Self::DEPTH, &nullifier)?;
+
+        let slot = self.change_count as usize % Self::ROOT_HISTORY_SIZE;
+        self.recent_changes[slot] = ArchiveChange {
+            root: self.root,
+            nullifier,
+        };
+        self.change_count = self.change_count.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+
+        let new_index = self.next_index;
+        let low = proof.low_leaf;
+        let updated_low = IndexedLeaf {
+            value: low.value,
+            next_index: new_index,
+            next_value: nullifier,
+        };
+        let nodes = merkle::path_nodes(updated_low.hash(), proof.low_index, &proof.path);
+        for (level, node) in nodes.iter().take(Self::DEPTH).enumerate() {
+            let frontier = new_index >> level;
+            if frontier & 1 == 1 && proof.low_index >> level == frontier - 1 {
+                self.filled_subtrees[level] = *node;
+            }
+        }
+        self.root = nodes[Self::DEPTH];
+
+        self.append(
+            IndexedLeaf {
+                value: nullifier,
+                next_index: low.next_index,
+                next_value: low.next_value,
+            }
+            .hash(),
+        )
+    }
+
+    fn append(&mut self, leaf: [u8; 32]) -> Result<()> {
+        require!(
+            self.next_index < (1u64 << Self::DEPTH),
+            ErrorCode::NullifierArchiveFull
+        );
+        let zeros = merkle::zero_hashes::<20>();
+        let mut index = self.next_index;
+        let mut node = leaf;
+        for (level, zero) in zeros.iter().enumerate() {
+            if index & 1 == 0 {
+                self.filled_subtrees[level] = node;
+                node = merkle::hash_pair(&node, zero);
+            } else {
+                node = merkle::hash_pair(&self.filled_subtrees[level], &node);
+            }
+            index >>= 1;
+        }
+        self.root = node;
+        self.next_index = self.next_index.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merkle::IndexedMerkleTree;
+
+    #[test]
+    fn nullifier_archive_tracks_offchain_mirror() {
+        let mut archive = NullifierArchive {
+            version: 0,
+            pool: Pubkey::default(),
+            root: [0u8; 32],
+            next_index: 0,
+            filled_subtrees: [[0u8; 32]; NullifierArchive::DEPTH],
+            bump: 0,
+            recent_changes: [ArchiveChange::default(); NullifierArchive::ROOT_HISTORY_SIZE],
+            change_count: 0,
+            reserved: [0u8; 32],
+        };
+        archive.initialize(Pubkey::default(), 1).unwrap();
+        assert_eq!(archive.version, NullifierArchive::VERSION);
+        assert_eq!(archive.try_to_vec().unwrap().len() + 8, NullifierArchive::SIZE);
+        let mut mirror = IndexedMerkleTree::new(NullifierArchive::DEPTH);
+        assert_eq!(archive.root, mirror.root());
+
+        for n in [[9u8; 32], [3u8; 32], [200u8; 32], [4u8; 32]] {
+            let proof = mirror.non_membership_proof(&n).unwrap();
+            archive.insert(n, &proof).unwrap();
+            mirror.insert(n).unwrap();
+            assert_eq!(archive.root, mirror.root());
+        }
+
+        // Archived nullifiers have no low leaf, fresh ones verify against the live root.
+        assert!(mirror.non_membership_proof(&[3u8; 32]).is_none());
+        let fresh = [5u8; 32];
+        let proof = mirror.non_membership_proof(&fresh).unwrap();
+        archive.check_not_archived(&fresh, Some(&proof)).unwrap();
+        assert!(archive.check_not_archived(&fresh, None).is_err());
+        let stale = IndexedMerkleTree::new(NullifierArchive::DEPTH)
+            .non_membership_proof(&[3u8; 32])
+            .unwrap();
+        assert!(archive.check_not_archived(&[3u8; 32], Some(&stale)).is_err());
+    }
+
+    #[test]
+    fn withdrawals_prove_against_recent_archive_roots() {
+        let mut archive = NullifierArchive {
+            version: 0,
+            pool: Pubkey::default(),
+            root: [0u8; 32],
+            next_index: 0,
+            filled_subtrees: [[0u8; 32]; NullifierArchive::DEPTH],
+            bump: 0,
+            recent_changes: [ArchiveChange::default(); NullifierArchive::ROOT_HISTORY_SIZE],
+            change_count: 0,
+            reserved: [0u8; 32],
+        };
+        archive.initialize(Pubkey::default(), 1).unwrap();
+        let mut mirror = IndexedMerkleTree::new(NullifierArchive::DEPTH);
+        let mut archive_one = |archive: &mut NullifierArchive, n: [u8; 32]| {
+            archive.insert(n, &mirror.non_membership_proof(&n).unwrap()).unwrap();
+            mirror.insert(n).unwrap();
+            mirror.clone()
+        };
+        let before = archive_one(&mut archive, [100u8; 32]);
+
+        // A crank archiving other nullifiers does not invalidate an in-flight proof.
+        let fresh = [50u8; 32];
+        let in_flight = before.non_membership_proof(&fresh).unwrap();
+        let spent = [60u8; 32];
+        let spent_proof = before.non_membership_proof(&spent).unwrap();
+        archive_one(&mut archive, [7u8; 32]);
+        archive_one(&mut archive, spent);
+        archive.check_not_archived(&fresh, Some(&in_flight)).unwrap();
+
+        // A nullifier archived since the proof's root is caught.
+        assert_eq!(
+            archive.check_not_archived(&spent, Some(&spent_proof)),
+            Err(ErrorCode::StaleMerkleRoot.into())
+        );
+
+        // Roots older than the history are rejected.
+        for i in 0..NullifierArchive::ROOT_HISTORY_SIZE as u8 {
+            archive_one(&mut archive, [200 + i % 50; 32]);
+        }
+        assert_eq!(
+            archive.check_not_archived(&fresh, Some(&in_flight)),
+            Err(ErrorCode::StaleMerkleRoot.into())
+        );
+    }
+}
@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+/// Per-nullifier spend record at `["nullifier", pool, nullifier]`.
+///
+/// Its existence blocks a second spend; once old enough it can be folded into the
+/// pool's `NullifierArchive` and closed, refunding rent to `payer`.
+#[account]
+pub struct NullifierSet {
+    pub version: u8,
+    pub pool: Pubkey,
+    pub nullifier: [u8; 32],
+    pub payer: Pubkey,
+    pub slot: u64,
+    pub bump: u8,
+    /// Zeroed space for future fields, so they can be added without a realloc.
+    pub reserved: [u8; 16],
+}
+
+impl NullifierSet {
+    /// Records younger than this cannot be archived, so a recent spend stays visible as a
+    /// PDA while indexers catch up. It does not slow the archive root down: any older
+    /// record can be archived at any time, which is why withdrawals accept proofs against
+    /// any root in `NullifierArchive::recent_changes`.
+    pub const ARCHIVE_MIN_AGE_SLOTS: u64 = 216_000;
+
+    pub const SIZE: usize = 8  // discriminator
+        + 1                    // version
+        + 32                   // pool
+        + 32                   // nullifier
+        + 32                   // payer
+        + 8                    // slot
+        + 1                    // bump
+        + 16;                  // reserved
+
+    pub const VERSION: u8 = 1;
+}
@@ -0,0 +1,107 @@
+use anchor_lang::prelude::*;
+
+use crate::crypto;
+use crate::error::ErrorCode;
+
+/// Deposits made into a privacy account by others with `deposit_for`, at
+/// `["pending_deposits", privacy_account]`. They wait here until the owner folds them in
+/// with `claim_pending_deposits`, so only the owner changes the commitment and ciphertext
+/// that the owner's proofs are bound to.
+#[account]
+pub struct PendingDeposits {
+    pub version: u8,
+    pub privacy_account: Pubkey,
+    pub bump: u8,
+    /// Latest `deposit_for` commitment, which the next one must extend. Starts at the
+    /// privacy account's address and is never reset.
+    pub commitment: [u8; 32],
+    /// Sum of the pending amounts, encrypted to the account's `view_key`.
+    pub encrypted_amount: [u8; 64],
+    pub amount: u64,
+    pub count: u64,
+    /// Zeroed space for future fields, so they can be added without a realloc.
+    pub reserved: [u8; 32],
+}
+
+impl PendingDeposits {
+    pub const SIZE: usize = 8   // discriminator
+        + 1                     // version
+        + 32                    // privacy_account
+        + 1                     // bump
+        + 32                    // commitment
+        + 64                    // encrypted_amount
+        + 8                     // amount
+        + 8                     // count
+        + 32;                   // reserved
+
+    pub const VERSION: u8 = 1;
+
+    /// Head of the commitment chain for `privacy_account`, whether or not its record has
+    /// been created yet.
+    pub fn head(privacy_account: &Pubkey, pending: Option<&PendingDeposits>) -> [u8; 32] {
+        pending.map_or(privacy_account.to_bytes(), |pending| pending.commitment)
+    }
+
+    /// Queues a deposit of `amount` whose commitment extends the chain.
+    pub fn push(
+        &mut self,
+        amount: u64,
+        commitment: [u8; 32],
+        encrypted_amount: &[u8; 64],
+    ) -> Result<()> {
+        require!(
+            commitment == crypto::pedersen_commit(amount, &self.commitment),
+            ErrorCode::CommitmentMismatch
+        );
+        self.encrypted_amount = if self.count == 0 {
+            *encrypted_amount
+        } else {
+            crypto::add_encrypted(&self.encrypted_amount, encrypted_amount)?
+        };
+        self.amount = self.amount.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+        self.count = self.count.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+        self.commitment = commitment;
+        Ok(())
+    }
+
+    /// Empties the queue, returning the pending `(amount, encrypted_amount, count)`.
+    pub fn take(&mut self) -> Result<(u64, [u8; 64], u64)> {
+        require!(self.count > 0, ErrorCode::NoPendingDeposits);
+        let taken = (self.amount, self.encrypted_amount, self.count);
+        self.amount = 0;
+        self.encrypted_amount = [0u8; 64];
+        self.count = 0;
+        Ok(taken)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pending_deposits_chain_and_drain() {
+        let account = Pubkey::new_unique();
+        let mut pending = PendingDeposits::try_from_slice(&[0u8; PendingDeposits::SIZE - 8])
+            .unwrap();
+        pending.commitment = PendingDeposits::head(&account, None);
+        assert_eq!(pending.take(), Err(ErrorCode::NoPendingDeposits.into()));
+
+        let first = crypto::pedersen_commit(40, &account.to_bytes());
+        pending.push(40, first, &[1u8; 64]).unwrap();
+        // A second depositor racing the first computed its commitment from the old head.
+        assert_eq!(
+            pending.push(10, first, &[2u8; 64]),
+            Err(ErrorCode::CommitmentMismatch.into())
+        );
+        let second = crypto::pedersen_commit(10, &first);
+        pending.push(10, second, &[2u8; 64]).unwrap();
+        assert_eq!(PendingDeposits::head(&account, Some(&pending)), second);
+
+        let (amount, _, count) = pending.take().unwrap();
+        assert_eq!((amount, count), (50, 2));
+        assert_eq!((pending.amount, pending.count), (0, 0));
+        // The chain carries on from where it was, so in-flight deposits stay valid.
+        assert_eq!(pending.commitment, second);
+    }
+}
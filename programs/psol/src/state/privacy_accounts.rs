@@ -1,19 +1,179 @@
 use anchor_lang::prelude::*;
+use crate::crypto;
+use crate::error::ErrorCode;
+use crate::state::layout_version;
 
 #[account]
 pub struct PrivacyAccount {
+    /// Layout version, `PrivacyAccount::VERSION` for accounts written by this program.
+    pub version: u8,
     pub owner: Pubkey,
     pub encrypted_balance: [u8; 64],
     pub commitment: [u8; 32],
-    pub encryption_key: [u8; 32],
+    /// Public spend key; proofs that move value out of the account are bound to it.
+    pub spend_key: [u8; 32],
+    /// Public view key; balances, incoming amounts and memos are encrypted to it.
+    pub view_key: [u8; 32],
     pub nonce: u64,
     pub total_deposits: u64,
     pub total_withdrawals: u64,
     pub last_update: u64,
     pub bump: u8,
+    /// Number of private transfers sent. Encrypted amounts leave `total_deposits`
+    /// stale, so accounts that have sent value cannot use the emergency exit.
+    pub transfers_out: u64,
+    /// Number of private transfers received. Their amounts are encrypted and do not count
+    /// toward the provable balance.
+    pub transfers_in: u64,
+    /// Slot of the latest deposit or credit, screened by `CallPolicy::NoDepositThenWithdraw`.
+    pub last_deposit_slot: u64,
+    /// Zeroed space for future fields, so they can be added without a realloc.
+    pub reserved: [u8; 16],
 }
 
 impl PrivacyAccount {
+    pub const SIZE: usize = 8   // discriminator
+        + 1                     // version
+        + 32                    // owner
+        + 64                    // encrypted_balance
+        + 32                    // commitment
+        + 32                    // spend_key
+        + 32                    // view_key
+        + 8                     // nonce
+        + 8                     // total_deposits
+        + 8                     // total_withdrawals
+        + 8                     // last_update
+        + 1                     // bump
+        + 8                     // transfers_out
+        + 8                     // transfers_in
+        + 8                     // last_deposit_slot
+        + 16;                   // reserved
+
+    pub const VERSION: u8 = 1;
+
+    /// Decodes an account stored under any layout version into the current layout.
+    pub fn decode_any_version(data: &[u8]) -> Result<Self> {
+        match layout_version::<Self>(data, PrivacyAccountV0::SIZE)? {
+            0 => Ok(PrivacyAccountV0::deserialize(&mut &data[8..])?.upgrade()),
+            Self::VERSION => Self::try_deserialize(&mut &data[..]),
+            _ => err!(ErrorCode::UnsupportedAccountVersion),
+        }
+    }
+
+    /// Lamports the account can prove it deposited and has not withdrawn. Zero once
+    /// withdrawals of privately received value exceed the deposits.
+    pub fn provable_balance(&self) -> Result<u64> {
+        Ok(self.total_deposits.saturating_sub(self.total_withdrawals))
+    }
+
+    /// Credits `amount` of rewards at `slot`. The lamports are already locked in the pool,
+    /// and the amount is public, so it counts toward the provable balance.
+    pub fn credit(&mut self, amount: u64, slot: u64) -> Result<()> {
+        let encrypted_amount = crypto::encrypt_amount(amount, &self.view_key);
+        self.encrypted_balance =
+            crypto::add_encrypted(&self.encrypted_balance, &encrypted_amount)?;
+        self.total_deposits = self
+            .total_deposits
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        self.last_update = slot;
+        self.last_deposit_slot = slot;
+        Ok(())
+    }
+
+    /// Re-keys the account to `new_view_key` at `slot`, replacing the balance with
+    /// `new_encrypted_balance` once `proof` shows it holds the same value under the new key.
+    /// Returns the old view key.
+    pub fn rotate_view_key(
+        &mut self,
+        new_view_key: [u8; 32],
+        new_encrypted_balance: [u8; 64],
+        proof: &[u8],
+        slot: u64,
+    ) -> Result<[u8; 32]> {
+        require!(
+            new_view_key != [0u8; 32] && new_view_key != self.view_key,
+            ErrorCode::InvalidEncryptionKey
+        );
+        require!(
+            crypto::verify_reencryption_proof(
+                &self.encrypted_balance,
+                &new_encrypted_balance,
+                &self.view_key,
+                &new_view_key,
+                proof,
+            ),
+            ErrorCode::InvalidProof
+        );
+        let old_view_key = self.view_key;
+        self.view_key = new_view_key;
+        self.encrypted_balance = new_encrypted_balance;
+        self.nonce = self.nonce.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+        self.last_update = slot;
+        Ok(old_view_key)
+    }
+
+    /// Re-keys the account to `new_spend_key` at `slot` once `proof`, made with the current
+    /// spend key, hands the commitment over to it. Later spend and transfer proofs must be
+    /// made with the new key. Returns the old spend key.
+    pub fn rotate_spend_key(
+        &mut self,
+        new_spend_key: [u8; 32],
+        proof: &[u8],
+        slot: u64,
+    ) -> Result<[u8; 32]> {
+        require!(
+            new_spend_key != [0u8; 32] && new_spend_key != self.spend_key,
+            ErrorCode::InvalidSpendKey
+        );
+        require!(
+            crypto::verify_spend_key_rotation_proof(
+                &self.commitment,
+                &self.spend_key,
+                &new_spend_key,
+                proof,
+            ),
+            ErrorCode::InvalidProof
+        );
+        let old_spend_key = self.spend_key;
+        self.spend_key = new_spend_key;
+        self.nonce = self.nonce.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+        self.last_update = slot;
+        Ok(old_spend_key)
+    }
+
+    /// Debits a withdrawal of `amount` at `slot`, never more than the provable balance.
+    /// The placeholder verifier cannot bound `amount` by the committed balance, so value
+    /// received through private transfers cannot be withdrawn until a real one lands.
+    pub fn withdraw(&mut self, amount: u64, slot: u64) -> Result<()> {
+        require!(amount <= self.provable_balance()?, ErrorCode::InsufficientBalance);
+        let encrypted_amount = crypto::encrypt_amount(amount, &self.view_key);
+        self.encrypted_balance =
+            crypto::subtract_encrypted(&self.encrypted_balance, &encrypted_amount)?;
+        self.total_withdrawals = self
+            .total_withdrawals
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        self.last_update = slot;
+        Ok(())
+    }
+}
+
+/// `PrivacyAccount` layout before versioning, with one key for spending and viewing.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct PrivacyAccountV0 {
+    pub owner: Pubkey,
+    pub encrypted_balance: [u8; 64],
+    pub commitment: [u8; 32],
+    pub encryption_key: [u8; 32],
+    pub nonce: u64,
+    pub total_deposits: u64,
+    pub total_withdrawals: u64,
+    pub last_update: u64,
+    pub bump: u8,
+}
+
+impl PrivacyAccountV0 {
     pub const SIZE: usize = 8   // discriminator
         + 32                    // owner
         + 64                    // encrypted_balance
@@ -24,4 +184,186 @@ impl PrivacyAccount {
         + 8                     // total_withdrawals
         + 8                     // last_update
         + 1;                    // bump
+
+    /// The single key becomes both the spend and the view key. v0 did not count sent
+    /// transfers, but every transfer bumped `nonce`, so it bounds `transfers_out` and keeps
+    /// accounts with any history out of the emergency exit. `transfers_in` takes the same
+    /// bound, so withdrawals from such accounts are not capped by `total_deposits`.
+    pub fn upgrade(self) -> PrivacyAccount {
+        PrivacyAccount {
+            version: PrivacyAccount::VERSION,
+            owner: self.owner,
+            encrypted_balance: self.encrypted_balance,
+            commitment: self.commitment,
+            spend_key: self.encryption_key,
+            view_key: self.encryption_key,
+            nonce: self.nonce,
+            total_deposits: self.total_deposits,
+            total_withdrawals: self.total_withdrawals,
+            last_update: self.last_update,
+            bump: self.bump,
+            transfers_out: self.nonce,
+            transfers_in: self.nonce,
+            last_deposit_slot: 0,
+            reserved: [0u8; 16],
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::state::PrivacyPool;
+    use anchor_lang::{AccountSerialize, Discriminator};
+
+    pub(crate) fn test_account() -> PrivacyAccount {
+        PrivacyAccount {
+            version: PrivacyAccount::VERSION,
+            owner: Pubkey::default(),
+            encrypted_balance: [0u8; 64],
+            commitment: [0u8; 32],
+            spend_key: [0u8; 32],
+            view_key: [0u8; 32],
+            nonce: 0,
+            total_deposits: 0,
+            total_withdrawals: 0,
+            last_update: 0,
+            bump: 0,
+            transfers_out: 0,
+            transfers_in: 0,
+            last_deposit_slot: 0,
+            reserved: [0u8; 16],
+        }
+    }
+
+    #[test]
+    fn privacy_account_balances_move() {
+        let mut account = test_account();
+        account.total_deposits = 10;
+        assert_eq!(account.provable_balance().unwrap(), 10);
+        account.total_withdrawals = 5;
+        assert_eq!(account.provable_balance().unwrap(), 5);
+        account.total_withdrawals = 11;
+        assert_eq!(account.provable_balance().unwrap(), 0);
+    }
+
+    #[test]
+    fn withdrawals_are_bounded_by_provable_balance() {
+        let mut account = test_account();
+        account.total_deposits = 100;
+        account.withdraw(60, 7).unwrap();
+        assert_eq!((account.total_withdrawals, account.last_update), (60, 7));
+        assert_eq!(
+            account.withdraw(41, 8),
+            Err(ErrorCode::InsufficientBalance.into())
+        );
+        account.withdraw(40, 8).unwrap();
+        assert_eq!(account.provable_balance().unwrap(), 0);
+
+        // Credited rewards are public, so they can be withdrawn like deposits.
+        account.credit(15, 9).unwrap();
+        assert_eq!((account.provable_balance().unwrap(), account.last_update), (15, 9));
+        account.withdraw(15, 9).unwrap();
+
+        // Privately received value does not lift the bound.
+        account.transfers_in = 1;
+        assert_eq!(
+            account.withdraw(25, 10),
+            Err(ErrorCode::InsufficientBalance.into())
+        );
+        assert_eq!(account.total_withdrawals, 115);
+    }
+
+    #[test]
+    fn view_key_rotation_replaces_key_and_balance() {
+        let mut account = test_account();
+        account.view_key = [1u8; 32];
+        account.encrypted_balance = crypto::encrypt_amount(50, &account.view_key);
+        let rekeyed = crypto::encrypt_amount(50, &[2u8; 32]);
+
+        assert_eq!(
+            account.rotate_view_key([1u8; 32], rekeyed, &[7u8; 64], 5),
+            Err(ErrorCode::InvalidEncryptionKey.into())
+        );
+        assert_eq!(
+            account.rotate_view_key([0u8; 32], rekeyed, &[7u8; 64], 5),
+            Err(ErrorCode::InvalidEncryptionKey.into())
+        );
+        assert_eq!(
+            account.rotate_view_key([2u8; 32], rekeyed, &[], 5),
+            Err(ErrorCode::InvalidProof.into())
+        );
+        assert_eq!(account.view_key, [1u8; 32]);
+
+        let old = account.rotate_view_key([2u8; 32], rekeyed, &[7u8; 64], 5).unwrap();
+        assert_eq!(old, [1u8; 32]);
+        assert_eq!((account.view_key, account.encrypted_balance), ([2u8; 32], rekeyed));
+        assert_eq!((account.nonce, account.last_update), (1, 5));
+        // The spend key is untouched; rotation only changes who can read the balance.
+        assert_eq!(account.spend_key, [0u8; 32]);
+    }
+
+    #[test]
+    fn spend_key_rotation_hands_the_commitment_to_the_new_key() {
+        let mut account = test_account();
+        account.spend_key = [1u8; 32];
+        account.commitment = [4u8; 32];
+        account.encrypted_balance = crypto::encrypt_amount(50, &account.view_key);
+        let balance = account.encrypted_balance;
+
+        assert_eq!(
+            account.rotate_spend_key([1u8; 32], &[7u8; 64], 5),
+            Err(ErrorCode::InvalidSpendKey.into())
+        );
+        assert_eq!(
+            account.rotate_spend_key([0u8; 32], &[7u8; 64], 5),
+            Err(ErrorCode::InvalidSpendKey.into())
+        );
+        assert_eq!(
+            account.rotate_spend_key([2u8; 32], &[], 5),
+            Err(ErrorCode::InvalidProof.into())
+        );
+        assert_eq!((account.spend_key, account.nonce), ([1u8; 32], 0));
+
+        let old = account.rotate_spend_key([2u8; 32], &[7u8; 64], 5).unwrap();
+        assert_eq!(old, [1u8; 32]);
+        assert_eq!((account.spend_key, account.nonce, account.last_update), ([2u8; 32], 1, 5));
+        // The commitment and balance stay; only the key spends are bound to changes.
+        assert_eq!((account.commitment, account.encrypted_balance), ([4u8; 32], balance));
+    }
+
+    #[test]
+    fn every_historical_account_layout_decodes() {
+        // v0 privacy accounts: the single key becomes both keys.
+        let v0 = PrivacyAccountV0 {
+            owner: Pubkey::new_unique(),
+            encrypted_balance: [3u8; 64],
+            commitment: [4u8; 32],
+            encryption_key: [5u8; 32],
+            nonce: 2,
+            total_deposits: 900,
+            total_withdrawals: 100,
+            last_update: 40,
+            bump: 250,
+        };
+        let mut data = PrivacyAccount::DISCRIMINATOR.to_vec();
+        v0.serialize(&mut data).unwrap();
+        assert_eq!(data.len(), PrivacyAccountV0::SIZE);
+        let account = PrivacyAccount::decode_any_version(&data).unwrap();
+        assert_eq!(account.version, PrivacyAccount::VERSION);
+        assert_eq!((account.owner, account.bump), (v0.owner, 250));
+        assert_eq!((account.spend_key, account.view_key), ([5u8; 32], [5u8; 32]));
+        assert_eq!(account.encrypted_balance, [3u8; 64]);
+        assert_eq!(account.provable_balance().unwrap(), 800);
+        assert_eq!((account.transfers_out, account.transfers_in), (2, 2));
+
+        let mut current = Vec::new();
+        account.try_serialize(&mut current).unwrap();
+        assert_eq!(current.len(), PrivacyAccount::SIZE);
+        let decoded = PrivacyAccount::decode_any_version(&current).unwrap();
+        assert_eq!((decoded.owner, decoded.last_update), (v0.owner, 40));
+
+        // Another account type's data is rejected.
+        assert!(PrivacyAccount::decode_any_version(&PrivacyPool::DISCRIMINATOR).is_err());
+    }
 }
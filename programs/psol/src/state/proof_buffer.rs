@@ -0,0 +1,138 @@
+use anchor_lang::prelude::*;
+use crate::error::ErrorCode;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerificationStage {
+    /// Proof bytes are still being written.
+    Uploading,
+    /// Upload complete; `verify_proof_buffer` is folding inputs and checking the proof.
+    Verifying,
+    /// Proof accepted; the buffer can be consumed by `withdraw_private`.
+    Verified,
+}
+
+/// Proof staged across several transactions at `["proof_buffer", pool, owner, nullifier]`.
+///
+/// Created with its final size, filled by `write_proof_chunk`, checked step by step by
+/// `verify_proof_buffer`, and closed by the `withdraw_private` that consumes it.
+///
+/// Unversioned and without reserved space: a buffer lives for one withdrawal and is sized
+/// on creation, so a layout change only needs in-flight buffers to be consumed or closed
+/// before the upgrade.
+#[account]
+pub struct ProofBuffer {
+    pub owner: Pubkey,
+    pub pool: Pubkey,
+    pub nullifier: [u8; 32],
+    pub stage: VerificationStage,
+    /// Declared proof length; the upload is complete once `proof` reaches it.
+    pub proof_len: u32,
+    /// Verification steps completed so far.
+    pub step: u32,
+    /// Verifier state carried between steps.
+    pub accumulator: [u8; 32],
+    pub public_inputs: Vec<[u8; 32]>,
+    pub proof: Vec<u8>,
+    pub bump: u8,
+}
+
+impl ProofBuffer {
+    pub const MAX_PROOF_LEN: usize = 4096;
+    pub const MAX_PUBLIC_INPUTS: usize = 16;
+
+    pub fn space(proof_len: usize, public_inputs: usize) -> usize {
+        8                           // discriminator
+        + 32                        // owner
+        + 32                        // pool
+        + 32                        // nullifier
+        + 1                         // stage
+        + 4                         // proof_len
+        + 4                         // step
+        + 32                        // accumulator
+        + 4 + (32 * public_inputs)  // public_inputs
+        + 4 + proof_len             // proof
+        + 1                         // bump
+    }
+
+    /// Appends `data` at `offset`. Chunks must arrive in order; the last one moves the
+    /// buffer to `Verifying`.
+    pub fn write_chunk(&mut self, offset: u32, data: &[u8]) -> Result<()> {
+        require!(
+            self.stage == VerificationStage::Uploading,
+            ErrorCode::ProofBufferNotReady
+        );
+        require!(
+            offset as usize == self.proof.len()
+                && self.proof.len() + data.len() <= self.proof_len as usize,
+            ErrorCode::InvalidProofChunk
+        );
+        self.proof.extend_from_slice(data);
+        if self.proof.len() == self.proof_len as usize {
+            self.stage = VerificationStage::Verifying;
+        }
+        Ok(())
+    }
+
+    /// Checks this buffer holds an accepted proof for exactly this withdrawal.
+    pub fn check_verified_for(&self, nullifier: &[u8; 32], public_inputs: &[[u8; 32]]) -> Result<()> {
+        require!(
+            self.stage == VerificationStage::Verified,
+            ErrorCode::ProofBufferNotReady
+        );
+        require!(
+            self.nullifier == *nullifier && self.public_inputs == public_inputs,
+            ErrorCode::ProofBufferMismatch
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto;
+
+    #[test]
+    fn proof_buffer_uploads_and_verifies_in_steps() {
+        let nullifier = [4u8; 32];
+        let inputs: Vec<[u8; 32]> = (0..6u8).map(|i| [i; 32]).collect();
+        let mut buffer = ProofBuffer {
+            owner: Pubkey::new_unique(),
+            pool: Pubkey::new_unique(),
+            nullifier,
+            stage: VerificationStage::Uploading,
+            proof_len: 96,
+            step: 0,
+            accumulator: [0u8; 32],
+            public_inputs: inputs.clone(),
+            proof: Vec::new(),
+            bump: 1,
+        };
+
+        assert!(buffer.write_chunk(10, &[1u8; 40]).is_err());
+        buffer.write_chunk(0, &[1u8; 40]).unwrap();
+        assert!(buffer.write_chunk(40, &[1u8; 57]).is_err());
+        assert!(buffer.check_verified_for(&nullifier, &inputs).is_err());
+        buffer.write_chunk(40, &[1u8; 56]).unwrap();
+        assert_eq!(buffer.stage, VerificationStage::Verifying);
+        assert!(buffer.write_chunk(96, &[1u8; 1]).is_err());
+
+        // Six inputs fold in two steps, then the pairing step accepts the proof.
+        let mut verified = false;
+        while !verified {
+            verified = crypto::verify_proof_step(
+                &buffer.proof,
+                &buffer.public_inputs,
+                &mut buffer.step,
+                &mut buffer.accumulator,
+            )
+            .unwrap();
+        }
+        assert_eq!(buffer.step, 3);
+        buffer.stage = VerificationStage::Verified;
+
+        buffer.check_verified_for(&nullifier, &inputs).unwrap();
+        assert!(buffer.check_verified_for(&[5u8; 32], &inputs).is_err());
+        assert!(buffer.check_verified_for(&nullifier, &inputs[..5]).is_err());
+    }
+}
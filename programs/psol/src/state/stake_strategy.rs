@@ -0,0 +1,220 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransientState {
+    /// No transient stake account exists.
+    Idle,
+    /// Delegated by `rebalance_stake`; `settle_stake` merges it into the main account once
+    /// it is active.
+    Activating,
+    /// Split off the main account and deactivated; `settle_stake` withdraws it to the
+    /// vault once it is inactive.
+    Deactivating,
+}
+
+/// What `rebalance_stake` does next, from `StakeStrategy::plan_rebalance`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StakeAction {
+    None,
+    /// Delegate this many lamports from the vault.
+    Stake(u64),
+    /// Split this many lamports off the main stake account and deactivate them.
+    Unstake(u64),
+}
+
+/// Optional strategy at `["stake_strategy", pool]` that delegates part of `total_locked`
+/// to one validator through native stake accounts whose staker and withdrawer are the
+/// vault PDA.
+///
+/// Delegated lamports stay in `total_locked`. Stake moves through a main account at
+/// `["stake", pool]` and a transient one at `["transient_stake", pool]`, so at most one
+/// activation or deactivation is in flight.
+#[account]
+pub struct StakeStrategy {
+    pub version: u8,
+    pub pool: Pubkey,
+    pub vote_account: Pubkey,
+    /// Share of `total_locked` to keep delegated, in basis points.
+    pub target_bps: u16,
+    /// Lamports of `total_locked` always left liquid in the vault for withdrawals.
+    pub min_buffer: u64,
+    /// Lamports in the main stake account as of the last crank.
+    pub staked: u64,
+    /// Lamports in the transient stake account as of the last crank.
+    pub transient: u64,
+    pub transient_state: TransientState,
+    /// Signer allowed to `claim_stake_rewards`, e.g. psol_token's config PDA. Harvested
+    /// rewards stay in `total_locked` as pool surplus until claimed.
+    pub rewards_authority: Pubkey,
+    /// Rewards harvested into `total_locked` and not yet claimed.
+    pub unclaimed_rewards: u64,
+    pub bump: u8,
+    pub stake_bump: u8,
+    pub transient_bump: u8,
+    /// Zeroed space for future fields, so they can be added without a realloc.
+    pub reserved: [u8; 32],
+}
+
+impl StakeStrategy {
+    pub const SIZE: usize = 8  // discriminator
+        + 1                    // version
+        + 32                   // pool
+        + 32                   // vote_account
+        + 2                    // target_bps
+        + 8                    // min_buffer
+        + 8                    // staked
+        + 8                    // transient
+        + 1                    // transient_state
+        + 32                   // rewards_authority
+        + 8                    // unclaimed_rewards
+        + 1                    // bump
+        + 1                    // stake_bump
+        + 1                    // transient_bump
+        + 32; // reserved
+
+    pub const VERSION: u8 = 1;
+
+    /// Smallest move `rebalance_stake` makes, so a crank cannot churn dust through stake
+    /// accounts and every account stays above the rent reserve and minimum delegation.
+    pub const MIN_REBALANCE_LAMPORTS: u64 = 1_000_000_000;
+
+    pub fn set_target(&mut self, target_bps: u16, min_buffer: u64) -> Result<()> {
+        require!(target_bps <= 10_000, ErrorCode::InvalidStakeTarget);
+        self.target_bps = target_bps;
+        self.min_buffer = min_buffer;
+        Ok(())
+    }
+
+    /// Lamports that should be delegated: `target_bps` of `total_locked`, less whatever
+    /// would eat into `min_buffer`.
+    pub fn target_staked(&self, total_locked: u64) -> u64 {
+        let share = (total_locked as u128 * self.target_bps as u128 / 10_000) as u64;
+        share.min(total_locked.saturating_sub(self.min_buffer))
+    }
+
+    /// Next move towards the target, given the vault's `liquid` lamports above its own rent
+    /// and the rent `reserve` of a stake account. Unstaking splits into a transient account
+    /// funded with `reserve` from the vault, and leaves nothing in the main account if what
+    /// would remain is below `MIN_REBALANCE_LAMPORTS`.
+    pub fn plan_rebalance(&self, total_locked: u64, liquid: u64, reserve: u64) -> StakeAction {
+        if self.transient_state != TransientState::Idle {
+            return StakeAction::None;
+        }
+        let target = self.target_staked(total_locked);
+        if target > self.staked {
+            let lamports = (target - self.staked).min(liquid);
+            if lamports < Self::MIN_REBALANCE_LAMPORTS {
+                return StakeAction::None;
+            }
+            StakeAction::Stake(lamports)
+        } else {
+            let lamports = self.staked - target;
+            if lamports < Self::MIN_REBALANCE_LAMPORTS || liquid < reserve {
+                return StakeAction::None;
+            }
+            if self.staked - lamports < Self::MIN_REBALANCE_LAMPORTS {
+                return StakeAction::Unstake(self.staked);
+            }
+            StakeAction::Unstake(lamports)
+        }
+    }
+
+    /// Records the stake accounts' observed balances and returns the rewards they earned
+    /// since the last crank, which the caller adds to `total_locked`. Balances of accounts
+    /// the strategy is not using are ignored, so lamports sent to an unused PDA are not
+    /// mistaken for stake.
+    pub fn record_balances(&mut self, main_lamports: u64, transient_lamports: u64) -> Result<u64> {
+        let mut rewards = 0u64;
+        if self.staked > 0 {
+            rewards = main_lamports.saturating_sub(self.staked);
+            self.staked = self.staked.max(main_lamports);
+        }
+        if self.transient_state != TransientState::Idle {
+            rewards = rewards
+                .checked_add(transient_lamports.saturating_sub(self.transient))
+                .ok_or(ErrorCode::MathOverflow)?;
+            self.transient = self.transient.max(transient_lamports);
+        }
+        self.unclaimed_rewards = self
+            .unclaimed_rewards
+            .checked_add(rewards)
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(rewards)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stake_strategy_keeps_a_liquid_buffer_and_books_rewards() {
+        const SOL: u64 = 1_000_000_000;
+        let reserve = 2_282_880;
+        let mut strategy = StakeStrategy {
+            version: StakeStrategy::VERSION,
+            pool: Pubkey::default(),
+            vote_account: Pubkey::default(),
+            target_bps: 0,
+            min_buffer: 0,
+            staked: 0,
+            transient: 0,
+            transient_state: TransientState::Idle,
+            rewards_authority: Pubkey::default(),
+            unclaimed_rewards: 0,
+            bump: 0,
+            stake_bump: 0,
+            transient_bump: 0,
+            reserved: [0u8; 32],
+        };
+        assert_eq!(strategy.try_to_vec().unwrap().len() + 8, StakeStrategy::SIZE);
+        assert!(strategy.set_target(10_001, 0).is_err());
+
+        // 80% of 100 SOL would leave 20 liquid, but the buffer keeps 30.
+        strategy.set_target(8_000, 30 * SOL).unwrap();
+        assert_eq!(strategy.target_staked(100 * SOL), 70 * SOL);
+        assert_eq!(
+            strategy.plan_rebalance(100 * SOL, 100 * SOL, reserve),
+            StakeAction::Stake(70 * SOL)
+        );
+        // Never more than the vault holds, and never dust.
+        assert_eq!(
+            strategy.plan_rebalance(100 * SOL, 40 * SOL, reserve),
+            StakeAction::Stake(40 * SOL)
+        );
+        assert_eq!(strategy.plan_rebalance(100 * SOL, SOL / 2, reserve), StakeAction::None);
+        strategy.staked = 70 * SOL;
+        assert_eq!(strategy.plan_rebalance(100 * SOL, 30 * SOL, reserve), StakeAction::None);
+
+        // Withdrawals shrink total_locked; the excess is split off and deactivated.
+        assert_eq!(
+            strategy.plan_rebalance(60 * SOL, 20 * SOL, reserve),
+            StakeAction::Unstake(40 * SOL)
+        );
+        assert_eq!(strategy.plan_rebalance(60 * SOL, 0, reserve), StakeAction::None);
+        // A remainder too small to keep goes too.
+        strategy.set_target(0, 0).unwrap();
+        assert_eq!(
+            strategy.plan_rebalance(60 * SOL, 20 * SOL, reserve),
+            StakeAction::Unstake(70 * SOL)
+        );
+
+        // Nothing moves while a transient account is in flight.
+        strategy.transient = 10 * SOL;
+        strategy.transient_state = TransientState::Activating;
+        assert_eq!(strategy.plan_rebalance(60 * SOL, 20 * SOL, reserve), StakeAction::None);
+
+        // Rewards are whatever the accounts hold above what was put in, counted once.
+        assert_eq!(strategy.record_balances(71 * SOL, 10 * SOL + 5).unwrap(), SOL + 5);
+        assert_eq!(strategy.record_balances(71 * SOL, 10 * SOL + 5).unwrap(), 0);
+        assert_eq!(
+            (strategy.staked, strategy.transient, strategy.unclaimed_rewards),
+            (71 * SOL, 10 * SOL + 5, SOL + 5)
+        );
+        // Lamports sent to an unused PDA are not stake.
+        strategy.transient_state = TransientState::Idle;
+        assert_eq!(strategy.record_balances(71 * SOL, 99 * SOL).unwrap(), 0);
+    }
+}
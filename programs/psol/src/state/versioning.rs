@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_lang::Discriminator;
+
+/// Layout version of a serialized `T` (discriminator included). Versioned layouts store
+/// it in the first byte after the discriminator; accounts written before versioning have
+/// no version byte and are recognised by their exact length, `unversioned_len`.
+pub fn layout_version<T: Discriminator>(data: &[u8], unversioned_len: usize) -> Result<u8> {
+    require!(
+        data.len() > 8 && data[..8] == T::DISCRIMINATOR,
+        anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+    );
+    if data.len() == unversioned_len {
+        return Ok(0);
+    }
+    Ok(data[8])
+}
+
+/// Replaces `account`'s data with `data`, reallocating it and topping its rent exemption
+/// up from `payer` as needed.
+pub fn rewrite_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    data: &[u8],
+) -> Result<()> {
+    let required = Rent::get()?.minimum_balance(data.len());
+    let shortfall = required.saturating_sub(account.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    if account.data_len() != data.len() {
+        account.realloc(data.len(), false)?;
+    }
+    account.try_borrow_mut_data()?.copy_from_slice(data);
+    Ok(())
+}
@@ -0,0 +1,172 @@
+//! Off-chain stealth address derivation.
+//!
+//! A recipient publishes a meta-address `(scan_key, spend_key)` of compressed Ed25519
+//! points. For every payment the sender picks an ephemeral scalar `r`, publishes
+//! `R = r·G`, and pays into the `PrivacyAccount` owned by the one-time address
+//! `P = spend_key + H(8·r·scan_key)·G`. The recipient detects it by computing the same
+//! shared secret from `8·s·R`, and can spend with the private scalar `b + H(..)`.
+//!
+//! The one-time account's view secret `v = H(view, shared)` is derived the same way and
+//! never leaves the two parties; only its public key `v·G` is passed to `transfer_stealth`.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::scalar::Scalar;
+
+const TWEAK_DOMAIN: &[u8] = b"psol-stealth-tweak";
+const VIEW_DOMAIN: &[u8] = b"psol-stealth-view";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StealthMetaAddress {
+    pub scan_key: [u8; 32],
+    pub spend_key: [u8; 32],
+}
+
+/// Everything a sender needs to call `transfer_stealth`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StealthPayment {
+    pub one_time_address: Pubkey,
+    pub ephemeral_pubkey: [u8; 32],
+    pub view_tag: u8,
+    /// Public view key of the one-time account; amounts and memos are encrypted to it.
+    pub view_key: [u8; 32],
+}
+
+/// Payment found by the recipient while scanning `StealthPayment` events.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DetectedPayment {
+    pub one_time_address: Pubkey,
+    /// Added to the recipient's spend scalar to obtain the one-time private scalar.
+    pub spend_tweak: [u8; 32],
+    /// Secret scalar of the one-time account's public view key, for decrypting amounts.
+    pub view_secret: [u8; 32],
+}
+
+fn point(bytes: &[u8; 32]) -> Option<EdwardsPoint> {
+    CompressedEdwardsY(*bytes).decompress()
+}
+
+fn tweak(shared: &EdwardsPoint) -> Scalar {
+    let h = hashv(&[TWEAK_DOMAIN, shared.compress().as_bytes()]);
+    Scalar::from_bytes_mod_order(h.to_bytes())
+}
+
+fn view_tag(shared: &EdwardsPoint) -> u8 {
+    hashv(&[shared.compress().as_bytes()]).to_bytes()[0]
+}
+
+fn view_secret(shared: &EdwardsPoint) -> Scalar {
+    let h = hashv(&[VIEW_DOMAIN, shared.compress().as_bytes()]);
+    Scalar::from_bytes_mod_order(h.to_bytes())
+}
+
+/// Sender side: derives the one-time address for `meta` from a fresh random `ephemeral_secret`.
+pub fn derive_payment(
+    meta: &StealthMetaAddress,
+    ephemeral_secret: &[u8; 32],
+) -> Option<StealthPayment> {
+    let r = Scalar::from_bytes_mod_order(*ephemeral_secret);
+    let shared = (r * point(&meta.scan_key)?).mul_by_cofactor();
+    let one_time = point(&meta.spend_key)? + tweak(&shared) * ED25519_BASEPOINT_POINT;
+    Some(StealthPayment {
+        one_time_address: Pubkey::new_from_array(one_time.compress().to_bytes()),
+        ephemeral_pubkey: (r * ED25519_BASEPOINT_POINT).compress().to_bytes(),
+        view_tag: view_tag(&shared),
+        view_key: public_key(&view_secret(&shared).to_bytes()),
+    })
+}
+
+/// Recipient side: checks whether a published payment belongs to the holder of `scan_secret`.
+///
+/// The view tag rejects almost all foreign payments before the point addition.
+pub fn scan_payment(
+    scan_secret: &[u8; 32],
+    spend_key: &[u8; 32],
+    ephemeral_pubkey: &[u8; 32],
+    tag: u8,
+    one_time_address: &Pubkey,
+) -> Option<DetectedPayment> {
+    let s = Scalar::from_bytes_mod_order(*scan_secret);
+    let shared = (s * point(ephemeral_pubkey)?).mul_by_cofactor();
+    if view_tag(&shared) != tag {
+        return None;
+    }
+    let t = tweak(&shared);
+    let expected = point(spend_key)? + t * ED25519_BASEPOINT_POINT;
+    if expected.compress().to_bytes() != one_time_address.to_bytes() {
+        return None;
+    }
+    Some(DetectedPayment {
+        one_time_address: *one_time_address,
+        spend_tweak: t.to_bytes(),
+        view_secret: view_secret(&shared).to_bytes(),
+    })
+}
+
+/// Address of the `PrivacyAccount` that `transfer_stealth` creates in `pool` for
+/// `one_time_address`. Stealth accounts have their own seed, apart from the
+/// `["privacy", owner]` accounts of `init_privacy_account`.
+pub fn account_address(pool: &Pubkey, one_time_address: &Pubkey) -> Pubkey {
+    let seeds: &[&[u8]] = &[b"stealth", pool.as_ref(), one_time_address.as_ref()];
+    Pubkey::find_program_address(seeds, &crate::ID).0
+}
+
+/// Public key for a raw secret scalar, used to build meta-addresses.
+pub fn public_key(secret: &[u8; 32]) -> [u8; 32] {
+    (Scalar::from_bytes_mod_order(*secret) * ED25519_BASEPOINT_POINT)
+        .compress()
+        .to_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stealth_payments_are_detected_only_by_recipient() {
+        let scan_secret = [3u8; 32];
+        let spend_secret = [5u8; 32];
+        let meta = StealthMetaAddress {
+            scan_key: public_key(&scan_secret),
+            spend_key: public_key(&spend_secret),
+        };
+        let payment = derive_payment(&meta, &[11u8; 32]).unwrap();
+
+        let found = scan_payment(
+            &scan_secret,
+            &meta.spend_key,
+            &payment.ephemeral_pubkey,
+            payment.view_tag,
+            &payment.one_time_address,
+        )
+        .unwrap();
+        assert_eq!(found.one_time_address, payment.one_time_address);
+        // Only the public half of the view key is published.
+        assert_eq!(public_key(&found.view_secret), payment.view_key);
+        assert_ne!(found.view_secret, payment.view_key);
+
+        assert!(scan_payment(
+            &[4u8; 32],
+            &meta.spend_key,
+            &payment.ephemeral_pubkey,
+            payment.view_tag,
+            &payment.one_time_address,
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn stealth_accounts_cannot_collide_with_privacy_accounts() {
+        let pool = Pubkey::find_program_address(&[b"privacy_pool"], &crate::ID).0;
+        let owner = Pubkey::new_unique();
+        let privacy_account =
+            Pubkey::find_program_address(&[b"privacy", owner.as_ref()], &crate::ID).0;
+
+        // A sender naming an existing owner as the one-time address gets a separate account.
+        assert_ne!(account_address(&pool, &owner), privacy_account);
+        assert_ne!(account_address(&pool, &owner), account_address(&Pubkey::new_unique(), &owner));
+        assert_eq!(account_address(&pool, &owner), account_address(&pool, &owner));
+    }
+}
@@ -0,0 +1,187 @@
+//! Off-chain selective disclosure.
+//!
+//! A view secret lets its holder (the owner, an auditor, a compliance team) read one
+//! account's balance, deposits, incoming transfer amounts and memos from emitted events
+//! without gaining spend authority. Event payloads are the bytes after base64-decoding a
+//! `Program data:` log line, discriminator included.
+
+use anchor_lang::prelude::*;
+use anchor_lang::Event;
+
+use crate::crypto;
+use crate::error::ErrorCode;
+use crate::events::MemoAttached;
+use crate::instructions::{PrivateDeposit, PrivateTransfer};
+use crate::state::PrivacyAccount;
+use crate::stealth;
+
+/// Opens memo envelopes (`ephemeral_pubkey || nonce || ciphertext || tag`).
+///
+/// Implemented by wallets with their own X25519 + XChaCha20-Poly1305 stack so this
+/// crate does not pull AEAD dependencies into the on-chain build.
+pub trait MemoOpener {
+    fn open(&self, view_secret: &[u8; 32], envelope: &[u8]) -> Option<Vec<u8>>;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Activity {
+    Deposit { amount: u64, slot: u64 },
+    TransferIn { from: Pubkey, amount: u64, slot: u64 },
+    /// Outgoing amounts are encrypted to the recipient, so only the counterparty is known.
+    TransferOut { to: Pubkey, slot: u64 },
+    /// `None` when the opener could not authenticate the envelope.
+    Memo { plaintext: Option<Vec<u8>> },
+}
+
+pub struct ViewingKey {
+    /// `PrivacyAccount` address the key discloses.
+    pub account: Pubkey,
+    secret: [u8; 32],
+    /// Public key of `secret`, the `view_key` on-chain code encrypts to.
+    view_key: [u8; 32],
+}
+
+impl ViewingKey {
+    pub fn new(account: Pubkey, secret: [u8; 32]) -> Self {
+        Self {
+            account,
+            secret,
+            view_key: stealth::public_key(&secret),
+        }
+    }
+
+    /// Decrypts the balance of `account`, which must publish this key's `view_key`.
+    pub fn balance(&self, account: &PrivacyAccount) -> Result<u64> {
+        require!(account.view_key == self.view_key, ErrorCode::InvalidEncryptionKey);
+        open_amount(&account.encrypted_balance, &self.secret)
+    }
+
+    /// Decodes one event, returning `None` for events that do not concern `self.account`.
+    pub fn read_event(&self, data: &[u8], opener: &dyn MemoOpener) -> Result<Option<Activity>> {
+        if let Some(ev) = decode::<PrivateDeposit>(data)? {
+            if ev.privacy_account != self.account {
+                return Ok(None);
+            }
+            return Ok(Some(Activity::Deposit {
+                amount: ev.amount,
+                slot: ev.slot,
+            }));
+        }
+        if let Some(ev) = decode::<PrivateTransfer>(data)? {
+            if ev.recipient_account == self.account {
+                return Ok(Some(Activity::TransferIn {
+                    from: ev.sender_account,
+                    amount: open_amount(&ev.encrypted_amount, &self.secret)?,
+                    slot: ev.slot,
+                }));
+            }
+            if ev.sender_account == self.account {
+                return Ok(Some(Activity::TransferOut {
+                    to: ev.recipient_account,
+                    slot: ev.slot,
+                }));
+            }
+            return Ok(None);
+        }
+        if let Some(ev) = decode::<MemoAttached>(data)? {
+            if ev.recipient_account != self.account {
+                return Ok(None);
+            }
+            return Ok(Some(Activity::Memo {
+                plaintext: opener.open(&self.secret, &ev.memo),
+            }));
+        }
+        Ok(None)
+    }
+
+    /// Reads a stream of event payloads in order, keeping those that concern this account.
+    pub fn scan<'a>(
+        &self,
+        events: impl IntoIterator<Item = &'a [u8]>,
+        opener: &dyn MemoOpener,
+    ) -> Result<Vec<Activity>> {
+        let mut activity = Vec::new();
+        for data in events {
+            if let Some(a) = self.read_event(data, opener)? {
+                activity.push(a);
+            }
+        }
+        Ok(activity)
+    }
+}
+
+/// Decrypts the auditor copy of a transfer amount with the pool's auditor secret.
+pub fn audit_transfer(event: &PrivateTransfer, auditor_secret: &[u8; 32]) -> Result<Option<u64>> {
+    event
+        .auditor_amount
+        .as_ref()
+        .map(|ct| open_amount(ct, auditor_secret))
+        .transpose()
+}
+
+/// Decrypts an amount encrypted on chain to the public key of `secret`. The placeholder
+/// cipher in `crypto` is keyed by that public key rather than by the secret itself.
+fn open_amount(ciphertext: &[u8; 64], secret: &[u8; 32]) -> Result<u64> {
+    crypto::decrypt_amount(ciphertext, &stealth::public_key(secret))
+}
+
+fn decode<T: Event>(data: &[u8]) -> Result<Option<T>> {
+    if data.len() < 8 || data[..8] != T::DISCRIMINATOR {
+        return Ok(None);
+    }
+    T::try_from_slice(&data[8..])
+        .map(Some)
+        .map_err(|e| ProgramError::BorshIoError(e.to_string()).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::privacy_accounts::tests::test_account;
+
+    struct NoMemos;
+
+    impl MemoOpener for NoMemos {
+        fn open(&self, _: &[u8; 32], _: &[u8]) -> Option<Vec<u8>> {
+            None
+        }
+    }
+
+    #[test]
+    fn viewing_key_reads_only_its_own_transfers() {
+        let view_secret = [7u8; 32];
+        let alice = Pubkey::new_unique();
+        let bob = Pubkey::new_unique();
+        let mut bob_account = test_account();
+        bob_account.view_key = stealth::public_key(&view_secret);
+
+        // Senders encrypt to the recipient's published view key, as the handlers expect.
+        let encrypted_amount = crypto::encrypt_amount(42, &bob_account.view_key);
+        bob_account.encrypted_balance = encrypted_amount;
+        let transfer = PrivateTransfer {
+            pool: Pubkey::default(),
+            sender_account: alice,
+            recipient_account: bob,
+            encrypted_amount,
+            auditor_amount: None,
+            slot: 9,
+        };
+        let data = transfer.data();
+
+        let key = ViewingKey::new(bob, view_secret);
+        let activity = key.scan([data.as_slice()], &NoMemos).unwrap();
+        assert_eq!(
+            activity,
+            vec![Activity::TransferIn {
+                from: alice,
+                amount: 42,
+                slot: 9
+            }]
+        );
+        assert_eq!(key.balance(&bob_account).unwrap(), 42);
+        let unrelated = ViewingKey::new(Pubkey::new_unique(), view_secret);
+        assert!(unrelated.scan([data.as_slice()], &NoMemos).unwrap().is_empty());
+        let wrong_secret = ViewingKey::new(bob, [8u8; 32]);
+        assert!(wrong_secret.balance(&bob_account).is_err());
+    }
+}
@@ -0,0 +1,176 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { createHash } from "crypto";
+import { Psol } from "../target/types/psol";
+import { assert } from "chai";
+
+// A relayer submits withdrawals from two privacy accounts under one aggregate proof.
+describe("aggregate_withdraw", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const psol = anchor.workspace.Psol as Program<Psol>;
+  const connection = provider.connection;
+  const wallet = provider.wallet as anchor.Wallet;
+  const { PublicKey, Keypair, SystemProgram, LAMPORTS_PER_SOL } = anchor.web3;
+
+  const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, psol.programId)[0];
+  const pool = pda(Buffer.from("privacy_pool"));
+  const vault = pda(Buffer.from("vault"), pool.toBuffer());
+  const commitmentRegistry = pda(Buffer.from("commitment"), pool.toBuffer());
+  const nullifierArchive = pda(Buffer.from("nullifier_archive"), pool.toBuffer());
+
+  const deposit = new BN(LAMPORTS_PER_SOL / 10);
+  const owners = [Keypair.generate(), Keypair.generate()];
+  const recipients = [Keypair.generate().publicKey, Keypair.generate().publicKey];
+  const privacyAccounts = owners.map((o) => pda(Buffer.from("privacy"), o.publicKey.toBuffer()));
+
+  const sha256 = (...parts: Buffer[]) => {
+    const hash = createHash("sha256");
+    parts.forEach((p) => hash.update(p));
+    return hash.digest();
+  };
+  const u64 = (n: BN | number) => new BN(n).toArrayLike(Buffer, "le", 8);
+  const word = (n: BN) => Buffer.concat([u64(n), Buffer.alloc(24)]);
+
+  // crypto::aggregation_transcript over crypto::aggregated_withdrawal_inputs, followed by
+  // the placeholder proof body.
+  const aggregateProof = async (items: { amount: BN; nullifier: Buffer }[]) => {
+    const statements = await Promise.all(
+      items.map(async ({ amount, nullifier }, i) => {
+        const account = await psol.account.privacyAccount.fetch(privacyAccounts[i]);
+        return [
+          Buffer.from(account.commitment),
+          Buffer.from(account.spendKey),
+          nullifier,
+          recipients[i].toBuffer(),
+          word(amount),
+        ];
+      })
+    );
+    const transcript = sha256(
+      Buffer.from("psol-snarkpack-v1"),
+      u64(statements.length),
+      ...statements.flatMap((inputs) => [u64(inputs.length), ...inputs])
+    );
+    return Buffer.concat([transcript, Buffer.alloc(32)]);
+  };
+
+  const withdraw = async (amounts: BN[], signers: anchor.web3.Keypair[]) => {
+    const items = amounts.map((amount) => ({
+      amount,
+      nullifier: Keypair.generate().publicKey.toBuffer(),
+    }));
+    const proof = await aggregateProof(items);
+    const remaining = items.flatMap(({ nullifier }, i) => [
+      { pubkey: privacyAccounts[i], isWritable: true, isSigner: false },
+      {
+        pubkey: owners[i].publicKey,
+        isWritable: false,
+        isSigner: signers.includes(owners[i]),
+      },
+      { pubkey: recipients[i], isWritable: true, isSigner: false },
+      {
+        pubkey: pda(Buffer.from("nullifier"), pool.toBuffer(), nullifier),
+        isWritable: true,
+        isSigner: false,
+      },
+    ]);
+    return psol.methods
+      .aggregateWithdraw(
+        items.map(({ amount, nullifier }) => ({
+          amount,
+          nullifier: Array.from(nullifier),
+          nullifierProof: null,
+        })),
+        proof
+      )
+      .accountsPartial({
+        pool,
+        vault,
+        nullifierArchive,
+        nullifierTree: null,
+        relayer: wallet.publicKey,
+        callerAllowlist: null,
+      })
+      .remainingAccounts(remaining)
+      .signers(signers)
+      .rpc();
+  };
+
+  before(async () => {
+    if ((await connection.getAccountInfo(pool)) === null) {
+      await psol.methods
+        .initializePool(0, new BN(216_000), { accounts: {} })
+        .accountsPartial({
+          pool,
+          vault,
+          commitmentTree: null,
+          nullifierTree: null,
+          admin: wallet.publicKey,
+        })
+        .rpc();
+    }
+    for (const [i, owner] of owners.entries()) {
+      await connection.confirmTransaction(
+        await connection.requestAirdrop(owner.publicKey, LAMPORTS_PER_SOL)
+      );
+      await psol.methods
+        .initPrivacyAccount(Array(32).fill(2), Array(32).fill(3))
+        .accountsPartial({ privacyAccount: privacyAccounts[i], owner: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      // Fund each account through deposit_for, then claim the pending deposit.
+      const pendingDeposits = pda(Buffer.from("pending_deposits"), privacyAccounts[i].toBuffer());
+      await psol.methods
+        .depositFor(
+          deposit,
+          Array.from(sha256(u64(deposit), privacyAccounts[i].toBuffer())),
+          Array(64).fill(1),
+          Buffer.alloc(64, 1),
+          null
+        )
+        .accountsPartial({
+          pool,
+          vault,
+          commitmentRegistry,
+          commitmentTree: null,
+          privacyAccount: privacyAccounts[i],
+          depositor: wallet.publicKey,
+          callerAllowlist: null,
+          pendingDeposits,
+        })
+        .rpc();
+      await psol.methods
+        .claimPendingDeposits()
+        .accountsPartial({
+          privacyAccount: privacyAccounts[i],
+          pendingDeposits,
+          owner: owner.publicKey,
+        })
+        .signers([owner])
+        .rpc();
+    }
+  });
+
+  it("rejects withdrawals the owners did not sign", async () => {
+    const half = deposit.divn(2);
+    await assert.isRejected(withdraw([half, half], [owners[0]]), /Unauthorized/);
+  });
+
+  it("rejects withdrawals beyond an account's provable balance", async () => {
+    await assert.isRejected(
+      withdraw([deposit.addn(1), new BN(1)], owners),
+      /InsufficientBalance/
+    );
+  });
+
+  it("pays every recipient when all owners co-sign", async () => {
+    const half = deposit.divn(2);
+    await withdraw([half, half], owners);
+    for (const [i, account] of privacyAccounts.entries()) {
+      const { totalWithdrawals } = await psol.account.privacyAccount.fetch(account);
+      assert.equal(totalWithdrawals.toString(), half.toString());
+      assert.equal(await connection.getBalance(recipients[i]), half.toNumber());
+    }
+  });
+});
@@ -0,0 +1,210 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { createHash } from "crypto";
+import { Psol } from "../target/types/psol";
+import { MockCaller } from "../target/types/mock_caller";
+import { assert } from "chai";
+
+// `mock_caller` stands in for a router (the user pays) and a payroll program (its
+// treasury PDA pays), both depositing through psol-cpi into someone else's account. The
+// deposits wait in the account's pending record until the owner claims them.
+describe("deposit_for", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const psol = anchor.workspace.Psol as Program<Psol>;
+  const caller = anchor.workspace.MockCaller as Program<MockCaller>;
+  const connection = provider.connection;
+  const wallet = provider.wallet as anchor.Wallet;
+  const { PublicKey, Keypair, SystemProgram, LAMPORTS_PER_SOL } = anchor.web3;
+
+  const pda = (programId: anchor.web3.PublicKey, ...seeds: Buffer[]) =>
+    PublicKey.findProgramAddressSync(seeds, programId)[0];
+  const pool = pda(psol.programId, Buffer.from("privacy_pool"));
+  const vault = pda(psol.programId, Buffer.from("vault"), pool.toBuffer());
+  const commitmentRegistry = pda(psol.programId, Buffer.from("commitment"), pool.toBuffer());
+  const callerAllowlist = pda(psol.programId, Buffer.from("caller_allowlist"), pool.toBuffer());
+  const treasury = pda(caller.programId, Buffer.from("treasury"));
+
+  const beneficiary = Keypair.generate();
+  const privacyAccount = pda(
+    psol.programId,
+    Buffer.from("privacy"),
+    beneficiary.publicKey.toBuffer()
+  );
+  const pendingDeposits = pda(
+    psol.programId,
+    Buffer.from("pending_deposits"),
+    privacyAccount.toBuffer()
+  );
+  const amount = new BN(LAMPORTS_PER_SOL / 10);
+  const encryptedAmount = Array(64).fill(1);
+  const proof = Buffer.alloc(64, 1);
+
+  // crypto::pedersen_commit: sha256(amount_le || blinding).
+  const commit = (value: BN, blinding: number[] | Buffer) => {
+    const hash = createHash("sha256");
+    hash.update(value.toArrayLike(Buffer, "le", 8));
+    hash.update(Buffer.from(blinding));
+    return Array.from(hash.digest());
+  };
+  // psol_cpi::next_commitment: extends the pending record's head, or the account's address
+  // before the first deposit.
+  const nextCommitment = async () => {
+    const pending = await psol.account.pendingDeposits.fetchNullable(pendingDeposits);
+    return commit(amount, pending ? pending.commitment : privacyAccount.toBuffer());
+  };
+
+  const psolAccounts = () => ({
+    psolProgram: psol.programId,
+    pool,
+    vault,
+    commitmentRegistry,
+    commitmentTree: null,
+    privacyAccount,
+    instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+    callerAllowlist: null,
+    systemProgram: SystemProgram.programId,
+    pendingDeposits,
+  });
+
+  before(async () => {
+    if ((await connection.getAccountInfo(pool)) === null) {
+      await psol.methods
+        .initializePool(0, new BN(216_000), { accounts: {} })
+        .accountsPartial({
+          pool,
+          vault,
+          commitmentTree: null,
+          nullifierTree: null,
+          admin: wallet.publicKey,
+        })
+        .rpc();
+    }
+    await connection.confirmTransaction(
+      await connection.requestAirdrop(beneficiary.publicKey, LAMPORTS_PER_SOL)
+    );
+    await psol.methods
+      .initPrivacyAccount(Array(32).fill(2), Array(32).fill(3))
+      .accountsPartial({ privacyAccount, owner: beneficiary.publicKey })
+      .signers([beneficiary])
+      .rpc();
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        SystemProgram.transfer({
+          fromPubkey: wallet.publicKey,
+          toPubkey: treasury,
+          lamports: LAMPORTS_PER_SOL,
+        })
+      )
+    );
+  });
+
+  it("queues a router's deposit without touching the account", async () => {
+    const vaultBefore = await connection.getBalance(vault);
+    const before = await psol.account.privacyAccount.fetch(privacyAccount);
+    const expected = await nextCommitment();
+    await caller.methods
+      .routeDeposit(amount, encryptedAmount, proof)
+      .accountsStrict({ psol: psolAccounts(), user: wallet.publicKey })
+      .rpc();
+
+    const pending = await psol.account.pendingDeposits.fetch(pendingDeposits);
+    assert.deepEqual(pending.commitment, expected);
+    assert.equal(pending.amount.toString(), amount.toString());
+    const account = await psol.account.privacyAccount.fetch(privacyAccount);
+    assert.deepEqual(account.commitment, before.commitment);
+    assert.deepEqual(account.encryptedBalance, before.encryptedBalance);
+    assert.equal(account.totalDeposits.toNumber(), 0);
+    assert.equal(await connection.getBalance(vault), vaultBefore + amount.toNumber());
+  });
+
+  it("lets a program PDA fund the deposit", async () => {
+    const treasuryBefore = await connection.getBalance(treasury);
+    await caller.methods
+      .payFromTreasury(amount, encryptedAmount, proof)
+      .accountsStrict({ psol: psolAccounts(), treasury })
+      .rpc();
+
+    const pending = await psol.account.pendingDeposits.fetch(pendingDeposits);
+    assert.equal(pending.amount.toString(), amount.muln(2).toString());
+    assert.equal(await connection.getBalance(treasury), treasuryBefore - amount.toNumber());
+  });
+
+  it("lets only the owner claim pending deposits", async () => {
+    const other = Keypair.generate();
+    await assert.isRejected(
+      psol.methods
+        .claimPendingDeposits()
+        .accountsPartial({ privacyAccount, pendingDeposits, owner: other.publicKey })
+        .signers([other])
+        .rpc(),
+      /ConstraintSeeds/
+    );
+
+    const { commitment } = await psol.account.privacyAccount.fetch(privacyAccount);
+    await psol.methods
+      .claimPendingDeposits()
+      .accountsPartial({ privacyAccount, pendingDeposits, owner: beneficiary.publicKey })
+      .signers([beneficiary])
+      .rpc();
+    const account = await psol.account.privacyAccount.fetch(privacyAccount);
+    assert.equal(account.totalDeposits.toString(), amount.muln(2).toString());
+    assert.deepEqual(account.commitment, commit(amount.muln(2), commitment));
+    const pending = await psol.account.pendingDeposits.fetch(pendingDeposits);
+    assert.equal(pending.amount.toNumber(), 0);
+
+    await assert.isRejected(
+      psol.methods
+        .claimPendingDeposits()
+        .accountsPartial({ privacyAccount, pendingDeposits, owner: beneficiary.publicKey })
+        .signers([beneficiary])
+        .rpc(),
+      /NoPendingDeposits/
+    );
+  });
+
+  it("rejects a commitment computed from a stale head", async () => {
+    const { psolProgram, ...accounts } = psolAccounts();
+    await assert.isRejected(
+      psol.methods
+        .depositFor(amount, Array(32).fill(0), encryptedAmount, proof, null)
+        .accountsPartial({ ...accounts, depositor: wallet.publicKey })
+        .rpc(),
+      /CommitmentMismatch/
+    );
+  });
+
+  it("admits only allowlisted callers under ApprovedCallersOnly", async () => {
+    const route = () =>
+      caller.methods
+        .routeDeposit(amount, encryptedAmount, proof)
+        .accountsStrict({
+          psol: { ...psolAccounts(), callerAllowlist },
+          user: wallet.publicKey,
+        })
+        .rpc();
+
+    await psol.methods
+      .adminSetCallPolicy(0b10)
+      .accountsPartial({ pool, admin: wallet.publicKey })
+      .rpc();
+    if ((await connection.getAccountInfo(callerAllowlist)) === null) {
+      await psol.methods
+        .initCallerAllowlist()
+        .accountsPartial({ pool, callerAllowlist, admin: wallet.publicKey })
+        .rpc();
+    }
+    await assert.isRejected(route(), /UnapprovedCaller/);
+
+    await psol.methods
+      .updateCallerAllowlist(caller.programId, true)
+      .accountsPartial({ pool, callerAllowlist, admin: wallet.publicKey })
+      .rpc();
+    await route();
+
+    await psol.methods
+      .adminSetCallPolicy(0)
+      .accountsPartial({ pool, admin: wallet.publicKey })
+      .rpc();
+  });
+});
@@ -0,0 +1,79 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Psol } from "../target/types/psol";
+import { assert } from "chai";
+
+// psol_token routes yield through its wrapper's privacy account, so deposit_rewards may only
+// credit the payer's own account.
+describe("deposit_rewards", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const psol = anchor.workspace.Psol as Program<Psol>;
+  const connection = provider.connection;
+  const wallet = provider.wallet as anchor.Wallet;
+  const { PublicKey, Keypair, LAMPORTS_PER_SOL } = anchor.web3;
+
+  const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, psol.programId)[0];
+  const pool = pda(Buffer.from("privacy_pool"));
+  const vault = pda(Buffer.from("vault"), pool.toBuffer());
+
+  const payer = Keypair.generate();
+  const other = Keypair.generate();
+  const privacyAccount = (owner: anchor.web3.PublicKey) =>
+    pda(Buffer.from("privacy"), owner.toBuffer());
+  const amount = new BN(LAMPORTS_PER_SOL / 10);
+
+  const depositRewards = (beneficiary: anchor.web3.PublicKey | null) =>
+    psol.methods
+      .depositRewards(amount)
+      .accountsPartial({ pool, vault, payer: payer.publicKey, beneficiary })
+      .signers([payer])
+      .rpc();
+
+  before(async () => {
+    if ((await connection.getAccountInfo(pool)) === null) {
+      await psol.methods
+        .initializePool(0, new BN(216_000), { accounts: {} })
+        .accountsPartial({
+          pool,
+          vault,
+          commitmentTree: null,
+          nullifierTree: null,
+          admin: wallet.publicKey,
+        })
+        .rpc();
+    }
+    for (const owner of [payer, other]) {
+      await connection.confirmTransaction(
+        await connection.requestAirdrop(owner.publicKey, LAMPORTS_PER_SOL)
+      );
+      await psol.methods
+        .initPrivacyAccount(Array(32).fill(2), Array(32).fill(3))
+        .accountsPartial({
+          privacyAccount: privacyAccount(owner.publicKey),
+          owner: owner.publicKey,
+        })
+        .signers([owner])
+        .rpc();
+    }
+  });
+
+  it("rejects crediting another owner's account", async () => {
+    await assert.isRejected(depositRewards(privacyAccount(other.publicKey)), /Unauthorized/);
+  });
+
+  it("credits the payer's own account", async () => {
+    await depositRewards(privacyAccount(payer.publicKey));
+    const { totalDeposits } = await psol.account.privacyAccount.fetch(
+      privacyAccount(payer.publicKey)
+    );
+    assert.equal(totalDeposits.toString(), amount.toString());
+  });
+
+  it("locks the yield without crediting anyone when no account is passed", async () => {
+    const { totalLocked } = await psol.account.privacyPool.fetch(pool);
+    await depositRewards(null);
+    const after = await psol.account.privacyPool.fetch(pool);
+    assert.equal(after.totalLocked.toString(), totalLocked.add(amount).toString());
+  });
+});
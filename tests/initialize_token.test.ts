@@ -0,0 +1,66 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { createMint } from "@solana/spl-token";
+import { Psol } from "../target/types/psol";
+import { PsolToken } from "../target/types/psol_token";
+import { assert } from "chai";
+
+// The config PDA is seeded by pool alone, so only the pool admin may create it.
+describe("initialize_token", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const psol = anchor.workspace.Psol as Program<Psol>;
+  const psolToken = anchor.workspace.PsolToken as Program<PsolToken>;
+  const connection = provider.connection;
+  const wallet = provider.wallet as anchor.Wallet;
+  const { PublicKey, Keypair, LAMPORTS_PER_SOL } = anchor.web3;
+
+  const pda = (programId: anchor.web3.PublicKey, ...seeds: Buffer[]) =>
+    PublicKey.findProgramAddressSync(seeds, programId)[0];
+  const pool = pda(psol.programId, Buffer.from("privacy_pool"));
+  const vault = pda(psol.programId, Buffer.from("vault"), pool.toBuffer());
+  const config = pda(psolToken.programId, Buffer.from("psol_config"), pool.toBuffer());
+  const mintAuthority = pda(psolToken.programId, Buffer.from("psol_mint_auth"), pool.toBuffer());
+
+  const intruder = Keypair.generate();
+
+  before(async () => {
+    if ((await connection.getAccountInfo(pool)) === null) {
+      await psol.methods
+        .initializePool(0, new BN(216_000), { accounts: {} })
+        .accountsPartial({
+          pool,
+          vault,
+          commitmentTree: null,
+          nullifierTree: null,
+          admin: wallet.publicKey,
+        })
+        .rpc();
+    }
+    await connection.confirmTransaction(
+      await connection.requestAirdrop(intruder.publicKey, LAMPORTS_PER_SOL)
+    );
+  });
+
+  it("rejects a signer other than the pool admin", async function () {
+    if ((await connection.getAccountInfo(config)) !== null) {
+      this.skip();
+    }
+    const mint = await createMint(connection, intruder, intruder.publicKey, intruder.publicKey, 9);
+    await assert.isRejected(
+      psolToken.methods
+        .initializeToken()
+        .accountsPartial({
+          pool,
+          config,
+          psolMint: mint,
+          mintAuthority,
+          admin: intruder.publicKey,
+        })
+        .signers([intruder])
+        .rpc(),
+      /Unauthorized/
+    );
+    assert.isNull(await connection.getAccountInfo(config));
+  });
+});
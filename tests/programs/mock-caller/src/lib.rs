@@ -0,0 +1,128 @@
+//! Test-only program that deposits into psol through `psol-cpi`, standing in for the
+//! routers and payroll programs that integrate with the pool.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use psol_cpi::psol::program::Psol;
+use psol_cpi::psol::state::{PendingDeposits, PrivacyAccount};
+use psol_cpi::{DepositFor, DepositForArgs};
+
+declare_id!("93vcf5UBQ8TVEvjsThpzZY8drmfXKPZKSneRw5ePbhTs");
+
+pub const TREASURY_SEED: &[u8] = b"treasury";
+
+#[program]
+pub mod mock_caller {
+    use super::*;
+
+    /// Router flow: the user signs and pays, and the deposit is credited to any privacy
+    /// account.
+    pub fn route_deposit(
+        ctx: Context<RouteDeposit>,
+        amount: u64,
+        encrypted_amount: [u8; 64],
+        proof: Vec<u8>,
+    ) -> Result<()> {
+        let psol = &ctx.accounts.psol;
+        let cpi_ctx = CpiContext::new(
+            psol.psol_program.to_account_info(),
+            psol.deposit_for(ctx.accounts.user.to_account_info()),
+        );
+        psol_cpi::deposit_for(cpi_ctx, psol.args(amount, encrypted_amount, proof))
+    }
+
+    /// Payroll flow: the program's treasury PDA pays and signs through its seeds.
+    pub fn pay_from_treasury(
+        ctx: Context<PayFromTreasury>,
+        amount: u64,
+        encrypted_amount: [u8; 64],
+        proof: Vec<u8>,
+    ) -> Result<()> {
+        let psol = &ctx.accounts.psol;
+        let bump = [ctx.bumps.treasury];
+        let signer_seeds: &[&[&[u8]]] = &[&[TREASURY_SEED, &bump]];
+        let cpi_ctx = CpiContext::new_with_signer(
+            psol.psol_program.to_account_info(),
+            psol.deposit_for(ctx.accounts.treasury.to_account_info()),
+            signer_seeds,
+        );
+        psol_cpi::deposit_for(cpi_ctx, psol.args(amount, encrypted_amount, proof))
+    }
+}
+
+/// psol accounts shared by both flows; psol validates them again in the CPI.
+#[derive(Accounts)]
+pub struct PsolAccounts<'info> {
+    pub psol_program: Program<'info, Psol>,
+    /// CHECK: validated in CPI
+    #[account(mut)]
+    pub pool: UncheckedAccount<'info>,
+    /// CHECK: validated in CPI
+    #[account(mut)]
+    pub vault: UncheckedAccount<'info>,
+    /// CHECK: validated in CPI; set for `Accounts` pools
+    #[account(mut)]
+    pub commitment_registry: Option<UncheckedAccount<'info>>,
+    /// CHECK: validated in CPI; set for `Compressed` pools
+    #[account(mut)]
+    pub commitment_tree: Option<UncheckedAccount<'info>>,
+    #[account(mut)]
+    pub privacy_account: Account<'info, PrivacyAccount>,
+    /// CHECK: instructions sysvar, read by psol's call policy checks
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    /// CHECK: validated in CPI
+    pub caller_allowlist: Option<UncheckedAccount<'info>>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: validated in CPI, which creates it on the first deposit
+    #[account(mut)]
+    pub pending_deposits: UncheckedAccount<'info>,
+}
+
+impl<'info> PsolAccounts<'info> {
+    fn deposit_for(&self, depositor: AccountInfo<'info>) -> DepositFor<'info> {
+        DepositFor {
+            pool: self.pool.to_account_info(),
+            vault: self.vault.to_account_info(),
+            commitment_registry: self.commitment_registry.as_ref().map(|a| a.to_account_info()),
+            commitment_tree: self.commitment_tree.as_ref().map(|a| a.to_account_info()),
+            privacy_account: self.privacy_account.to_account_info(),
+            depositor,
+            instructions_sysvar: self.instructions_sysvar.to_account_info(),
+            caller_allowlist: self.caller_allowlist.as_ref().map(|a| a.to_account_info()),
+            system_program: self.system_program.to_account_info(),
+            pending_deposits: self.pending_deposits.to_account_info(),
+        }
+    }
+
+    fn args(&self, amount: u64, encrypted_amount: [u8; 64], proof: Vec<u8>) -> DepositForArgs {
+        // Absent until the first deposit for this account.
+        let data = self.pending_deposits.data.borrow();
+        let pending = PendingDeposits::try_deserialize(&mut &data[..]).ok();
+        DepositForArgs {
+            amount,
+            commitment: psol_cpi::next_commitment(
+                &self.privacy_account.key(),
+                pending.as_ref(),
+                amount,
+            ),
+            encrypted_amount,
+            proof,
+            memo: None,
+        }
+    }
+}
+
+#[derive(Accounts)]
+pub struct RouteDeposit<'info> {
+    pub psol: PsolAccounts<'info>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PayFromTreasury<'info> {
+    pub psol: PsolAccounts<'info>,
+    #[account(mut, seeds = [TREASURY_SEED], bump)]
+    pub treasury: SystemAccount<'info>,
+}
@@ -0,0 +1,146 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Psol } from "../target/types/psol";
+import { assert } from "chai";
+
+// Runs against the local test validator only: stake is delegated to the validator's own
+// vote account, and Anchor.toml shortens epochs so deactivation completes in seconds.
+describe("stake strategy", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.Psol as Program<Psol>;
+  const connection = provider.connection;
+  const wallet = provider.wallet as anchor.Wallet;
+  const { PublicKey, SystemProgram, StakeProgram, LAMPORTS_PER_SOL } = anchor.web3;
+
+  const pda = (...seeds: Buffer[]) =>
+    PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+  const pool = pda(Buffer.from("privacy_pool"));
+  const vault = pda(Buffer.from("vault"), pool.toBuffer());
+  const stakeStrategy = pda(Buffer.from("stake_strategy"), pool.toBuffer());
+  const mainStake = pda(Buffer.from("stake"), pool.toBuffer());
+  const transientStake = pda(Buffer.from("transient_stake"), pool.toBuffer());
+  const stakeHistory = new PublicKey("SysvarStakeHistory1111111111111111111111111");
+  const stakeConfig = new PublicKey("StakeConfig11111111111111111111111111111111");
+
+  let voteAccount: anchor.web3.PublicKey;
+
+  const crankAccounts = () => ({
+    pool,
+    stakeStrategy,
+    vault,
+    mainStake,
+    transientStake,
+    voteAccount,
+    clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+    stakeHistory,
+    stakeConfig,
+    stakeProgram: StakeProgram.programId,
+    systemProgram: SystemProgram.programId,
+  });
+
+  before(async () => {
+    voteAccount = new PublicKey((await connection.getVoteAccounts()).current[0].votePubkey);
+
+    if ((await connection.getAccountInfo(pool)) === null) {
+      await program.methods
+        .initializePool(0, new BN(216_000), { accounts: {} })
+        .accountsPartial({
+          pool,
+          vault,
+          commitmentTree: null,
+          nullifierTree: null,
+          admin: wallet.publicKey,
+        })
+        .rpc();
+    }
+    await program.methods
+      .depositRewards(new BN(10 * LAMPORTS_PER_SOL))
+      .accountsPartial({ pool, vault, payer: wallet.publicKey, beneficiary: null })
+      .rpc();
+  });
+
+  it("delegates up to the target and keeps the buffer liquid", async () => {
+    await program.methods
+      .initStakeStrategy(5_000, new BN(LAMPORTS_PER_SOL), wallet.publicKey)
+      .accountsPartial({
+        pool,
+        stakeStrategy,
+        mainStake,
+        transientStake,
+        voteAccount,
+        admin: wallet.publicKey,
+      })
+      .rpc();
+    await program.methods.rebalanceStake().accountsPartial(crankAccounts()).rpc();
+
+    const totalLocked = (await program.account.privacyPool.fetch(pool)).totalLocked;
+    const strategy = await program.account.stakeStrategy.fetch(stakeStrategy);
+    assert.equal(strategy.staked.toString(), totalLocked.divn(2).toString());
+    assert.deepEqual(strategy.transientState, { idle: {} });
+    const stake = await connection.getAccountInfo(mainStake);
+    assert.isTrue(stake.owner.equals(StakeProgram.programId));
+
+    // Already at target.
+    await assert.isRejected(
+      program.methods.rebalanceStake().accountsPartial(crankAccounts()).rpc(),
+      /NothingToRebalance/
+    );
+  });
+
+  it("unwinds stake through the transient account when the target drops", async () => {
+    const vaultBefore = await connection.getBalance(vault);
+    await program.methods
+      .setStakeStrategy(0, new BN(LAMPORTS_PER_SOL), wallet.publicKey)
+      .accountsPartial({ pool, stakeStrategy, admin: wallet.publicKey })
+      .rpc();
+    await program.methods.rebalanceStake().accountsPartial(crankAccounts()).rpc();
+
+    let strategy = await program.account.stakeStrategy.fetch(stakeStrategy);
+    assert.equal(strategy.staked.toNumber(), 0);
+    assert.deepEqual(strategy.transientState, { deactivating: {} });
+
+    // Deactivation completes at the next epoch boundary.
+    const { epoch } = await connection.getEpochInfo();
+    while ((await connection.getEpochInfo()).epoch === epoch) {
+      await new Promise((resolve) => setTimeout(resolve, 1_000));
+    }
+    await program.methods
+      .settleStake()
+      .accountsPartial({
+        pool,
+        stakeStrategy,
+        vault,
+        mainStake,
+        transientStake,
+        clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+        stakeHistory,
+        stakeProgram: StakeProgram.programId,
+      })
+      .rpc();
+
+    strategy = await program.account.stakeStrategy.fetch(stakeStrategy);
+    assert.deepEqual(strategy.transientState, { idle: {} });
+    assert.equal(strategy.transient.toNumber(), 0);
+    assert.isAbove(await connection.getBalance(vault), vaultBefore);
+  });
+
+  it("lets only the rewards authority claim", async () => {
+    const other = anchor.web3.Keypair.generate();
+    await assert.isRejected(
+      program.methods
+        .claimStakeRewards()
+        .accountsPartial({ stakeStrategy, rewardsAuthority: other.publicKey, beneficiary: null })
+        .signers([other])
+        .rpc(),
+      /Unauthorized/
+    );
+    await program.methods
+      .claimStakeRewards()
+      .accountsPartial({ stakeStrategy, rewardsAuthority: wallet.publicKey, beneficiary: null })
+      .rpc();
+    const strategy = await program.account.stakeStrategy.fetch(stakeStrategy);
+    assert.equal(strategy.unclaimedRewards.toNumber(), 0);
+  });
+});
@@ -0,0 +1,112 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Psol } from "../target/types/psol";
+import { assert } from "chai";
+
+// Stealth accounts live at ["stealth", pool, one_time_address], apart from the
+// ["privacy", owner] accounts of init_privacy_account, so a sender that names someone's
+// wallet as the one-time address can neither squat on nor overwrite their account.
+describe("transfer_stealth", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const psol = anchor.workspace.Psol as Program<Psol>;
+  const connection = provider.connection;
+  const wallet = provider.wallet as anchor.Wallet;
+  const { PublicKey, Keypair, LAMPORTS_PER_SOL } = anchor.web3;
+
+  const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, psol.programId)[0];
+  const pool = pda(Buffer.from("privacy_pool"));
+  const vault = pda(Buffer.from("vault"), pool.toBuffer());
+  const privacyAccount = (owner: anchor.web3.PublicKey) =>
+    pda(Buffer.from("privacy"), owner.toBuffer());
+  const stealthAccount = (oneTimeAddress: anchor.web3.PublicKey) =>
+    pda(Buffer.from("stealth"), pool.toBuffer(), oneTimeAddress.toBuffer());
+
+  const sender = Keypair.generate();
+  const victim = Keypair.generate();
+  const proof = Buffer.alloc(64, 1);
+
+  const sendStealth = (
+    oneTimeAddress: anchor.web3.PublicKey,
+    recipientAccount: anchor.web3.PublicKey
+  ) =>
+    psol.methods
+      .transferStealth(
+        oneTimeAddress,
+        Array(32).fill(9),
+        7,
+        Array(32).fill(8),
+        Array(64).fill(1),
+        proof,
+        null,
+        null
+      )
+      .accountsPartial({
+        pool,
+        senderAccount: privacyAccount(sender.publicKey),
+        recipientAccount,
+        sender: sender.publicKey,
+      })
+      .signers([sender])
+      .rpc();
+
+  before(async () => {
+    if ((await connection.getAccountInfo(pool)) === null) {
+      await psol.methods
+        .initializePool(0, new BN(216_000), { accounts: {} })
+        .accountsPartial({
+          pool,
+          vault,
+          commitmentTree: null,
+          nullifierTree: null,
+          admin: wallet.publicKey,
+        })
+        .rpc();
+    }
+    await connection.confirmTransaction(
+      await connection.requestAirdrop(sender.publicKey, LAMPORTS_PER_SOL)
+    );
+    await psol.methods
+      .initPrivacyAccount(Array(32).fill(2), Array(32).fill(3))
+      .accountsPartial({
+        privacyAccount: privacyAccount(sender.publicKey),
+        owner: sender.publicKey,
+      })
+      .signers([sender])
+      .rpc();
+  });
+
+  it("creates the recipient in the stealth namespace", async () => {
+    const oneTimeAddress = Keypair.generate().publicKey;
+    await sendStealth(oneTimeAddress, stealthAccount(oneTimeAddress));
+
+    const account = await psol.account.privacyAccount.fetch(stealthAccount(oneTimeAddress));
+    assert.equal(account.owner.toBase58(), oneTimeAddress.toBase58());
+    assert.isNull(await connection.getAccountInfo(privacyAccount(oneTimeAddress)));
+  });
+
+  it("cannot create or overwrite a normal privacy account", async () => {
+    // Before the victim opens an account, a stealth send naming them cannot squat on it.
+    await assert.isRejected(sendStealth(victim.publicKey, privacyAccount(victim.publicKey)));
+    await sendStealth(victim.publicKey, stealthAccount(victim.publicKey));
+    assert.isNull(await connection.getAccountInfo(privacyAccount(victim.publicKey)));
+
+    await connection.confirmTransaction(
+      await connection.requestAirdrop(victim.publicKey, LAMPORTS_PER_SOL)
+    );
+    await psol.methods
+      .initPrivacyAccount(Array(32).fill(4), Array(32).fill(5))
+      .accountsPartial({
+        privacyAccount: privacyAccount(victim.publicKey),
+        owner: victim.publicKey,
+      })
+      .signers([victim])
+      .rpc();
+
+    // Once it exists, a stealth send cannot target it either.
+    await assert.isRejected(sendStealth(victim.publicKey, privacyAccount(victim.publicKey)));
+    const account = await psol.account.privacyAccount.fetch(privacyAccount(victim.publicKey));
+    assert.deepEqual(account.viewKey, Array(32).fill(5));
+    assert.equal(account.transfersIn.toNumber(), 0);
+  });
+});
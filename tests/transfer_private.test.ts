@@ -0,0 +1,80 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Psol } from "../target/types/psol";
+import { assert } from "chai";
+
+describe("transfer_private", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const psol = anchor.workspace.Psol as Program<Psol>;
+  const connection = provider.connection;
+  const wallet = provider.wallet as anchor.Wallet;
+  const { PublicKey, Keypair, LAMPORTS_PER_SOL } = anchor.web3;
+
+  const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, psol.programId)[0];
+  const pool = pda(Buffer.from("privacy_pool"));
+  const vault = pda(Buffer.from("vault"), pool.toBuffer());
+  const privacyAccount = (owner: anchor.web3.PublicKey) =>
+    pda(Buffer.from("privacy"), owner.toBuffer());
+
+  const alice = Keypair.generate();
+  const bob = Keypair.generate();
+
+  const transfer = (recipient: anchor.web3.PublicKey) =>
+    psol.methods
+      .transferPrivate(Array(64).fill(1), Buffer.alloc(64, 1), null, null)
+      .accountsPartial({
+        pool,
+        senderAccount: privacyAccount(alice.publicKey),
+        recipientAccount: privacyAccount(recipient),
+        sender: alice.publicKey,
+        recipient,
+      })
+      .signers([alice])
+      .rpc();
+
+  before(async () => {
+    if ((await connection.getAccountInfo(pool)) === null) {
+      await psol.methods
+        .initializePool(0, new BN(216_000), { accounts: {} })
+        .accountsPartial({
+          pool,
+          vault,
+          commitmentTree: null,
+          nullifierTree: null,
+          admin: wallet.publicKey,
+        })
+        .rpc();
+    }
+    for (const owner of [alice, bob]) {
+      await connection.confirmTransaction(
+        await connection.requestAirdrop(owner.publicKey, LAMPORTS_PER_SOL)
+      );
+      await psol.methods
+        .initPrivacyAccount(Array(32).fill(2), Array(32).fill(3))
+        .accountsPartial({
+          privacyAccount: privacyAccount(owner.publicKey),
+          owner: owner.publicKey,
+        })
+        .signers([owner])
+        .rpc();
+    }
+  });
+
+  it("rejects transfers to the sender's own account", async () => {
+    await assert.isRejected(transfer(alice.publicKey), /SelfTransfer/);
+    const account = await psol.account.privacyAccount.fetch(privacyAccount(alice.publicKey));
+    assert.equal(account.nonce.toNumber(), 0);
+    assert.equal(account.transfersIn.toNumber(), 0);
+  });
+
+  it("credits another account", async () => {
+    await transfer(bob.publicKey);
+    const sender = await psol.account.privacyAccount.fetch(privacyAccount(alice.publicKey));
+    const recipient = await psol.account.privacyAccount.fetch(privacyAccount(bob.publicKey));
+    assert.equal(sender.transfersOut.toNumber(), 1);
+    assert.equal(recipient.transfersIn.toNumber(), 1);
+    // Received amounts are encrypted, so they do not raise the withdrawable deposits.
+    assert.equal(recipient.totalDeposits.toNumber(), 0);
+  });
+});
@@ -0,0 +1,115 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { createHash } from "crypto";
+import { Psol } from "../target/types/psol";
+import { assert } from "chai";
+
+// archive_nullifier refunds NullifierSet rent to the recorded payer, so a withdrawal
+// made by a PDA on a user's behalf (psol-token swap_to_sol) names the user instead.
+describe("withdraw_private", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const psol = anchor.workspace.Psol as Program<Psol>;
+  const connection = provider.connection;
+  const wallet = provider.wallet as anchor.Wallet;
+  const { PublicKey, Keypair, LAMPORTS_PER_SOL } = anchor.web3;
+
+  const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, psol.programId)[0];
+  const pool = pda(Buffer.from("privacy_pool"));
+  const vault = pda(Buffer.from("vault"), pool.toBuffer());
+  const commitmentRegistry = pda(Buffer.from("commitment"), pool.toBuffer());
+  const nullifierArchive = pda(Buffer.from("nullifier_archive"), pool.toBuffer());
+
+  const owner = Keypair.generate();
+  const privacyAccount = pda(Buffer.from("privacy"), owner.publicKey.toBuffer());
+  const deposit = new BN(LAMPORTS_PER_SOL / 10);
+
+  const u64 = (n: BN) => n.toArrayLike(Buffer, "le", 8);
+  const sha256 = (...parts: Buffer[]) => {
+    const hash = createHash("sha256");
+    parts.forEach((p) => hash.update(p));
+    return hash.digest();
+  };
+
+  const withdraw = async (rentRefund: anchor.web3.PublicKey | null) => {
+    const nullifier = Keypair.generate().publicKey.toBuffer();
+    const nullifierSet = pda(Buffer.from("nullifier"), pool.toBuffer(), nullifier);
+    await psol.methods
+      .withdrawPrivate(new BN(1_000), Array.from(nullifier), Buffer.alloc(64, 1), null)
+      .accountsPartial({
+        pool,
+        vault,
+        nullifierSet,
+        nullifierArchive,
+        nullifierTree: null,
+        proofBuffer: null,
+        privacyAccount,
+        owner: owner.publicKey,
+        recipient: owner.publicKey,
+        rentRefund,
+        callerAllowlist: null,
+      })
+      .signers([owner])
+      .rpc();
+    return psol.account.nullifierSet.fetch(nullifierSet);
+  };
+
+  before(async () => {
+    if ((await connection.getAccountInfo(pool)) === null) {
+      await psol.methods
+        .initializePool(0, new BN(216_000), { accounts: {} })
+        .accountsPartial({
+          pool,
+          vault,
+          commitmentTree: null,
+          nullifierTree: null,
+          admin: wallet.publicKey,
+        })
+        .rpc();
+    }
+    await connection.confirmTransaction(
+      await connection.requestAirdrop(owner.publicKey, LAMPORTS_PER_SOL)
+    );
+    await psol.methods
+      .initPrivacyAccount(Array(32).fill(2), Array(32).fill(3))
+      .accountsPartial({ privacyAccount, owner: owner.publicKey })
+      .signers([owner])
+      .rpc();
+    const pendingDeposits = pda(Buffer.from("pending_deposits"), privacyAccount.toBuffer());
+    await psol.methods
+      .depositFor(
+        deposit,
+        Array.from(sha256(u64(deposit), privacyAccount.toBuffer())),
+        Array(64).fill(1),
+        Buffer.alloc(64, 1),
+        null
+      )
+      .accountsPartial({
+        pool,
+        vault,
+        commitmentRegistry,
+        commitmentTree: null,
+        privacyAccount,
+        depositor: wallet.publicKey,
+        callerAllowlist: null,
+        pendingDeposits,
+      })
+      .rpc();
+    await psol.methods
+      .claimPendingDeposits()
+      .accountsPartial({ privacyAccount, pendingDeposits, owner: owner.publicKey })
+      .signers([owner])
+      .rpc();
+  });
+
+  it("records the owner as the nullifier rent payer by default", async () => {
+    const record = await withdraw(null);
+    assert.equal(record.payer.toBase58(), owner.publicKey.toBase58());
+  });
+
+  it("records the rent_refund account when one is passed", async () => {
+    const user = Keypair.generate().publicKey;
+    const record = await withdraw(user);
+    assert.equal(record.payer.toBase58(), user.toBase58());
+  });
+});